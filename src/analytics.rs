@@ -0,0 +1,191 @@
+use crate::types::*;
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use std::collections::{BTreeMap, HashMap};
+
+/// Reconstruct a history report by replaying the audit trail.
+///
+/// `events` must be the full trail in chronological order (not just the
+/// window), so that the status of every issue is known at each bucket end.
+pub fn compute_history(events: &[Event], since: DateTime<Utc>, until: DateTime<Utc>, bucket: Bucket) -> History {
+    let starts = bucket_starts(since, until, bucket);
+    let mut buckets: Vec<HistoryBucket> = starts.iter()
+        .map(|start| HistoryBucket {
+            start: *start,
+            opened: 0,
+            closed: 0,
+            status_counts: BTreeMap::new(),
+        })
+        .collect();
+
+    let mut statuses: HashMap<&str, String> = HashMap::new();
+    let mut started_at: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    let mut cycle_hours = Vec::new();
+    let mut throughput_by_actor: BTreeMap<String, i32> = BTreeMap::new();
+
+    let mut events = events.iter().peekable();
+    for (idx, start) in starts.iter().enumerate() {
+        let end = bucket_end(*start, bucket).min(until);
+
+        while let Some(event) = events.next_if(|e| e.created_at < end) {
+            let in_window = event.created_at >= since;
+            let issue_id = event.issue_id.as_str();
+            let previous = statuses.get(issue_id).cloned();
+
+            let next = match event.event_type {
+                EventType::Created => {
+                    if in_window {
+                        buckets[idx].opened += 1;
+                    }
+                    Some(event.new_value.clone().unwrap_or_else(|| Status::Open.to_string()))
+                }
+                EventType::StatusChanged => event.new_value.clone(),
+                EventType::Closed => Some(Status::Closed.to_string()),
+                EventType::Reopened => Some(Status::Open.to_string()),
+                _ => None,
+            };
+
+            let Some(next) = next else { continue };
+
+            if next == Status::InProgress.to_string() {
+                started_at.entry(issue_id).or_insert(event.created_at);
+            }
+
+            let was_closed = previous.as_deref() == Some("closed");
+            if next == "closed" && !was_closed && in_window {
+                buckets[idx].closed += 1;
+                *throughput_by_actor.entry(event.actor.clone()).or_insert(0) += 1;
+                if let Some(started) = started_at.get(issue_id) {
+                    let hours = event.created_at.signed_duration_since(*started).num_seconds() as f64 / 3600.0;
+                    cycle_hours.push(hours);
+                }
+            }
+
+            statuses.insert(issue_id, next);
+        }
+
+        for status in statuses.values() {
+            *buckets[idx].status_counts.entry(status.clone()).or_insert(0) += 1;
+        }
+    }
+
+    cycle_hours.sort_by(|a, b| a.total_cmp(b));
+
+    History {
+        since,
+        until,
+        bucket,
+        buckets,
+        cycle_time: CycleTimeStats {
+            count: cycle_hours.len(),
+            p50_hours: percentile(&cycle_hours, 50.0),
+            p75_hours: percentile(&cycle_hours, 75.0),
+            p90_hours: percentile(&cycle_hours, 90.0),
+            p95_hours: percentile(&cycle_hours, 95.0),
+        },
+        throughput_by_actor,
+    }
+}
+
+/// Summarize events per actor.
+///
+/// `events` must be the full trail in chronological order so claims made
+/// before `since` can be matched with closes inside the window.
+pub fn compute_activity(events: &[Event], since: Option<DateTime<Utc>>) -> BTreeMap<String, ActorActivity> {
+    let mut activity: BTreeMap<String, ActorActivity> = BTreeMap::new();
+    let mut closed: HashMap<&str, bool> = HashMap::new();
+    // Latest claim per issue
+    let mut claims: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    let mut claim_hours: HashMap<String, Vec<f64>> = HashMap::new();
+
+    for event in events {
+        let in_window = since.is_none_or(|since| event.created_at >= since);
+        let issue_id = event.issue_id.as_str();
+
+        let next_status = match event.event_type {
+            EventType::StatusChanged => event.new_value.clone(),
+            EventType::Closed => Some(Status::Closed.to_string()),
+            EventType::Reopened => Some(Status::Open.to_string()),
+            _ => None,
+        };
+
+        if in_window {
+            let entry = activity.entry(event.actor.clone()).or_insert_with(|| ActorActivity {
+                actor: event.actor.clone(),
+                ..Default::default()
+            });
+            match event.event_type {
+                EventType::Created => entry.created += 1,
+                EventType::Commented => entry.comments += 1,
+                EventType::DependencyAdded => entry.dependencies_added += 1,
+                _ => {}
+            }
+        }
+
+        let Some(next) = next_status else { continue };
+        let was_closed = closed.get(issue_id).copied().unwrap_or(false);
+
+        if next == Status::InProgress.to_string() {
+            claims.insert(issue_id, event.created_at);
+            if in_window {
+                if let Some(entry) = activity.get_mut(&event.actor) {
+                    entry.claimed += 1;
+                }
+            }
+        } else if next == Status::Closed.to_string() && !was_closed && in_window {
+            if let Some(entry) = activity.get_mut(&event.actor) {
+                entry.closed += 1;
+            }
+            if let Some(claimed_at) = claims.get(issue_id) {
+                let hours = event.created_at.signed_duration_since(*claimed_at).num_seconds() as f64 / 3600.0;
+                claim_hours.entry(event.actor.clone()).or_default().push(hours);
+            }
+        }
+
+        closed.insert(issue_id, next == Status::Closed.to_string());
+    }
+
+    for (actor, mut hours) in claim_hours {
+        hours.sort_by(|a, b| a.total_cmp(b));
+        if let Some(entry) = activity.get_mut(&actor) {
+            entry.median_claim_to_close_hours = percentile(&hours, 50.0);
+        }
+    }
+
+    activity
+}
+
+/// Start of the bucket containing `at` (midnight UTC; weeks start on Monday)
+pub fn bucket_start(at: DateTime<Utc>, bucket: Bucket) -> DateTime<Utc> {
+    let date = at.date_naive();
+    let date = match bucket {
+        Bucket::Day => date,
+        Bucket::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+    };
+    Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).expect("midnight is a valid time"))
+}
+
+fn bucket_end(start: DateTime<Utc>, bucket: Bucket) -> DateTime<Utc> {
+    match bucket {
+        Bucket::Day => start + Duration::days(1),
+        Bucket::Week => start + Duration::weeks(1),
+    }
+}
+
+fn bucket_starts(since: DateTime<Utc>, until: DateTime<Utc>, bucket: Bucket) -> Vec<DateTime<Utc>> {
+    let mut starts = Vec::new();
+    let mut start = bucket_start(since, bucket);
+    while start < until {
+        starts.push(start);
+        start = bucket_end(start, bucket);
+    }
+    starts
+}
+
+/// Nearest-rank percentile of an ascending slice
+fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
@@ -0,0 +1,71 @@
+//! Consistent copies of SQLite databases, taken with SQLite's online backup API
+//! so they are safe to make while other agents are writing
+
+use crate::error::Result;
+use chrono::Utc;
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use std::path::{Path, PathBuf};
+
+/// How many automatic backups of each kind (pre-migration, pre-import, ...) are kept
+pub const KEEP_AUTOMATIC: usize = 5;
+
+/// Copy the database at `db_path` to `dest`, replacing whatever is there
+pub fn backup_database(db_path: &Path, dest: &Path) -> Result<()> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Open without SQLITE_OPEN_CREATE, so a missing database is an error rather than an empty backup
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX)?;
+    conn.busy_timeout(crate::utils::busy_timeout())?;
+    conn.backup(DatabaseName::Main, dest, None)?;
+    Ok(())
+}
+
+/// File name for a new backup: `<db>-<timestamp>.db`, or `<db>-<kind>-<timestamp>.db`
+/// for automatic ones. Timestamps sort in the order the backups were made.
+pub fn backup_file_name(db_path: &Path, kind: Option<&str>) -> String {
+    let timestamp = Utc::now().format("%Y%m%d-%H%M%S-%3f");
+    match kind {
+        Some(kind) => format!("{}{}.db", automatic_prefix(db_path, kind), timestamp),
+        None => format!("{}-{}.db", db_stem(db_path), timestamp),
+    }
+}
+
+/// Back up the database into the backups directory before a risky operation,
+/// keeping only the newest `KEEP_AUTOMATIC` backups of that kind. Returns the
+/// backup's path, or `None` if there is no database yet.
+pub fn create_automatic(db_path: &Path, kind: &str) -> Result<Option<PathBuf>> {
+    if !db_path.exists() {
+        return Ok(None);
+    }
+
+    let dir = crate::utils::find_backups_dir(db_path);
+    let dest = dir.join(backup_file_name(db_path, Some(kind)));
+    backup_database(db_path, &dest)?;
+
+    let prefix = automatic_prefix(db_path, kind);
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(&dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.starts_with(&prefix) && name.ends_with(".db")
+        })
+        .collect();
+    backups.sort();
+    let excess = backups.len().saturating_sub(KEEP_AUTOMATIC);
+    for old in &backups[..excess] {
+        std::fs::remove_file(old)?;
+    }
+
+    Ok(Some(dest))
+}
+
+fn automatic_prefix(db_path: &Path, kind: &str) -> String {
+    format!("{}-{}-", db_stem(db_path), kind)
+}
+
+fn db_stem(db_path: &Path) -> String {
+    db_path.file_stem().unwrap_or_default().to_string_lossy().to_string()
+}
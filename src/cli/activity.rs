@@ -0,0 +1,80 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::Args;
+use tracer::storage::Storage;
+use tracer::types::*;
+
+#[derive(Args)]
+pub struct ActivityArgs {
+    /// Only show this actor
+    #[arg(long)]
+    pub actor: Option<String>,
+
+    /// Only count events since (YYYY-MM-DD, RFC 3339, or relative like 24h)
+    #[arg(long, value_parser = tracer::utils::parse_date_spec)]
+    pub since: Option<DateTime<Utc>>,
+}
+
+pub fn execute(args: ActivityArgs, storage: &dyn Storage, json: bool) -> Result<()> {
+    // Replay the whole trail so claims made before --since still pair with later closes
+    let events = storage.get_all_events(&EventFilter::default())?;
+    let mut activity = tracer::analytics::compute_activity(&events, args.since);
+
+    let filter = IssueFilter {
+        status: Some(Status::InProgress),
+        ..Default::default()
+    };
+    for issue in storage.search_issues("", &filter)? {
+        if issue.assignee.is_empty() {
+            continue;
+        }
+        activity.entry(issue.assignee.clone())
+            .or_insert_with(|| ActorActivity {
+                actor: issue.assignee.clone(),
+                ..Default::default()
+            })
+            .in_progress
+            .push(issue);
+    }
+
+    let activity: Vec<ActorActivity> = activity.into_values()
+        .filter(|a| args.actor.as_ref().is_none_or(|actor| &a.actor == actor))
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&activity)?);
+        return Ok(());
+    }
+
+    use colored::Colorize;
+
+    if activity.is_empty() {
+        println!("No activity found");
+        return Ok(());
+    }
+
+    match args.since {
+        Some(since) => println!("{} (since {})\n", "Activity".bold(), since.format("%Y-%m-%d %H:%M")),
+        None => println!("{}\n", "Activity".bold()),
+    }
+
+    let now = Utc::now();
+    for a in activity {
+        println!("{}", a.actor.bold().cyan());
+        println!("  Created: {}  Claimed: {}  Closed: {}  Comments: {}  Deps added: {}",
+            a.created, a.claimed, a.closed, a.comments, a.dependencies_added);
+        if let Some(hours) = a.median_claim_to_close_hours {
+            println!("  Median claim → close: {:.1} hours", hours);
+        }
+        if !a.in_progress.is_empty() {
+            println!("  In progress:");
+            for issue in &a.in_progress {
+                let idle_days = now.signed_duration_since(issue.updated_at).num_days();
+                println!("    {} {} {}", issue.id.cyan(), issue.title, format!("(updated {} days ago)", idle_days).dimmed());
+            }
+        }
+        println!();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::Args;
+use std::path::Path;
+use tracer::storage::Storage;
+
+#[derive(Args)]
+pub struct ArchiveArgs {
+    /// Archive issues closed before this date (ISO date, or an age like 90d)
+    #[arg(long, value_parser = tracer::utils::parse_date_spec)]
+    pub closed_before: DateTime<Utc>,
+
+    /// Show what would be archived and compacted without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Archive old closed issues and compact the events before the cutoff. With
+/// `db_path`, the JSONL files are rewritten to match (SQLite databases only).
+pub fn execute(args: ArchiveArgs, storage: &mut Box<dyn Storage>, db_path: Option<&Path>, json: bool) -> Result<()> {
+    // A dry run does the work and rolls it back, so it reports exactly what would happen
+    storage.begin_transaction()?;
+    let result = archive_and_compact(storage, args.closed_before);
+    if result.is_ok() && !args.dry_run {
+        storage.commit_transaction()?;
+    } else {
+        let _ = storage.rollback_transaction();
+    }
+    let (archived, compacted) = result?;
+
+    if let Some(db_path) = db_path.filter(|_| !args.dry_run && !archived.is_empty()) {
+        super::export::export_all(storage, &tracer::find_jsonl_path(db_path))?;
+        super::export::export_archive(storage, &tracer::find_archive_path(db_path))?;
+    }
+
+    if json {
+        let output = serde_json::json!({
+            "archived": archived,
+            "events_removed": compacted,
+            "dry_run": args.dry_run,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    use colored::Colorize;
+
+    let cutoff = args.closed_before.format("%Y-%m-%d");
+    if archived.is_empty() {
+        println!("No issues closed before {} can be archived", cutoff);
+    } else if args.dry_run {
+        println!("Would archive {} issue(s) closed before {}:", archived.len(), cutoff);
+    } else {
+        println!("{} Archived {} issue(s) closed before {}:", "✓".green(), archived.len(), cutoff);
+    }
+    if !archived.is_empty() {
+        println!("  {}", archived.join(", ").dimmed());
+    }
+    let verb = if args.dry_run { "Would remove" } else { "Removed" };
+    println!("{} {} routine event(s) from before {}", verb, compacted, cutoff);
+
+    Ok(())
+}
+
+fn archive_and_compact(storage: &mut Box<dyn Storage>, cutoff: DateTime<Utc>) -> Result<(Vec<String>, usize)> {
+    let archived = storage.archive_issues(cutoff)?;
+    let compacted = storage.compact_events(cutoff)?;
+    Ok((archived, compacted))
+}
@@ -0,0 +1,53 @@
+use anyhow::Result;
+use clap::Args;
+use std::path::{Path, PathBuf};
+use tracer::storage::Backend;
+
+#[derive(Args)]
+pub struct BackupArgs {
+    /// Where to write the backup (default: .trace/backups/<db>-<timestamp>.db)
+    pub path: Option<PathBuf>,
+}
+
+pub fn execute(args: BackupArgs, db_path: &Path, json: bool) -> Result<()> {
+    if Backend::for_path(db_path) != Backend::Sqlite {
+        anyhow::bail!("{} uses the JSONL backend; its files are backed up by committing them to git", db_path.display());
+    }
+    if !db_path.exists() {
+        anyhow::bail!("no database at {} (run `tracer init` first)", db_path.display());
+    }
+
+    let dest = args.path.unwrap_or_else(|| {
+        tracer::find_backups_dir(db_path).join(tracer::backup::backup_file_name(db_path, None))
+    });
+    if dest.exists() && std::fs::canonicalize(&dest)? == std::fs::canonicalize(db_path)? {
+        anyhow::bail!("cannot back up the database onto itself");
+    }
+    // Database discovery opens any *.db file in .trace/, so a backup there could be mistaken for the database
+    let dest_dir = dest.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let db_dir = db_path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    if dest.extension().is_some_and(|ext| ext == "db")
+        && std::fs::canonicalize(dest_dir).ok() == Some(std::fs::canonicalize(db_dir)?)
+    {
+        anyhow::bail!(
+            "cannot write a .db backup next to the database, where it could be opened instead of it; use {} or another directory",
+            tracer::find_backups_dir(db_path).display()
+        );
+    }
+    tracer::backup::backup_database(db_path, &dest)?;
+
+    if json {
+        let output = serde_json::json!({
+            "database": db_path.display().to_string(),
+            "backup": dest.display().to_string(),
+            "bytes": std::fs::metadata(&dest)?.len(),
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        use colored::Colorize;
+        println!("{} Backed up {} to {}", "✓".green(), db_path.display(), dest.display());
+        println!("  Restore it with: tracer restore {}", dest.display());
+    }
+
+    Ok(())
+}
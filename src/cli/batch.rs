@@ -0,0 +1,332 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::PathBuf;
+use tracer::storage::{with_transaction, IssueUpdates, Storage};
+use tracer::types::*;
+
+#[derive(Args)]
+pub struct BatchArgs {
+    /// JSONL file of operations, one per line (default: stdin)
+    #[arg(short, long)]
+    pub input: Option<PathBuf>,
+}
+
+/// One line of a batch script. Issue IDs starting with `$` refer to the
+/// `ref` of an issue created earlier in the same batch.
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+enum Operation {
+    Create {
+        #[serde(rename = "ref")]
+        placeholder: Option<String>,
+        title: String,
+        #[serde(default)]
+        description: String,
+        #[serde(rename = "type")]
+        issue_type: Option<IssueType>,
+        priority: Option<i32>,
+        #[serde(default)]
+        assignee: String,
+        #[serde(default)]
+        labels: Vec<String>,
+        milestone: Option<String>,
+        due: Option<String>,
+        estimate: Option<String>,
+    },
+    Update {
+        id: String,
+        title: Option<String>,
+        description: Option<String>,
+        notes: Option<String>,
+        status: Option<Status>,
+        priority: Option<i32>,
+        #[serde(rename = "type")]
+        issue_type: Option<IssueType>,
+        assignee: Option<String>,
+        milestone: Option<String>, // "" removes the milestone
+        due: Option<String>,       // "" removes the due date
+        estimate: Option<String>,  // "" removes the estimate
+    },
+    Close {
+        id: String,
+        #[serde(default = "default_reason")]
+        reason: String,
+    },
+    Comment {
+        id: String,
+        text: String,
+    },
+    DepAdd {
+        from: String,
+        to: String,
+        #[serde(rename = "type", default = "default_dep_type")]
+        dep_type: DependencyType,
+    },
+    DepRemove {
+        from: String,
+        to: String,
+    },
+    LabelAdd {
+        id: String,
+        label: String,
+    },
+    LabelRemove {
+        id: String,
+        label: String,
+    },
+}
+
+fn default_reason() -> String {
+    "Completed".to_string()
+}
+
+fn default_dep_type() -> DependencyType {
+    DependencyType::Blocks
+}
+
+impl Operation {
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::Create { .. } => "create",
+            Operation::Update { .. } => "update",
+            Operation::Close { .. } => "close",
+            Operation::Comment { .. } => "comment",
+            Operation::DepAdd { .. } => "dep_add",
+            Operation::DepRemove { .. } => "dep_remove",
+            Operation::LabelAdd { .. } => "label_add",
+            Operation::LabelRemove { .. } => "label_remove",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct OperationResult {
+    line: usize,
+    op: &'static str,
+    id: String,
+    #[serde(rename = "ref", skip_serializing_if = "Option::is_none")]
+    placeholder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depends_on_id: Option<String>,
+}
+
+#[derive(Serialize)]
+struct BatchResult {
+    applied: usize,
+    refs: BTreeMap<String, String>,
+    results: Vec<OperationResult>,
+}
+
+pub fn execute(args: BatchArgs, storage: &mut Box<dyn Storage>, actor: &str, prefix: &str) -> Result<()> {
+    let mut input = String::new();
+    match &args.input {
+        Some(path) if path.as_os_str() != "-" => {
+            input = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+        }
+        _ => {
+            std::io::stdin().read_to_string(&mut input).context("Failed to read operations from stdin")?;
+        }
+    }
+
+    // Parse the whole script before touching the database
+    let mut operations = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let op: Operation = serde_json::from_str(line)
+            .with_context(|| format!("line {}: invalid operation", idx + 1))?;
+        operations.push((idx + 1, op));
+    }
+
+    let result = with_transaction(storage, |storage| -> Result<BatchResult> {
+        let mut refs = BTreeMap::new();
+        let mut results = Vec::new();
+        for (line, op) in operations {
+            let name = op.name();
+            let result = apply(op, line, storage, &mut refs, actor, prefix)
+                .with_context(|| format!("line {}: {} failed; no changes were made", line, name))?;
+            results.push(result);
+        }
+        Ok(BatchResult { applied: results.len(), refs, results })
+    })?;
+
+    // Batch output is always JSON: its point is handing real IDs back to the caller
+    println!("{}", serde_json::to_string_pretty(&result)?);
+
+    Ok(())
+}
+
+fn apply(
+    op: Operation,
+    line: usize,
+    storage: &mut Box<dyn Storage>,
+    refs: &mut BTreeMap<String, String>,
+    actor: &str,
+    prefix: &str,
+) -> Result<OperationResult> {
+    let name = op.name();
+    let mut result = OperationResult { line, op: name, id: String::new(), placeholder: None, depends_on_id: None };
+
+    match op {
+        Operation::Create { placeholder, title, description, issue_type, priority, assignee, labels, milestone, due, estimate } => {
+            if let Some(placeholder) = &placeholder {
+                if refs.contains_key(placeholder) {
+                    anyhow::bail!("ref {} is already used", placeholder);
+                }
+            }
+            let priority = priority.unwrap_or(2);
+            tracer::utils::check_priority(priority)?;
+            if let Some(milestone) = &milestone {
+                storage.get_milestone(milestone)?
+                    .ok_or_else(|| tracer::Error::not_found("Milestone", milestone))?;
+            }
+
+            let issue = Issue {
+                description,
+                priority,
+                issue_type: issue_type.unwrap_or(IssueType::Task),
+                assignee,
+                estimated_minutes: estimate.as_deref().map(parse_estimate).transpose()?,
+                due_at: due.as_deref().map(tracer::utils::parse_due_spec).transpose()?,
+                milestone,
+                ..Issue::new(storage.generate_id(prefix)?, title)
+            };
+            storage.create_issue(&issue, actor)?;
+            for label in &labels {
+                storage.add_label(&issue.id, label, actor)?;
+            }
+
+            if let Some(placeholder) = &placeholder {
+                refs.insert(placeholder.clone(), issue.id.clone());
+            }
+            result.id = issue.id;
+            result.placeholder = placeholder;
+        }
+
+        Operation::Update { id, title, description, notes, status, priority, issue_type, assignee, milestone, due, estimate } => {
+            let id = resolve(&id, refs)?;
+            let issue = existing(storage.as_ref(), &id)?;
+            if let Some(priority) = priority {
+                tracer::utils::check_priority(priority)?;
+            }
+            if let Some(milestone) = milestone.as_deref().filter(|m| !m.is_empty()) {
+                storage.get_milestone(milestone)?
+                    .ok_or_else(|| tracer::Error::not_found("Milestone", milestone))?;
+            }
+
+            // Same rule as `tracer update`: starting work claims the issue unless someone else holds it
+            let assignee = if assignee.is_some() {
+                assignee
+            } else if status == Some(Status::InProgress) {
+                issue.check_claim(actor)?;
+                issue.assignee.is_empty().then(|| actor.to_string())
+            } else {
+                None
+            };
+
+            let updates = IssueUpdates {
+                title,
+                description,
+                notes,
+                status,
+                priority,
+                issue_type,
+                assignee,
+                milestone: milestone.map(|m| if m.is_empty() { None } else { Some(m) }),
+                due_at: match due.as_deref() {
+                    Some("") => Some(None),
+                    Some(spec) => Some(Some(tracer::utils::parse_due_spec(spec)?)),
+                    None => None,
+                },
+                estimated_minutes: match estimate.as_deref() {
+                    Some("") => Some(None),
+                    Some(spec) => Some(Some(parse_estimate(spec)?)),
+                    None => None,
+                },
+                ..Default::default()
+            };
+            storage.update_issue(&id, &updates, actor)?;
+            result.id = id;
+        }
+
+        Operation::Close { id, reason } => {
+            let id = resolve(&id, refs)?;
+            existing(storage.as_ref(), &id)?;
+            storage.close_issue(&id, &reason, actor)?;
+            result.id = id;
+        }
+
+        Operation::Comment { id, text } => {
+            let id = resolve(&id, refs)?;
+            existing(storage.as_ref(), &id)?;
+            storage.add_comment(&id, actor, &text)?;
+            result.id = id;
+        }
+
+        Operation::DepAdd { from, to, dep_type } => {
+            let from = resolve(&from, refs)?;
+            let to = resolve(&to, refs)?;
+            existing(storage.as_ref(), &from)?;
+            existing(storage.as_ref(), &to)?;
+            let dep = Dependency {
+                issue_id: from.clone(),
+                depends_on_id: to.clone(),
+                dep_type,
+                created_at: Utc::now(),
+                created_by: actor.to_string(),
+            };
+            storage.add_dependency(&dep, actor)?;
+            result.id = from;
+            result.depends_on_id = Some(to);
+        }
+
+        Operation::DepRemove { from, to } => {
+            let from = resolve(&from, refs)?;
+            let to = resolve(&to, refs)?;
+            storage.remove_dependency(&from, &to, actor)?;
+            result.id = from;
+            result.depends_on_id = Some(to);
+        }
+
+        Operation::LabelAdd { id, label } => {
+            let id = resolve(&id, refs)?;
+            existing(storage.as_ref(), &id)?;
+            storage.add_label(&id, &label, actor)?;
+            result.id = id;
+        }
+
+        Operation::LabelRemove { id, label } => {
+            let id = resolve(&id, refs)?;
+            existing(storage.as_ref(), &id)?;
+            storage.remove_label(&id, &label, actor)?;
+            result.id = id;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Map `$ref` to the ID assigned earlier in the batch; other IDs pass through
+fn resolve(id: &str, refs: &BTreeMap<String, String>) -> Result<String> {
+    match id.strip_prefix('$') {
+        Some(placeholder) => refs.get(placeholder).cloned()
+            .with_context(|| format!("unknown ref {} (refs must be created earlier in the batch)", id)),
+        None => Ok(id.to_string()),
+    }
+}
+
+fn existing(storage: &dyn Storage, id: &str) -> Result<Issue> {
+    Ok(storage.get_issue(id)?.ok_or_else(|| tracer::Error::not_found("Issue", id))?)
+}
+
+fn parse_estimate(spec: &str) -> Result<i32> {
+    let minutes = tracer::utils::parse_work_duration(spec)?;
+    i32::try_from(minutes).context("estimate is too large")
+}
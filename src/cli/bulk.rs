@@ -0,0 +1,211 @@
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use tracer::storage::{with_transaction, IssueUpdates, Storage};
+use tracer::types::*;
+
+#[derive(Subcommand)]
+pub enum BulkCommands {
+    /// Update every issue matching a filter
+    Update(BulkUpdateArgs),
+}
+
+#[derive(Args)]
+pub struct BulkUpdateArgs {
+    /// Select issues (repeatable, all must match): status, priority, type, assignee, label, milestone
+    #[arg(long = "where", value_name = "FIELD=VALUE", value_parser = parse_assignment)]
+    pub filters: Vec<(String, String)>,
+
+    /// Select issues by ID (comma-separated; combined with --where, both must match)
+    #[arg(long, value_delimiter = ',')]
+    pub ids: Vec<String>,
+
+    /// Change to apply (repeatable): status, priority, type, assignee, milestone, due, estimate.
+    /// An empty value clears assignee, milestone, due and estimate
+    #[arg(long = "set", value_name = "FIELD=VALUE", value_parser = parse_assignment)]
+    pub sets: Vec<(String, String)>,
+
+    /// Labels to add (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    pub add_label: Vec<String>,
+
+    /// Labels to remove (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    pub remove_label: Vec<String>,
+
+    /// List the issues that would change without changing them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize)]
+struct BulkResult {
+    dry_run: bool,
+    changes: Vec<String>,
+    issues: Vec<Issue>,
+}
+
+pub fn execute_update(args: BulkUpdateArgs, storage: &mut Box<dyn Storage>, actor: &str, json: bool) -> Result<()> {
+    if args.filters.is_empty() && args.ids.is_empty() {
+        anyhow::bail!("select issues with --where and/or --ids");
+    }
+    if args.sets.is_empty() && args.add_label.is_empty() && args.remove_label.is_empty() {
+        anyhow::bail!("nothing to change: pass --set, --add-label or --remove-label");
+    }
+
+    let (updates, close) = build_updates(&args.sets, storage.as_ref())?;
+    // Only closing or relabelling: an empty update would still bump updated_at and record an event
+    let update_fields = updates != IssueUpdates::default();
+    let issues = select_issues(&args.filters, &args.ids, storage.as_ref())?;
+
+    let mut changes: Vec<String> = args.sets.iter().map(|(field, value)| format!("{}={}", field, value)).collect();
+    changes.extend(args.add_label.iter().map(|label| format!("+label {}", label)));
+    changes.extend(args.remove_label.iter().map(|label| format!("-label {}", label)));
+
+    let issues = if args.dry_run || issues.is_empty() {
+        issues
+    } else {
+        // All or nothing: any failure (e.g. a disallowed transition) rolls back every issue
+        with_transaction(storage, |storage| -> Result<Vec<Issue>> {
+            let mut updated = Vec::new();
+            for issue in &issues {
+                if update_fields {
+                    storage.update_issue(&issue.id, &updates, actor)
+                        .with_context(|| format!("failed to update {}", issue.id))?;
+                }
+                if close && issue.status != Status::Closed {
+                    storage.close_issue(&issue.id, "Closed by bulk update", actor)
+                        .with_context(|| format!("failed to close {}", issue.id))?;
+                }
+                for label in &args.add_label {
+                    storage.add_label(&issue.id, label, actor)?;
+                }
+                for label in &args.remove_label {
+                    storage.remove_label(&issue.id, label, actor)?;
+                }
+                updated.push(storage.get_issue(&issue.id)?.context("issue disappeared during update")?);
+            }
+            Ok(updated)
+        })?
+    };
+
+    if json {
+        let result = BulkResult { dry_run: args.dry_run, changes, issues };
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        use colored::Colorize;
+
+        if issues.is_empty() {
+            println!("No issues match");
+            return Ok(());
+        }
+
+        if args.dry_run {
+            println!("Would update {} issue(s) ({}):", issues.len(), changes.join(", ").yellow());
+        } else {
+            println!("✓ Updated {} issue(s) ({}):", issues.len(), changes.join(", ").yellow());
+        }
+        for issue in &issues {
+            println!("  {} {} [P{}, {}]", issue.id.cyan(), issue.title, issue.priority, issue.status);
+        }
+    }
+
+    Ok(())
+}
+
+/// Turn --set assignments into updates; closing is returned separately so it
+/// goes through close_issue (which records closed_at)
+fn build_updates(sets: &[(String, String)], storage: &dyn Storage) -> Result<(IssueUpdates, bool)> {
+    let mut updates = IssueUpdates::default();
+    let mut close = false;
+
+    for (field, value) in sets {
+        let clear = value.is_empty();
+        match field.as_str() {
+            "status" => {
+                let status: Status = value.parse()?;
+                if status == Status::Closed {
+                    close = true;
+                } else {
+                    updates.status = Some(status);
+                }
+            }
+            "priority" => {
+                let priority: i32 = value.parse().with_context(|| format!("invalid priority: {}", value))?;
+                tracer::utils::check_priority(priority)?;
+                updates.priority = Some(priority);
+            }
+            "type" | "issue_type" => updates.issue_type = Some(value.parse()?),
+            "assignee" => updates.assignee = Some(value.clone()),
+            "milestone" if clear => updates.milestone = Some(None),
+            "milestone" => {
+                storage.get_milestone(value)?
+                    .ok_or_else(|| tracer::Error::not_found("Milestone", value))?;
+                updates.milestone = Some(Some(value.clone()));
+            }
+            "due" if clear => updates.due_at = Some(None),
+            "due" => updates.due_at = Some(Some(tracer::utils::parse_due_spec(value)?)),
+            "estimate" if clear => updates.estimated_minutes = Some(None),
+            "estimate" => {
+                let minutes = tracer::utils::parse_work_duration(value)?;
+                updates.estimated_minutes = Some(Some(i32::try_from(minutes).context("estimate is too large")?));
+            }
+            _ => anyhow::bail!("cannot set {} (supported: status, priority, type, assignee, milestone, due, estimate)", field),
+        }
+    }
+
+    Ok((updates, close))
+}
+
+fn select_issues(filters: &[(String, String)], ids: &[String], storage: &dyn Storage) -> Result<Vec<Issue>> {
+    let mut filter = IssueFilter::default();
+    for (field, value) in filters {
+        match field.as_str() {
+            "status" => {
+                let status: Status = value.parse()?;
+                storage.get_workflow()?.check_known(&status)?;
+                filter.status = Some(status);
+            }
+            "priority" => filter.priority = Some(value.parse().with_context(|| format!("invalid priority: {}", value))?),
+            "type" | "issue_type" => filter.issue_type = Some(value.parse()?),
+            "assignee" => filter.assignee = Some(value.clone()),
+            "label" => filter.labels.push(value.clone()),
+            "milestone" => filter.milestone = Some(value.clone()),
+            _ => anyhow::bail!("cannot filter on {} (supported: status, priority, type, assignee, label, milestone)", field),
+        }
+    }
+
+    let mut issues = if ids.is_empty() {
+        storage.search_issues("", &filter)?
+    } else {
+        let mut issues = Vec::new();
+        for id in ids {
+            issues.push(storage.get_issue(id)?.ok_or_else(|| tracer::Error::not_found("Issue", id))?);
+        }
+        if !filters.is_empty() {
+            let matching: Vec<String> = storage.search_issues("", &filter)?.into_iter().map(|i| i.id).collect();
+            issues.retain(|issue| matching.contains(&issue.id));
+        }
+        issues
+    };
+
+    // The label filter matches any label; --where label=... means all of them
+    if filter.labels.len() > 1 {
+        let mut all = Vec::new();
+        for issue in issues {
+            let labels = storage.get_labels(&issue.id)?;
+            if filter.labels.iter().all(|label| labels.contains(label)) {
+                all.push(issue);
+            }
+        }
+        issues = all;
+    }
+
+    Ok(issues)
+}
+
+fn parse_assignment(spec: &str) -> Result<(String, String)> {
+    let (field, value) = spec.split_once('=')
+        .with_context(|| format!("invalid assignment: {} (expected field=value)", spec))?;
+    Ok((field.trim().to_string(), value.trim().to_string()))
+}
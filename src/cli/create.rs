@@ -1,7 +1,8 @@
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use clap::Args;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use tracer::storage::Storage;
 use tracer::types::*;
 
@@ -15,13 +16,13 @@ pub struct CreateArgs {
     #[arg(short, long)]
     pub description: Option<String>,
 
-    /// Priority (0=highest, 4=lowest)
-    #[arg(short, long, default_value = "2")]
-    pub priority: i32,
+    /// Priority (0=highest, 4=lowest) [default: 2]
+    #[arg(short, long)]
+    pub priority: Option<i32>,
 
-    /// Issue type
-    #[arg(short = 't', long, value_parser = clap::value_parser!(IssueType), default_value = "task")]
-    pub issue_type: IssueType,
+    /// Issue type [default: task]
+    #[arg(short = 't', long, value_parser = clap::value_parser!(IssueType))]
+    pub issue_type: Option<IssueType>,
 
     /// Assignee
     #[arg(short, long)]
@@ -35,31 +36,88 @@ pub struct CreateArgs {
     #[arg(long, value_delimiter = ',')]
     pub deps: Vec<String>,
 
+    /// Due date (YYYY-MM-DD, RFC 3339, or relative like 3d)
+    #[arg(long, value_parser = tracer::utils::parse_due_spec)]
+    pub due: Option<DateTime<Utc>>,
+
+    /// Milestone to plan this issue for
+    #[arg(short, long)]
+    pub milestone: Option<String>,
+
+    /// Start from a template in .trace/templates/<name>.json
+    #[arg(long)]
+    pub template: Option<String>,
+
+    /// Template variable (repeatable): --var key=value
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = tracer::templates::parse_var, requires = "template")]
+    pub vars: Vec<(String, String)>,
+
     /// Explicit issue ID (for avoiding collisions)
     #[arg(long)]
     pub id: Option<String>,
 
+    /// Refuse to create the issue if it looks like a duplicate of an open one
+    #[arg(long)]
+    pub no_duplicates: bool,
+
     /// Create from markdown file
     #[arg(short = 'f', long)]
     pub file: Option<PathBuf>,
 }
 
-pub fn execute(args: CreateArgs, storage: &mut Box<dyn Storage>, actor: &str, prefix: &str, json: bool) -> Result<()> {
+pub fn execute(args: CreateArgs, storage: &mut Box<dyn Storage>, actor: &str, prefix: &str, templates_dir: &Path, json: bool) -> Result<()> {
     // File import not yet supported
     if args.file.is_some() {
         anyhow::bail!("Creating from markdown files is not yet implemented");
     }
+
+    let template = match &args.template {
+        Some(name) => {
+            let mut vars: HashMap<String, String> = args.vars.iter().cloned().collect();
+            vars.entry("date".to_string()).or_insert_with(|| Utc::now().format("%Y-%m-%d").to_string());
+            vars.entry("actor".to_string()).or_insert_with(|| actor.to_string());
+            if let Some(title) = &args.title {
+                vars.entry("title".to_string()).or_insert_with(|| title.clone());
+            }
+            let template = tracer::templates::load_template(templates_dir, name)?;
+            Some(tracer::templates::instantiate(&template, &vars)?)
+        }
+        None => None,
+    };
     
     // Create single issue
-    let title = args.title.clone().context("Title is required")?;
-    create_single(args, &title, storage, actor, prefix, json)?;
+    let title = args.title.clone()
+        .or_else(|| template.as_ref().and_then(|t| t.title.clone()))
+        .context("Title is required")?;
+    create_single(args, &title, template.unwrap_or_default(), storage, actor, prefix, json)?;
     
     Ok(())
 }
 
-fn create_single(args: CreateArgs, title: &str, storage: &mut Box<dyn Storage>, actor: &str, prefix: &str, json: bool) -> Result<()> {
+fn create_single(args: CreateArgs, title: &str, template: IssueTemplate, storage: &mut Box<dyn Storage>, actor: &str, prefix: &str, json: bool) -> Result<()> {
     let now = Utc::now();
-    
+
+    if let Some(milestone) = &args.milestone {
+        storage.get_milestone(milestone)?
+            .ok_or_else(|| tracer::Error::not_found("Milestone", milestone))?;
+    }
+
+    let description = args.description.unwrap_or(template.description);
+    let duplicates = super::duplicates::find_duplicates(storage.as_ref(), title, &description)?;
+    if !duplicates.is_empty() {
+        let ids: Vec<&str> = duplicates.iter().map(|d| d.id.as_str()).collect();
+        if args.no_duplicates {
+            let message = format!("issue looks like a duplicate of {} (use `tracer show` to compare)", ids.join(", "));
+            return Err(tracer::Error::Conflict(message).into());
+        }
+        // Warn on stderr so --json output stays parseable
+        use colored::Colorize;
+        eprintln!("{} Possible duplicate of:", "⚠".yellow());
+        for similar in &duplicates {
+            eprintln!("  {} {} {}", similar.id.cyan(), similar.title, format!("({:.0}% similar)", similar.score * 100.0).dimmed());
+        }
+    }
+
     // Generate or use explicit ID
     let id = if let Some(explicit_id) = args.id {
         explicit_id
@@ -68,28 +126,29 @@ fn create_single(args: CreateArgs, title: &str, storage: &mut Box<dyn Storage>,
     };
 
     let issue = Issue {
-        id: id.clone(),
-        title: title.to_string(),
-        description: args.description.unwrap_or_default(),
-        design: String::new(),
-        acceptance_criteria: String::new(),
-        notes: String::new(),
-        status: Status::Open,
-        priority: args.priority,
-        issue_type: args.issue_type,
+        description,
+        design: template.design,
+        acceptance_criteria: template.acceptance_criteria,
+        priority: args.priority.or(template.priority).unwrap_or(2),
+        issue_type: args.issue_type.or(template.issue_type).unwrap_or(IssueType::Task),
         assignee: args.assignee.unwrap_or_default(),
-        estimated_minutes: None,
         created_at: now,
         updated_at: now,
-        closed_at: None,
-        external_ref: None,
-        dependencies: Vec::new(),
+        due_at: args.due,
+        milestone: args.milestone.clone(),
+        ..Issue::new(id.clone(), title)
     };
 
     storage.create_issue(&issue, actor)?;
 
-    // Add labels
-    for label in &args.labels {
+    // Add labels (template labels first, then any extra from the command line)
+    let mut labels = template.labels;
+    for label in args.labels {
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    for label in &labels {
         storage.add_label(&id, label, actor)?;
     }
 
@@ -106,19 +165,60 @@ fn create_single(args: CreateArgs, title: &str, storage: &mut Box<dyn Storage>,
         storage.add_dependency(&dep, actor)?;
     }
 
+    // Create template children, linked to this issue as parent-child
+    let mut children = Vec::new();
+    for child in template.children {
+        // Only the template's fields and the parent's milestone carry over, so a child
+        // never shares the parent's assignee, due date or external_ref
+        let child_issue = Issue {
+            description: child.description,
+            priority: child.priority.unwrap_or(issue.priority),
+            issue_type: child.issue_type.unwrap_or(IssueType::Task),
+            milestone: issue.milestone.clone(),
+            ..Issue::new(storage.generate_id(prefix)?, child.title)
+        };
+        storage.create_issue(&child_issue, actor)?;
+        for label in &child.labels {
+            storage.add_label(&child_issue.id, label, actor)?;
+        }
+        let dep = Dependency {
+            issue_id: child_issue.id.clone(),
+            depends_on_id: id.clone(),
+            dep_type: DependencyType::ParentChild,
+            created_at: now,
+            created_by: actor.to_string(),
+        };
+        storage.add_dependency(&dep, actor)?;
+        children.push(child_issue);
+    }
+
     if json {
-        println!("{}", serde_json::to_string_pretty(&issue)?);
+        if children.is_empty() {
+            println!("{}", serde_json::to_string_pretty(&issue)?);
+        } else {
+            #[derive(serde::Serialize)]
+            struct CreatedFromTemplate {
+                issue: Issue,
+                children: Vec<Issue>,
+            }
+            println!("{}", serde_json::to_string_pretty(&CreatedFromTemplate { issue, children })?);
+        }
     } else {
         use colored::Colorize;
         println!("✓ Created issue {} {}", id.bold().cyan(), title);
-        if !args.labels.is_empty() {
-            println!("  Labels: {}", args.labels.join(", "));
+        if !labels.is_empty() {
+            println!("  Labels: {}", labels.join(", "));
         }
         if !args.deps.is_empty() {
             println!("  Dependencies: {}", args.deps.join(", "));
         }
+        if let Some(milestone) = &args.milestone {
+            println!("  Milestone: {}", milestone);
+        }
+        for child in &children {
+            println!("  Child: {} {}", child.id.cyan(), child.title);
+        }
     }
 
     Ok(())
 }
-
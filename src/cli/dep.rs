@@ -1,4 +1,4 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use chrono::Utc;
 use clap::{Args, Subcommand};
 use tracer::storage::Storage;
@@ -54,9 +54,9 @@ pub struct TreeArgs {
 pub fn execute_add(args: AddArgs, storage: &mut Box<dyn Storage>, actor: &str, json: bool) -> Result<()> {
     // Verify both issues exist
     storage.get_issue(&args.issue_id)?
-        .context(format!("Issue {} not found", args.issue_id))?;
+        .ok_or_else(|| tracer::Error::not_found("Issue", &args.issue_id))?;
     storage.get_issue(&args.depends_on_id)?
-        .context(format!("Issue {} not found", args.depends_on_id))?;
+        .ok_or_else(|| tracer::Error::not_found("Issue", &args.depends_on_id))?;
 
     let dep = Dependency {
         issue_id: args.issue_id.clone(),
@@ -0,0 +1,195 @@
+use anyhow::Result;
+use clap::Args;
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use tracer::storage::sqlite::SqliteStorage;
+use tracer::storage::{Backend, Storage};
+use tracer::types::*;
+
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// Repair the problems that can be fixed without losing data
+    #[arg(long)]
+    pub fix: bool,
+}
+
+pub fn execute(args: DoctorArgs, db_path: &Path, actor: &str, json: bool) -> Result<()> {
+    if Backend::for_path(db_path) != Backend::Sqlite {
+        anyhow::bail!("{} uses the JSONL backend, which checks its files every time it opens them", db_path.display());
+    }
+    if !db_path.exists() {
+        anyhow::bail!("no database at {} (run `tracer init` first)", db_path.display());
+    }
+
+    // Keep other tracer processes from rewriting the JSONL files while they're checked
+    let _lock = super::export::lock_jsonl(db_path)?;
+
+    // Open the database directly: running the usual auto-import first would hide drift
+    let mut sqlite = SqliteStorage::new(db_path)?;
+    let mut findings = sqlite.check_integrity()?;
+    if args.fix && findings.iter().any(|finding| finding.fixable) {
+        sqlite.repair_integrity()?;
+        for finding in &mut findings {
+            finding.fixed = finding.fixable;
+        }
+    }
+    let mut storage: Box<dyn Storage> = Box::new(sqlite);
+
+    let jsonl_path = tracer::find_jsonl_path(db_path);
+    let (exported, unreadable) = read_jsonl::<Issue>(&jsonl_path)?;
+    let jsonl_readable = unreadable.is_empty();
+    findings.extend(unreadable);
+    findings.extend(read_jsonl::<Milestone>(&tracer::find_milestones_path(db_path))?.1);
+    findings.extend(read_jsonl::<ArchivedIssue>(&tracer::find_archive_path(db_path))?.1);
+
+    if jsonl_path.exists() {
+        findings.extend(check_drift(&mut storage, &jsonl_path, db_path, exported, jsonl_readable, args.fix, actor)?);
+
+        // Export what the repairs changed, unless that would drop the unreadable lines
+        if args.fix && jsonl_readable {
+            super::export::auto_export(&mut storage, &jsonl_path)?;
+        }
+    }
+
+    // Problems left after the run make the command fail, so scripts and CI notice them
+    let remaining = findings.iter().filter(|finding| !finding.fixed).count();
+    let unresolved = tracer::Error::Schema(format!("{} problem(s) remain", remaining));
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "findings": findings }))?);
+        if remaining > 0 {
+            // The findings are the JSON output; don't print an error object after them
+            std::process::exit(unresolved.exit_code());
+        }
+        return Ok(());
+    }
+
+    use colored::Colorize;
+
+    if findings.is_empty() {
+        println!("{} No problems found", "✓".green());
+        return Ok(());
+    }
+
+    for finding in &findings {
+        if finding.fixed {
+            println!("{} {} {}", "✓".green(), finding.message, "(fixed)".dimmed());
+        } else if finding.fixable {
+            println!("{} {} {}", "✗".red(), finding.message, "(fixable)".dimmed());
+        } else {
+            println!("{} {}", "✗".red(), finding.message);
+        }
+    }
+
+    let fixed = findings.iter().filter(|finding| finding.fixed).count();
+    let fixable = findings.iter().filter(|finding| finding.fixable && !finding.fixed).count();
+    println!();
+    if args.fix {
+        println!("{} problem(s) found, {} fixed", findings.len(), fixed);
+    } else if fixable > 0 {
+        println!("{} problem(s) found; run `tracer doctor --fix` to repair {} of them", findings.len(), fixable);
+    } else {
+        println!("{} problem(s) found", findings.len());
+    }
+
+    if remaining > 0 {
+        return Err(unresolved.into());
+    }
+    Ok(())
+}
+
+/// Parse a JSONL file, reporting each unreadable line instead of skipping it
+/// the way auto-export does
+fn read_jsonl<T: DeserializeOwned>(path: &Path) -> Result<(Vec<T>, Vec<Finding>)> {
+    let mut records = Vec::new();
+    let mut findings = Vec::new();
+    if !path.exists() {
+        return Ok((records, findings));
+    }
+
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    for (index, line) in std::fs::read_to_string(path)?.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(record) => records.push(record),
+            Err(err) => {
+                let message = format!("{} line {} can't be read and will be dropped by the next export: {}", name, index + 1, err);
+                findings.push(Finding::new("jsonl_parse", message, None, false));
+            }
+        }
+    }
+    Ok((records, findings))
+}
+
+/// Compare the database with the exported JSONL. If the file changed since the
+/// last import, importing it is the fix; otherwise the database is newer and
+/// the drifting issues are re-exported.
+fn check_drift(
+    storage: &mut Box<dyn Storage>,
+    jsonl_path: &Path,
+    db_path: &Path,
+    exported: Vec<Issue>,
+    can_fix: bool,
+    fix: bool,
+    actor: &str,
+) -> Result<Vec<Finding>> {
+    let name = jsonl_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let hash = tracer::utils::compute_hash(&std::fs::read(jsonl_path)?);
+    if storage.get_metadata("last_import_hash")?.as_deref() != Some(hash.as_str()) {
+        let message = format!("{} has changes that haven't been imported into the database", name);
+        let mut finding = Finding::new("jsonl_drift", message, None, can_fix);
+        if fix && can_fix {
+            super::export::auto_import(storage, &jsonl_path.to_path_buf(), db_path, actor)?;
+            finding.fixed = true;
+        }
+        return Ok(vec![finding]);
+    }
+
+    let mut findings = Vec::new();
+    let mut stale = Vec::new();
+    let mut in_file: HashMap<String, Issue> = exported.into_iter().map(|issue| (issue.id.clone(), issue)).collect();
+    let mut issues = storage.search_issues("", &IssueFilter::default())?;
+    issues.sort_by(|a, b| a.id.cmp(&b.id));
+    for issue in issues {
+        let message = match in_file.remove(&issue.id) {
+            None => format!("{} is missing from {}", issue.id, name),
+            Some(exported) if super::export::issue_differs(&exported, &issue)
+                || dependency_keys(&exported.dependencies) != dependency_keys(&storage.get_dependency_records(&issue.id)?) =>
+            {
+                format!("{} in {} differs from the database", issue.id, name)
+            }
+            Some(_) => continue,
+        };
+        findings.push(Finding::new("jsonl_drift", message, Some(&issue.id), can_fix));
+        stale.push(issue.id);
+    }
+
+    let mut only_in_file: Vec<String> = in_file.into_keys().collect();
+    only_in_file.sort();
+    for id in only_in_file {
+        let message = format!(
+            "{} is only in {} (add it with `tracer import -i {} --skip-existing`)",
+            id, name, jsonl_path.display()
+        );
+        findings.push(Finding::new("jsonl_drift", message, Some(&id), false));
+    }
+
+    if fix && can_fix && !stale.is_empty() {
+        super::export::export_issues(storage, jsonl_path, &stale)?;
+        storage.clear_dirty_issues_by_id(&stale)?;
+        for finding in findings.iter_mut().filter(|finding| finding.fixable) {
+            finding.fixed = true;
+        }
+    }
+
+    Ok(findings)
+}
+
+fn dependency_keys(dependencies: &[Dependency]) -> BTreeSet<(String, String)> {
+    dependencies.iter()
+        .map(|dep| (dep.depends_on_id.clone(), dep.dep_type.to_string()))
+        .collect()
+}
@@ -0,0 +1,86 @@
+use anyhow::Result;
+use clap::Args;
+use std::collections::HashMap;
+use tracer::similarity::{SimilarityIndex, DEFAULT_THRESHOLD};
+use tracer::storage::Storage;
+use tracer::types::*;
+
+#[derive(Args)]
+pub struct DuplicatesArgs {
+    /// Minimum similarity (0.0-1.0) for two issues to count as duplicates
+    #[arg(long, default_value_t = DEFAULT_THRESHOLD)]
+    pub threshold: f64,
+}
+
+pub fn execute(args: DuplicatesArgs, storage: &dyn Storage, json: bool) -> Result<()> {
+    if !(0.0..=1.0).contains(&args.threshold) {
+        anyhow::bail!("--threshold must be between 0.0 and 1.0");
+    }
+
+    let issues = open_issues(storage)?;
+    let by_id: HashMap<&str, &Issue> = issues.iter().map(|i| (i.id.as_str(), i)).collect();
+
+    let clusters: Vec<Vec<SimilarIssue>> = SimilarityIndex::new(&issues)
+        .clusters(args.threshold)
+        .into_iter()
+        .map(|cluster| cluster.into_iter()
+            .map(|(id, score)| to_similar(by_id[id.as_str()], score))
+            .collect())
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&clusters)?);
+    } else {
+        use colored::Colorize;
+
+        if clusters.is_empty() {
+            println!("{} No likely duplicates among {} open issue(s)", "✓".green(), issues.len());
+            return Ok(());
+        }
+
+        println!("{} {} group(s) of likely duplicates:\n", "⚠".yellow(), clusters.len());
+        for cluster in clusters {
+            for similar in cluster {
+                println!("  {} {} [{}] {}",
+                    similar.id.cyan(),
+                    similar.title,
+                    similar.status,
+                    format!("({:.0}%)", similar.score * 100.0).dimmed()
+                );
+            }
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+/// Issues that aren't in a done status, i.e. the ones a new issue could duplicate
+pub fn open_issues(storage: &dyn Storage) -> Result<Vec<Issue>> {
+    let workflow = storage.get_workflow()?;
+    let mut issues = storage.search_issues("", &IssueFilter::default())?;
+    issues.retain(|issue| !workflow.is_done(&issue.status));
+    Ok(issues)
+}
+
+/// Find open issues that look like duplicates of a new title and description
+pub fn find_duplicates(storage: &dyn Storage, title: &str, description: &str) -> Result<Vec<SimilarIssue>> {
+    let issues = open_issues(storage)?;
+    let by_id: HashMap<&str, &Issue> = issues.iter().map(|i| (i.id.as_str(), i)).collect();
+
+    let matches = SimilarityIndex::new(&issues)
+        .find_similar(title, description, DEFAULT_THRESHOLD)
+        .into_iter()
+        .map(|(id, score)| to_similar(by_id[id.as_str()], score))
+        .collect();
+    Ok(matches)
+}
+
+fn to_similar(issue: &Issue, score: f64) -> SimilarIssue {
+    SimilarIssue {
+        id: issue.id.clone(),
+        title: issue.title.clone(),
+        status: issue.status.clone(),
+        score,
+    }
+}
@@ -1,6 +1,6 @@
 use anyhow::{Context, Result};
 use clap::Args;
-use tracer::storage::Storage;
+use tracer::storage::{with_transaction, Storage};
 use tracer::types::*;
 use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Write};
@@ -15,6 +15,9 @@ pub struct ExportArgs {
     /// Filter by status
     #[arg(long, value_parser = clap::value_parser!(Status))]
     pub status: Option<Status>,
+
+    #[command(flatten)]
+    pub dates: super::list::DateFilterArgs,
 }
 
 #[derive(Args)]
@@ -33,24 +36,16 @@ pub struct ImportArgs {
 }
 
 pub fn execute_export(args: ExportArgs, storage: &dyn Storage) -> Result<()> {
-    let filter = IssueFilter {
+    if let Some(status) = &args.status {
+        storage.get_workflow()?.check_known(status)?;
+    }
+    let mut filter = IssueFilter {
         status: args.status,
-        priority: None,
-        issue_type: None,
-        assignee: None,
-        labels: Vec::new(),
-        limit: None,
+        ..Default::default()
     };
+    args.dates.apply(&mut filter);
 
-    let mut issues = storage.search_issues("", &filter)?;
-    
-    // Sort by ID for consistent output
-    issues.sort_by(|a, b| a.id.cmp(&b.id));
-
-    // Add dependencies to each issue
-    for issue in &mut issues {
-        issue.dependencies = storage.get_dependency_records(&issue.id)?;
-    }
+    let issues = load_issues(storage, &filter)?;
 
     // Write JSONL
     let output: Box<dyn Write> = if let Some(path) = args.output {
@@ -64,6 +59,21 @@ pub fn execute_export(args: ExportArgs, storage: &dyn Storage) -> Result<()> {
     Ok(())
 }
 
+/// Issues matching the filter, sorted by ID, with their dependencies and work logs
+fn load_issues(storage: &dyn Storage, filter: &IssueFilter) -> Result<Vec<Issue>> {
+    let mut issues = storage.search_issues("", filter)?;
+    
+    // Sort by ID for consistent output
+    issues.sort_by(|a, b| a.id.cmp(&b.id));
+
+    // Add dependencies to each issue
+    for issue in &mut issues {
+        issue.dependencies = storage.get_dependency_records(&issue.id)?;
+        issue.work_logs = storage.get_work_logs(&issue.id)?;
+    }
+    Ok(issues)
+}
+
 fn export_jsonl(issues: &[Issue], mut writer: Box<dyn Write>) -> Result<()> {
     for issue in issues {
         serde_json::to_writer(&mut writer, issue)?;
@@ -73,6 +83,16 @@ fn export_jsonl(issues: &[Issue], mut writer: Box<dyn Write>) -> Result<()> {
     Ok(())
 }
 
+/// Serialize records as JSONL, one per line
+fn to_jsonl<T: serde::Serialize>(records: &[T]) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    for record in records {
+        serde_json::to_writer(&mut data, record)?;
+        writeln!(data)?;
+    }
+    Ok(data)
+}
+
 pub fn execute_import(args: ImportArgs, storage: &mut Box<dyn Storage>, actor: &str) -> Result<()> {
     let reader: Box<dyn BufRead> = if let Some(path) = args.input {
         Box::new(BufReader::new(File::open(path)?))
@@ -115,12 +135,15 @@ pub fn execute_import(args: ImportArgs, storage: &mut Box<dyn Storage>, actor: &
                 design: Some(issue.design.clone()),
                 acceptance_criteria: Some(issue.acceptance_criteria.clone()),
                 notes: Some(issue.notes.clone()),
-                status: Some(issue.status),
+                status: Some(issue.status.clone()),
                 priority: Some(issue.priority),
                 issue_type: Some(issue.issue_type),
                 assignee: Some(issue.assignee.clone()),
                 estimated_minutes: Some(issue.estimated_minutes),
                 external_ref: Some(issue.external_ref.clone()),
+                due_at: Some(issue.due_at),
+                milestone: Some(issue.milestone.clone()),
+                bypass_workflow: true,
             };
             storage.update_issue(&issue.id, &updates, actor)?;
             updated += 1;
@@ -142,6 +165,8 @@ pub fn execute_import(args: ImportArgs, storage: &mut Box<dyn Storage>, actor: &
                 storage.add_dependency(dep, actor)?;
             }
         }
+
+        import_work_logs(storage, &issue)?;
     }
 
     use colored::Colorize;
@@ -155,14 +180,33 @@ pub fn execute_import(args: ImportArgs, storage: &mut Box<dyn Storage>, actor: &
     Ok(())
 }
 
-/// Auto-export issues to JSONL file
-pub fn auto_export(storage: &dyn Storage, jsonl_path: &Path) -> Result<()> {
-    // Get all dirty issues
-    let dirty_ids = storage.get_dirty_issues()?;
-    if dirty_ids.is_empty() {
-        return Ok(()); // Nothing to export
+/// Take the advisory lock that tracer processes hold while they import or
+/// export the JSONL files of the database at `db_path`
+pub fn lock_jsonl(db_path: &Path) -> Result<File> {
+    Ok(tracer::storage::lock_file(&db_path.with_extension("lock"), tracer::utils::busy_timeout())?)
+}
+
+/// Auto-export dirty issues to the JSONL file and clear their dirty flags, in
+/// one transaction, so issues another process marks dirty meanwhile stay dirty
+pub fn auto_export(storage: &mut Box<dyn Storage>, jsonl_path: &Path) -> Result<()> {
+    // Check before taking the write lock, so read-only commands never wait for it
+    if storage.get_dirty_issues()?.is_empty() {
+        return Ok(());
     }
+    with_transaction(storage, |storage| -> Result<()> {
+        let dirty_ids = storage.get_dirty_issues()?;
+        if dirty_ids.is_empty() {
+            return Ok(()); // Nothing to export
+        }
+        export_issues(storage, jsonl_path, &dirty_ids)?;
+        storage.clear_dirty_issues_by_id(&dirty_ids)?;
+        Ok(())
+    })
+}
 
+/// Rewrite the given issues in the JSONL file from the database, leaving the
+/// other lines as they are
+pub fn export_issues(storage: &mut Box<dyn Storage>, jsonl_path: &Path, ids: &[String]) -> Result<()> {
     // Read existing JSONL into memory
     let mut existing_issues: std::collections::HashMap<String, Issue> = std::collections::HashMap::new();
     if jsonl_path.exists() {
@@ -179,11 +223,12 @@ pub fn auto_export(storage: &dyn Storage, jsonl_path: &Path) -> Result<()> {
         }
     }
 
-    // Update dirty issues
-    for id in &dirty_ids {
+    // Update the exported issues
+    for id in ids {
         if let Some(issue) = storage.get_issue(id)? {
             let mut issue_with_deps = issue.clone();
             issue_with_deps.dependencies = storage.get_dependency_records(id)?;
+            issue_with_deps.work_logs = storage.get_work_logs(id)?;
             existing_issues.insert(id.clone(), issue_with_deps);
         } else {
             // Issue was deleted, remove from map
@@ -195,15 +240,31 @@ pub fn auto_export(storage: &dyn Storage, jsonl_path: &Path) -> Result<()> {
     let mut all_issues: Vec<Issue> = existing_issues.into_values().collect();
     all_issues.sort_by(|a, b| a.id.cmp(&b.id));
 
-    let file = File::create(jsonl_path)?;
-    let writer = BufWriter::new(file);
-    export_jsonl(&all_issues, Box::new(writer))?;
+    // Replace the file atomically, so concurrent readers never see it half-written
+    let jsonl_data = to_jsonl(&all_issues)?;
+    tracer::utils::write_atomic(jsonl_path, &jsonl_data)?;
+
+    // Record the hash of what we wrote so the next auto-import doesn't re-import our own export
+    storage.set_metadata("last_import_hash", &tracer::utils::compute_hash(&jsonl_data))?;
 
     Ok(())
 }
 
-/// Auto-import issues from JSONL if it's newer than the database
-pub fn auto_import(storage: &mut Box<dyn Storage>, jsonl_path: &PathBuf, actor: &str) -> Result<bool> {
+/// Rewrite the whole JSONL file from the database, e.g. after the database was
+/// replaced, so the next auto-import doesn't bring back what the file had
+pub fn export_all(storage: &mut Box<dyn Storage>, jsonl_path: &Path) -> Result<()> {
+    let issues = load_issues(storage.as_ref(), &IssueFilter::default())?;
+    let jsonl_data = to_jsonl(&issues)?;
+    tracer::utils::write_atomic(jsonl_path, &jsonl_data)?;
+
+    storage.set_metadata("last_import_hash", &tracer::utils::compute_hash(&jsonl_data))?;
+    storage.clear_dirty_issues()?;
+    Ok(())
+}
+
+/// Auto-import issues from JSONL if it's newer than the database, after backing
+/// up the database at `db_path`
+pub fn auto_import(storage: &mut Box<dyn Storage>, jsonl_path: &PathBuf, db_path: &Path, actor: &str) -> Result<bool> {
     if !jsonl_path.exists() {
         return Ok(false);
     }
@@ -231,45 +292,192 @@ pub fn auto_import(storage: &mut Box<dyn Storage>, jsonl_path: &PathBuf, actor:
         issues.push(issue);
     }
 
-    // Import all issues
-    for issue in issues {
-        let exists = storage.get_issue(&issue.id)?.is_some();
-        
-        if exists {
-            let updates = tracer::storage::IssueUpdates {
-                title: Some(issue.title.clone()),
-                description: Some(issue.description.clone()),
-                design: Some(issue.design.clone()),
-                acceptance_criteria: Some(issue.acceptance_criteria.clone()),
-                notes: Some(issue.notes.clone()),
-                status: Some(issue.status),
-                priority: Some(issue.priority),
-                issue_type: Some(issue.issue_type),
-                assignee: Some(issue.assignee.clone()),
-                estimated_minutes: Some(issue.estimated_minutes),
-                external_ref: Some(issue.external_ref.clone()),
-            };
-            storage.update_issue(&issue.id, &updates, actor)?;
-        } else {
-            storage.create_issue(&issue, actor)?;
-        }
+    tracer::backup::create_automatic(db_path, "pre-import")?;
 
-        // Import dependencies
-        for dep in &issue.dependencies {
-            let existing_deps = storage.get_dependency_records(&issue.id)?;
-            let already_exists = existing_deps.iter().any(|d| 
-                d.depends_on_id == dep.depends_on_id && d.dep_type == dep.dep_type
-            );
+    // Import all issues (and record the hash) in one transaction, so a failure
+    // partway leaves the database as it was and the next command retries the import
+    with_transaction(storage, |storage| -> Result<()> {
+        for issue in issues {
+            // Issues archived here stay archived, even while other clones still list them
+            if storage.get_archived_issue(&issue.id)?.is_some() {
+                continue;
+            }
+            match storage.get_issue(&issue.id)? {
+                // Leave unchanged issues alone so their updated_at and event history stay accurate
+                Some(existing) if !issue_differs(&existing, &issue) => {}
+                Some(_) => {
+                    let updates = tracer::storage::IssueUpdates {
+                        title: Some(issue.title.clone()),
+                        description: Some(issue.description.clone()),
+                        design: Some(issue.design.clone()),
+                        acceptance_criteria: Some(issue.acceptance_criteria.clone()),
+                        notes: Some(issue.notes.clone()),
+                        status: Some(issue.status.clone()),
+                        priority: Some(issue.priority),
+                        issue_type: Some(issue.issue_type),
+                        assignee: Some(issue.assignee.clone()),
+                        estimated_minutes: Some(issue.estimated_minutes),
+                        external_ref: Some(issue.external_ref.clone()),
+                        due_at: Some(issue.due_at),
+                        milestone: Some(issue.milestone.clone()),
+                        bypass_workflow: true,
+                    };
+                    storage.update_issue(&issue.id, &updates, actor)?;
+                }
+                None => storage.create_issue(&issue, actor)?,
+            }
+
+            // Import dependencies
+            for dep in &issue.dependencies {
+                let existing_deps = storage.get_dependency_records(&issue.id)?;
+                let already_exists = existing_deps.iter().any(|d| 
+                    d.depends_on_id == dep.depends_on_id && d.dep_type == dep.dep_type
+                );
             
-            if !already_exists {
-                storage.add_dependency(dep, actor)?;
+                if !already_exists {
+                    storage.add_dependency(dep, actor)?;
+                }
             }
+
+            import_work_logs(storage, &issue)?;
+        }
+
+        // Store the hash
+        storage.set_metadata("last_import_hash", &current_hash)?;
+        Ok(())
+    })?;
+
+    Ok(true)
+}
+
+/// Add imported work log entries that aren't in the database yet.
+/// Running entries are skipped: tracking only means something in the clone that started it.
+fn import_work_logs(storage: &mut Box<dyn Storage>, issue: &Issue) -> Result<()> {
+    if issue.work_logs.is_empty() {
+        return Ok(());
+    }
+
+    let existing = storage.get_work_logs(&issue.id)?;
+    for log in &issue.work_logs {
+        if log.ended_at.is_some() && !existing.contains(log) {
+            storage.add_work_log(log)?;
         }
     }
+    Ok(())
+}
+
+/// Check whether an imported issue differs from the stored one in any imported field
+pub fn issue_differs(existing: &Issue, incoming: &Issue) -> bool {
+    existing.title != incoming.title
+        || existing.description != incoming.description
+        || existing.design != incoming.design
+        || existing.acceptance_criteria != incoming.acceptance_criteria
+        || existing.notes != incoming.notes
+        || existing.status != incoming.status
+        || existing.priority != incoming.priority
+        || existing.issue_type != incoming.issue_type
+        || existing.assignee != incoming.assignee
+        || existing.estimated_minutes != incoming.estimated_minutes
+        || existing.external_ref != incoming.external_ref
+        || existing.due_at != incoming.due_at
+        || existing.milestone != incoming.milestone
+}
+
+/// Auto-export milestones to their JSONL file when it differs from the database
+pub fn auto_export_milestones(storage: &mut Box<dyn Storage>, milestones_path: &Path) -> Result<()> {
+    let milestones = storage.list_milestones()?;
+    if milestones.is_empty() && !milestones_path.exists() {
+        return Ok(()); // Don't create an empty file in projects that don't use milestones
+    }
+
+    let data = to_jsonl(&milestones)?;
+    let current = std::fs::read(milestones_path).unwrap_or_default();
+    if current != data {
+        tracer::utils::write_atomic(milestones_path, &data)?;
+        // Record the hash of what we wrote so the next auto-import skips our own export
+        storage.set_metadata("last_milestones_import_hash", &tracer::utils::compute_hash(&data))?;
+    }
+
+    Ok(())
+}
+
+/// Auto-import milestones from their JSONL file if it changed since the last import
+pub fn auto_import_milestones(storage: &mut Box<dyn Storage>, milestones_path: &Path) -> Result<bool> {
+    if !milestones_path.exists() {
+        return Ok(false);
+    }
+
+    let data = std::fs::read(milestones_path)?;
+    let current_hash = tracer::utils::compute_hash(&data);
+    if storage.get_metadata("last_milestones_import_hash")?.as_deref() == Some(&current_hash) {
+        return Ok(false); // No changes
+    }
+
+    with_transaction(storage, |storage| -> Result<()> {
+        for line in BufReader::new(std::io::Cursor::new(data)).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let milestone: Milestone = serde_json::from_str(&line)
+                .context(format!("Failed to parse milestone line: {}", line))?;
+            storage.save_milestone(&milestone)?;
+        }
 
-    // Store the hash
-    storage.set_metadata("last_import_hash", &current_hash)?;
+        storage.set_metadata("last_milestones_import_hash", &current_hash)?;
+        Ok(())
+    })?;
 
     Ok(true)
 }
 
+/// Write every archived issue to the archive JSONL file
+pub fn export_archive(storage: &mut Box<dyn Storage>, archive_path: &Path) -> Result<()> {
+    let archived = storage.list_archived_issues()?;
+    if archived.is_empty() && !archive_path.exists() {
+        return Ok(()); // Don't create an empty file in projects that don't archive
+    }
+
+    let data = to_jsonl(&archived)?;
+    tracer::utils::write_atomic(archive_path, &data)?;
+
+    // Record the hash of what we wrote so the next auto-import skips our own export
+    storage.set_metadata("last_archive_import_hash", &tracer::utils::compute_hash(&data))?;
+    Ok(())
+}
+
+/// Auto-import the archive JSONL file if it changed since the last import, so
+/// issues archived in another clone are archived here too. An issue changed
+/// here after it was archived there stays live.
+pub fn auto_import_archive(storage: &mut Box<dyn Storage>, archive_path: &Path) -> Result<bool> {
+    if !archive_path.exists() {
+        return Ok(false);
+    }
+
+    let data = std::fs::read(archive_path)?;
+    let current_hash = tracer::utils::compute_hash(&data);
+    if storage.get_metadata("last_archive_import_hash")?.as_deref() == Some(&current_hash) {
+        return Ok(false); // No changes
+    }
+
+    with_transaction(storage, |storage| -> Result<()> {
+        for line in BufReader::new(std::io::Cursor::new(data)).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let archived: ArchivedIssue = serde_json::from_str(&line)
+                .context(format!("Failed to parse archived issue line: {}", line))?;
+            let changed_since = storage.get_issue(&archived.issue.id)?
+                .is_some_and(|live| live.updated_at > archived.issue.updated_at);
+            if !changed_since {
+                storage.save_archived_issue(&archived)?;
+            }
+        }
+
+        storage.set_metadata("last_archive_import_hash", &current_hash)?;
+        Ok(())
+    })?;
+
+    Ok(true)
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use clap::Args;
 use std::path::PathBuf;
-use tracer::Storage;
+use tracer::storage::Backend;
 
 #[derive(Args)]
 pub struct InitArgs {
@@ -9,9 +9,13 @@ pub struct InitArgs {
     #[arg(long, default_value = "bd")]
     pub prefix: String,
 
-    /// Database path (default: .trace/<prefix>.db)
+    /// Database path (default: .trace/<prefix>.db, or .trace/<prefix>.json for the jsonl backend)
     #[arg(long)]
     pub path: Option<PathBuf>,
+
+    /// Storage backend: sqlite (database synced to issues.jsonl) or jsonl (text files only, no database)
+    #[arg(long, default_value = "sqlite", value_parser = clap::value_parser!(Backend))]
+    pub backend: Backend,
 }
 
 pub fn execute(args: InitArgs) -> Result<()> {
@@ -21,23 +25,30 @@ pub fn execute(args: InitArgs) -> Result<()> {
         let current = std::env::current_dir()?;
         let trace_dir = current.join(".trace");
         std::fs::create_dir_all(&trace_dir)?;
-        trace_dir.join(format!("{}.db", args.prefix))
+        trace_dir.join(format!("{}.{}", args.prefix, args.backend.extension()))
     };
+    if Backend::for_path(&db_path) != args.backend {
+        anyhow::bail!("--path for the {} backend must end in .{}", args.backend, args.backend.extension());
+    }
 
     // Create the database (schema is auto-initialized)
-    let mut storage = tracer::storage::sqlite::SqliteStorage::new(&db_path)?;
-    
+    let mut storage = args.backend.open(&db_path)?;
+
     // Set the prefix in config
     storage.set_config("prefix", &args.prefix)?;
 
     println!("✓ Initialized tracer database at {}", db_path.display());
     println!("  Prefix: {}", args.prefix);
+    println!("  Backend: {}", args.backend);
     println!("  JSONL: {}", tracer::utils::find_jsonl_path(&db_path).display());
     println!();
     println!("Next steps:");
     println!("  1. Create your first issue: tracer create \"My first task\"");
     println!("  2. See ready work: tracer ready");
-    println!("  3. Add to git: git add .trace/issues.jsonl");
+    match args.backend {
+        Backend::Sqlite => println!("  3. Add to git: git add .trace/issues.jsonl"),
+        Backend::Jsonl => println!("  3. Add to git: git add .trace/*.json .trace/issues.jsonl"),
+    }
 
     Ok(())
 }
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use clap::Args;
 use tracer::storage::Storage;
 use tracer::types::*;
@@ -21,40 +22,140 @@ pub struct ListArgs {
     #[arg(long)]
     pub assignee: Option<String>,
 
+    /// Filter by milestone
+    #[arg(short, long)]
+    pub milestone: Option<String>,
+
     /// Filter by labels (comma-separated)
     #[arg(short, long, value_delimiter = ',')]
     pub labels: Vec<String>,
 
+    #[command(flatten)]
+    pub dates: DateFilterArgs,
+
+    /// Sort order (comma-separated field[:asc|desc]; fields: id, title, status, priority, created, updated, closed, estimate, due)
+    #[arg(long, value_delimiter = ',', value_parser = clap::value_parser!(SortKey))]
+    pub sort: Vec<SortKey>,
+
     /// Maximum number of results
     #[arg(long)]
     pub limit: Option<usize>,
+
+    /// Number of results to skip
+    #[arg(long)]
+    pub offset: Option<usize>,
+
+    /// Resume from a cursor returned by a previous page
+    #[arg(long)]
+    pub cursor: Option<String>,
+
+    /// Also list archived issues
+    #[arg(long)]
+    pub include_archived: bool,
+}
+
+/// Timestamp filters shared by `list` and `export`. Values accept ISO dates
+/// (2025-10-01), RFC 3339 timestamps, or relative ages (30m, 24h, 7d, 2w).
+#[derive(Args)]
+pub struct DateFilterArgs {
+    /// Only issues created at or after this date
+    #[arg(long, value_parser = tracer::utils::parse_date_spec)]
+    pub created_after: Option<DateTime<Utc>>,
+
+    /// Only issues created before this date
+    #[arg(long, value_parser = tracer::utils::parse_date_spec)]
+    pub created_before: Option<DateTime<Utc>>,
+
+    /// Only issues updated at or after this date
+    #[arg(long, value_parser = tracer::utils::parse_date_spec)]
+    pub updated_after: Option<DateTime<Utc>>,
+
+    /// Only issues last updated before this date
+    #[arg(long, value_parser = tracer::utils::parse_date_spec)]
+    pub updated_before: Option<DateTime<Utc>>,
+
+    /// Only issues closed at or after this date
+    #[arg(long, value_parser = tracer::utils::parse_date_spec)]
+    pub closed_after: Option<DateTime<Utc>>,
+
+    /// Only issues closed before this date
+    #[arg(long, value_parser = tracer::utils::parse_date_spec)]
+    pub closed_before: Option<DateTime<Utc>>,
+
+    /// Only issues due before this date (relative values count forward, e.g. 3d)
+    #[arg(long, value_parser = tracer::utils::parse_due_spec)]
+    pub due_before: Option<DateTime<Utc>>,
+
+    /// Only unfinished issues that are past their due date
+    #[arg(long)]
+    pub overdue: bool,
+}
+
+impl DateFilterArgs {
+    pub fn apply(self, filter: &mut IssueFilter) {
+        filter.created_after = self.created_after;
+        filter.created_before = self.created_before;
+        filter.updated_after = self.updated_after;
+        filter.updated_before = self.updated_before;
+        filter.closed_after = self.closed_after;
+        filter.closed_before = self.closed_before;
+        filter.due_before = self.due_before;
+        filter.overdue = self.overdue;
+    }
 }
 
 pub fn execute(args: ListArgs, storage: &dyn Storage, json: bool) -> Result<()> {
-    let filter = IssueFilter {
+    if let Some(status) = &args.status {
+        storage.get_workflow()?.check_known(status)?;
+    }
+    let offset = tracer::utils::resolve_offset(args.offset, args.cursor.as_deref())?;
+    let paginated = args.limit.is_some() || offset.is_some();
+
+    let mut filter = IssueFilter {
         status: args.status,
         priority: args.priority,
         issue_type: args.issue_type,
         assignee: args.assignee,
         labels: args.labels,
-        limit: args.limit,
+        milestone: args.milestone,
+        sort: args.sort,
+        limit: args.limit.map(|limit| limit.saturating_add(1)), // One extra row to detect a next page
+        offset,
+        ..Default::default()
     };
+    args.dates.apply(&mut filter);
 
-    let issues = storage.search_issues("", &filter)?;
+    let issues = if args.include_archived {
+        // Merge both sets before sorting and paging, so pages span live and archived issues
+        let unpaged = IssueFilter { limit: None, offset: None, ..filter.clone() };
+        let mut issues = storage.search_issues("", &unpaged)?;
+        issues.extend(storage.search_archived_issues("", &unpaged)?);
+        tracer::storage::sort_and_paginate(issues, &filter)
+    } else {
+        storage.search_issues("", &filter)?
+    };
+    let page = tracer::utils::into_page(issues, offset, args.limit);
 
     if json {
-        println!("{}", serde_json::to_string_pretty(&issues)?);
+        if paginated {
+            println!("{}", serde_json::to_string_pretty(&page)?);
+        } else {
+            println!("{}", serde_json::to_string_pretty(&page.items)?);
+        }
     } else {
-        if issues.is_empty() {
+        if page.items.is_empty() {
             println!("No issues found");
             return Ok(());
         }
 
-        println!("Found {} issue(s):\n", issues.len());
-        for issue in issues {
+        println!("Found {} issue(s):\n", page.items.len());
+        for issue in page.items {
             print!("{}", tracer::utils::format_issue(&issue, false));
             println!();
         }
+        if let Some(cursor) = page.next_cursor {
+            println!("More results available: --cursor {}", cursor);
+        }
     }
 
     Ok(())
@@ -0,0 +1,174 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use clap::Args;
+use serde::Serialize;
+use std::collections::HashSet;
+use tracer::storage::Storage;
+use tracer::types::*;
+
+#[derive(Args)]
+pub struct MergeArgs {
+    /// Duplicate issue IDs to merge
+    #[arg(required = true)]
+    pub sources: Vec<String>,
+
+    /// Issue to keep
+    #[arg(long)]
+    pub into: String,
+}
+
+#[derive(Serialize)]
+struct MergeResult {
+    target: String,
+    merged: Vec<String>,
+    comments_moved: usize,
+    labels_moved: usize,
+    dependencies_moved: usize,
+}
+
+pub fn execute(args: MergeArgs, storage: &mut Box<dyn Storage>, actor: &str, json: bool) -> Result<()> {
+    let target = args.into;
+    storage.get_issue(&target)?
+        .ok_or_else(|| tracer::Error::not_found("Issue", &target))?;
+
+    // Naming a duplicate twice merges it once
+    let mut sources: Vec<String> = Vec::new();
+    for source in args.sources {
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+    }
+
+    // Validate everything before changing anything
+    let workflow = storage.get_workflow()?;
+    for source in &sources {
+        if source == &target {
+            anyhow::bail!("cannot merge {} into itself", source);
+        }
+        let issue = storage.get_issue(source)?
+            .ok_or_else(|| tracer::Error::not_found("Issue", source))?;
+        if issue.status != Status::Closed {
+            workflow.check_transition(&issue.status, &Status::Closed)
+                .context(format!("cannot close {}", source))?;
+        }
+    }
+
+    if merge_creates_cycle(&storage.get_all_dependency_records()?, &sources, &target) {
+        return Err(tracer::Error::Cycle(format!("{} would depend on itself after merging {}", target, sources.join(", "))).into());
+    }
+
+    let mut result = MergeResult {
+        target: target.clone(),
+        merged: Vec::new(),
+        comments_moved: 0,
+        labels_moved: 0,
+        dependencies_moved: 0,
+    };
+
+    for source in &sources {
+        result.comments_moved += storage.move_comments(source, &target)?;
+
+        let target_labels = storage.get_labels(&target)?;
+        for label in storage.get_labels(source)? {
+            if !target_labels.contains(&label) {
+                storage.add_label(&target, &label, actor)?;
+                result.labels_moved += 1;
+            }
+            storage.remove_label(source, &label, actor)?;
+        }
+
+        result.dependencies_moved += move_dependencies(storage, source, &target, actor)?;
+
+        // Record the duplicate and close it
+        let duplicate = Dependency {
+            issue_id: source.clone(),
+            depends_on_id: target.clone(),
+            dep_type: DependencyType::DuplicateOf,
+            created_at: Utc::now(),
+            created_by: actor.to_string(),
+        };
+        storage.add_dependency(&duplicate, actor)?;
+
+        if storage.get_issue(source)?.is_some_and(|issue| issue.status != Status::Closed) {
+            storage.close_issue(source, &format!("Duplicate of {}", target), actor)?;
+        }
+        result.merged.push(source.clone());
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        use colored::Colorize;
+        println!("✓ Merged {} into {}", result.merged.join(", ").cyan(), target.bold().cyan());
+        println!("  Moved {} comment(s), {} label(s), {} dependency edge(s)",
+            result.comments_moved, result.labels_moved, result.dependencies_moved);
+    }
+
+    Ok(())
+}
+
+/// Re-point the source's dependency edges (both directions) at the target.
+/// Edges between the two issues are dropped, and an edge is not copied when
+/// the target already has one to the same issue, since (issue_id, depends_on_id)
+/// is the primary key.
+fn move_dependencies(storage: &mut Box<dyn Storage>, source: &str, target: &str, actor: &str) -> Result<usize> {
+    let all = storage.get_all_dependency_records()?;
+    let mut moved = 0;
+
+    // Outgoing: source -> X becomes target -> X
+    for dep in all.iter().filter(|d| d.issue_id == source) {
+        let clash = dep.depends_on_id == target
+            || all.iter().any(|d| d.issue_id == target && d.depends_on_id == dep.depends_on_id);
+        if !clash {
+            storage.add_dependency(&Dependency { issue_id: target.to_string(), ..dep.clone() }, actor)?;
+            moved += 1;
+        }
+        storage.remove_dependency(source, &dep.depends_on_id, actor)?;
+    }
+
+    // Incoming: Y -> source becomes Y -> target
+    for dep in all.iter().filter(|d| d.depends_on_id == source) {
+        let clash = dep.issue_id == target
+            || all.iter().any(|d| d.issue_id == dep.issue_id && d.depends_on_id == target);
+        if !clash {
+            storage.add_dependency(&Dependency { depends_on_id: target.to_string(), ..dep.clone() }, actor)?;
+            moved += 1;
+        }
+        storage.remove_dependency(&dep.issue_id, source, actor)?;
+    }
+
+    Ok(moved)
+}
+
+/// Whether re-pointing the sources' edges at the target would put the target on
+/// a dependency cycle it isn't on already. Edges between the merged issues become
+/// self-edges, which `move_dependencies` drops, so they are left out here too.
+fn merge_creates_cycle(all: &[Dependency], sources: &[String], target: &str) -> bool {
+    let before: Vec<(&str, &str)> = all.iter()
+        .map(|dep| (dep.issue_id.as_str(), dep.depends_on_id.as_str()))
+        .collect();
+    let after: Vec<(&str, &str)> = before.iter()
+        .map(|&(from, to)| (merged_id(from, sources, target), merged_id(to, sources, target)))
+        .filter(|(from, to)| from != to)
+        .collect();
+    on_cycle(&after, target) && !on_cycle(&before, target)
+}
+
+fn merged_id<'a>(id: &'a str, sources: &[String], target: &'a str) -> &'a str {
+    if sources.iter().any(|source| source == id) { target } else { id }
+}
+
+/// Whether `start` can reach itself by following the edges
+fn on_cycle(edges: &[(&str, &str)], start: &str) -> bool {
+    let mut stack: Vec<&str> = edges.iter().filter(|(from, _)| *from == start).map(|(_, to)| *to).collect();
+    let mut seen = HashSet::new();
+    while let Some(node) = stack.pop() {
+        if node == start {
+            return true;
+        }
+        if seen.insert(node) {
+            stack.extend(edges.iter().filter(|(from, _)| *from == node).map(|(_, to)| *to));
+        }
+    }
+    false
+}
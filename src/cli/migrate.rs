@@ -0,0 +1,85 @@
+use anyhow::Result;
+use clap::Args;
+use std::path::Path;
+use tracer::storage::sqlite::{Migration, SqliteStorage, SCHEMA_VERSION};
+use tracer::storage::Backend;
+
+#[derive(Args)]
+pub struct MigrateArgs {
+    /// Show the schema version and pending migrations
+    #[arg(long, conflicts_with = "dry_run")]
+    pub status: bool,
+
+    /// List the migrations that would be applied, without applying them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub fn execute(args: MigrateArgs, db_path: &Path, json: bool) -> Result<()> {
+    if Backend::for_path(db_path) != Backend::Sqlite {
+        anyhow::bail!("{} uses the JSONL backend, which has no schema to migrate", db_path.display());
+    }
+    if !db_path.exists() {
+        anyhow::bail!("no database at {} (run `tracer init` first)", db_path.display());
+    }
+
+    let mut storage = SqliteStorage::open_unmigrated(db_path)?;
+    let version = storage.schema_version()?;
+    let inspect_only = args.status || args.dry_run;
+    let mut backup = None;
+    let migrations = if inspect_only {
+        storage.pending_migrations()?
+    } else {
+        if !storage.pending_migrations()?.is_empty() {
+            backup = tracer::backup::create_automatic(db_path, "pre-migration")?;
+        }
+        storage.migrate()?
+    };
+
+    if json {
+        let list: Vec<_> = migrations.iter()
+            .map(|m| serde_json::json!({ "version": m.version, "description": m.description }))
+            .collect();
+        let mut output = serde_json::json!({
+            "schema_version": if inspect_only { version } else { SCHEMA_VERSION },
+            "latest_version": SCHEMA_VERSION,
+            (if inspect_only { "pending" } else { "applied" }): list,
+        });
+        if let Some(backup) = &backup {
+            output["backup"] = backup.display().to_string().into();
+        }
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    use colored::Colorize;
+
+    if args.status {
+        println!("Schema version: {} (latest: {})", version, SCHEMA_VERSION);
+        if migrations.is_empty() {
+            println!("{}", "Up to date".green());
+        } else {
+            println!("Pending migrations:");
+            print_migrations(&migrations);
+        }
+    } else if migrations.is_empty() {
+        println!("{} Already up to date (schema version {})", "✓".green(), version);
+    } else if args.dry_run {
+        println!("Would migrate from schema version {} to {}:", version, SCHEMA_VERSION);
+        print_migrations(&migrations);
+    } else {
+        println!("{} Migrated from schema version {} to {}:", "✓".green(), version, SCHEMA_VERSION);
+        print_migrations(&migrations);
+        if let Some(backup) = &backup {
+            println!("  Backup: {}", backup.display());
+        }
+    }
+
+    Ok(())
+}
+
+fn print_migrations(migrations: &[&Migration]) {
+    for migration in migrations {
+        println!("  {:>3}  {}", migration.version, migration.description);
+    }
+}
@@ -0,0 +1,201 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand};
+use tracer::storage::Storage;
+use tracer::types::*;
+use tracer::utils::format_minutes;
+
+#[derive(Subcommand)]
+pub enum MilestoneCommands {
+    /// Create a milestone
+    Create(CreateArgs),
+
+    /// List milestones with progress
+    List(ListArgs),
+
+    /// Show a milestone report and its issues
+    Show(ShowArgs),
+
+    /// Close a milestone
+    Close(CloseArgs),
+}
+
+#[derive(Args)]
+pub struct CreateArgs {
+    /// Milestone name (e.g. v1.2 or sprint-14)
+    pub name: String,
+
+    /// Milestone description
+    #[arg(short, long)]
+    pub description: Option<String>,
+
+    /// Target date (YYYY-MM-DD, RFC 3339, or relative like 2w)
+    #[arg(long, value_parser = tracer::utils::parse_due_spec)]
+    pub target: Option<DateTime<Utc>>,
+}
+
+#[derive(Args)]
+pub struct ListArgs {
+    /// Include closed milestones
+    #[arg(long)]
+    pub all: bool,
+}
+
+#[derive(Args)]
+pub struct ShowArgs {
+    /// Milestone name
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct CloseArgs {
+    /// Milestone name
+    pub name: String,
+}
+
+pub fn execute_create(args: CreateArgs, storage: &mut Box<dyn Storage>, json: bool) -> Result<()> {
+    if storage.get_milestone(&args.name)?.is_some() {
+        anyhow::bail!("Milestone {} already exists", args.name);
+    }
+
+    let milestone = Milestone {
+        name: args.name,
+        description: args.description.unwrap_or_default(),
+        target_date: args.target,
+        status: MilestoneStatus::Open,
+        created_at: Utc::now(),
+        closed_at: None,
+    };
+    storage.save_milestone(&milestone)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&milestone)?);
+    } else {
+        use colored::Colorize;
+        println!("✓ Created milestone {}", milestone.name.bold().cyan());
+        if let Some(target) = milestone.target_date {
+            println!("  Target: {}", target.format("%Y-%m-%d"));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn execute_list(args: ListArgs, storage: &dyn Storage, json: bool) -> Result<()> {
+    let mut reports = Vec::new();
+    for milestone in storage.list_milestones()? {
+        if !args.all && milestone.status == MilestoneStatus::Closed {
+            continue;
+        }
+        if let Some(report) = storage.get_milestone_report(&milestone.name)? {
+            reports.push(report);
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        if reports.is_empty() {
+            println!("No milestones found");
+            return Ok(());
+        }
+
+        use colored::Colorize;
+        for report in reports {
+            let target = report.milestone.target_date
+                .map(|t| t.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|| "no target".to_string());
+            println!("{} [{}, {}] {}/{} closed, {} remaining",
+                report.milestone.name.bold().cyan(),
+                report.milestone.status,
+                target.yellow(),
+                report.closed_issues,
+                report.total_issues,
+                format_minutes(report.remaining_estimated_minutes)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub fn execute_show(args: ShowArgs, storage: &dyn Storage, json: bool) -> Result<()> {
+    let report = storage.get_milestone_report(&args.name)?
+        .ok_or_else(|| tracer::Error::not_found("Milestone", &args.name))?;
+
+    let filter = IssueFilter {
+        milestone: Some(args.name.clone()),
+        ..Default::default()
+    };
+    let issues = storage.search_issues("", &filter)?;
+
+    if json {
+        #[derive(serde::Serialize)]
+        struct MilestoneDetails {
+            #[serde(flatten)]
+            report: MilestoneReport,
+            issues: Vec<Issue>,
+        }
+        println!("{}", serde_json::to_string_pretty(&MilestoneDetails { report, issues })?);
+    } else {
+        use colored::Colorize;
+
+        let milestone = &report.milestone;
+        println!("{} [{}]", milestone.name.bold().cyan(), milestone.status);
+        if !milestone.description.is_empty() {
+            println!("\n  {}\n", milestone.description);
+        }
+        if let Some(target) = milestone.target_date {
+            let overdue = target < Utc::now() && milestone.status == MilestoneStatus::Open && report.open_issues > 0;
+            let target = target.format("%Y-%m-%d").to_string();
+            if overdue {
+                println!("  Target:    {} {}", target.red(), "(past target)".red().bold());
+            } else {
+                println!("  Target:    {}", target);
+            }
+        }
+        println!("  Issues:    {} total, {} open, {} closed",
+            report.total_issues,
+            report.open_issues.to_string().green(),
+            report.closed_issues.to_string().dimmed()
+        );
+        print!("  Remaining: {} estimated", format_minutes(report.remaining_estimated_minutes));
+        if report.unestimated_open_issues > 0 {
+            print!(" (+{} unestimated)", report.unestimated_open_issues);
+        }
+        println!();
+
+        if !issues.is_empty() {
+            println!();
+            for issue in issues {
+                println!("  {} {} [P{}, {}]", issue.id.cyan(), issue.title, issue.priority, issue.status);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn execute_close(args: CloseArgs, storage: &mut Box<dyn Storage>, json: bool) -> Result<()> {
+    let mut milestone = storage.get_milestone(&args.name)?
+        .ok_or_else(|| tracer::Error::not_found("Milestone", &args.name))?;
+
+    milestone.status = MilestoneStatus::Closed;
+    milestone.closed_at = Some(Utc::now());
+    storage.save_milestone(&milestone)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&milestone)?);
+    } else {
+        use colored::Colorize;
+        println!("✓ Closed milestone {}", milestone.name.bold().cyan());
+
+        let report = storage.get_milestone_report(&args.name)?
+            .ok_or_else(|| tracer::Error::not_found("Milestone", &args.name))?;
+        if report.open_issues > 0 {
+            println!("  {} {} issue(s) are still open", "⚠".yellow(), report.open_issues);
+        }
+    }
+
+    Ok(())
+}
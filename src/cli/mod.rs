@@ -1,13 +1,28 @@
+pub mod activity;
+pub mod archive;
+pub mod backup;
+pub mod batch;
+pub mod bulk;
 pub mod create;
 pub mod dep;
+pub mod doctor;
+pub mod duplicates;
 pub mod export;
 pub mod init;
 pub mod learn;
 pub mod list;
+pub mod merge;
+pub mod migrate;
+pub mod milestone;
 pub mod ready;
+pub mod recur;
+pub mod restore;
 pub mod show;
+pub mod stale;
 pub mod stats;
+pub mod template;
 pub mod update;
+pub mod workflow;
 
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
@@ -75,7 +90,84 @@ pub enum Commands {
     /// Show statistics
     Stats(stats::StatsArgs),
     
+    /// Apply a script of operations in one transaction
+    Batch(batch::BatchArgs),
+    
+    /// Change many issues at once
+    #[command(subcommand)]
+    Bulk(bulk::BulkCommands),
+    
+    /// Merge duplicate issues into one
+    Merge(merge::MergeArgs),
+    
+    /// Find groups of open issues that look like duplicates
+    Duplicates(duplicates::DuplicatesArgs),
+    
+    /// Summarize activity per actor
+    Activity(activity::ActivityArgs),
+    
+    /// Move old closed issues into the archive and compact the event log
+    Archive(archive::ArchiveArgs),
+    
+    /// Find issues with no recent activity
+    Stale(stale::StaleArgs),
+    
+    /// Copy the database to a backup file
+    Backup(backup::BackupArgs),
+    
+    /// Replace the database with a backup
+    Restore(restore::RestoreArgs),
+    
+    /// Check the database and JSONL files for problems
+    Doctor(doctor::DoctorArgs),
+    
+    /// Show or apply database schema migrations
+    Migrate(migrate::MigrateArgs),
+    
+    /// Manage milestones
+    #[command(subcommand)]
+    Milestone(milestone::MilestoneCommands),
+    
+    /// Manage recurring issues
+    #[command(subcommand)]
+    Recur(recur::RecurCommands),
+    
+    /// List and inspect issue templates
+    #[command(subcommand)]
+    Template(template::TemplateCommands),
+    
+    /// Manage custom statuses and allowed transitions
+    #[command(subcommand)]
+    Workflow(workflow::WorkflowCommands),
+    
     /// Add a comment to an issue
     Comment(update::CommentArgs),
+    
+    /// Log time spent on an issue
+    Log(update::LogArgs),
 }
 
+
+impl Commands {
+    /// Whether the command only reads the database, so it can run without
+    /// taking the write lock
+    pub fn is_read_only(&self) -> bool {
+        match self {
+            Commands::List(_)
+            | Commands::Show(_)
+            | Commands::Ready(_)
+            | Commands::Blocked(_)
+            | Commands::Export(_)
+            | Commands::Stats(_)
+            | Commands::Duplicates(_)
+            | Commands::Activity(_)
+            | Commands::Template(_)
+            | Commands::Dep(dep::DepCommands::Tree(_) | dep::DepCommands::Cycles)
+            | Commands::Milestone(milestone::MilestoneCommands::List(_) | milestone::MilestoneCommands::Show(_))
+            | Commands::Recur(recur::RecurCommands::List)
+            | Commands::Workflow(workflow::WorkflowCommands::Show) => true,
+            Commands::Stale(args) => !args.release,
+            _ => false,
+        }
+    }
+}
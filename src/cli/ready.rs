@@ -13,63 +13,134 @@ pub struct ReadyArgs {
     #[arg(long)]
     pub assignee: Option<String>,
 
+    /// Filter by milestone
+    #[arg(short, long)]
+    pub milestone: Option<String>,
+
+    #[command(flatten)]
+    pub page: PageArgs,
+}
+
+#[derive(Args)]
+pub struct BlockedArgs {
+    /// Filter by priority
+    #[arg(long)]
+    pub priority: Option<i32>,
+
+    /// Filter by assignee
+    #[arg(long)]
+    pub assignee: Option<String>,
+
+    /// Filter by milestone
+    #[arg(short, long)]
+    pub milestone: Option<String>,
+
+    #[command(flatten)]
+    pub page: PageArgs,
+}
+
+/// Sorting and pagination options shared by `ready` and `blocked`
+#[derive(Args)]
+pub struct PageArgs {
+    /// Sort order (comma-separated field[:asc|desc]; fields: id, title, status, priority, created, updated, closed, estimate, due)
+    #[arg(long, value_delimiter = ',', value_parser = clap::value_parser!(SortKey))]
+    pub sort: Vec<SortKey>,
+
     /// Maximum number of results
     #[arg(long)]
     pub limit: Option<usize>,
+
+    /// Number of results to skip
+    #[arg(long)]
+    pub offset: Option<usize>,
+
+    /// Resume from a cursor returned by a previous page
+    #[arg(long)]
+    pub cursor: Option<String>,
 }
 
-#[derive(Args)]
-pub struct BlockedArgs {}
+impl PageArgs {
+    fn work_filter(self, priority: Option<i32>, assignee: Option<String>, milestone: Option<String>) -> Result<WorkFilter> {
+        let offset = tracer::utils::resolve_offset(self.offset, self.cursor.as_deref())?;
+        Ok(WorkFilter {
+            status: Status::Open,
+            priority,
+            assignee,
+            milestone,
+            sort: self.sort,
+            limit: self.limit.map(|limit| limit.saturating_add(1)), // One extra row to detect a next page
+            offset,
+        })
+    }
+
+    fn is_paginated(&self) -> bool {
+        self.limit.is_some() || self.offset.is_some() || self.cursor.is_some()
+    }
+}
 
 pub fn execute_ready(args: ReadyArgs, storage: &dyn Storage, json: bool) -> Result<()> {
-    let filter = WorkFilter {
-        status: Status::Open,
-        priority: args.priority,
-        assignee: args.assignee,
-        limit: args.limit,
-    };
+    let paginated = args.page.is_paginated();
+    let limit = args.page.limit;
+    let filter = args.page.work_filter(args.priority, args.assignee, args.milestone)?;
 
-    let issues = storage.get_ready_work(&filter)?;
+    let page = tracer::utils::into_page(storage.get_ready_work(&filter)?, filter.offset, limit);
 
     if json {
-        println!("{}", serde_json::to_string_pretty(&issues)?);
+        if paginated {
+            println!("{}", serde_json::to_string_pretty(&page)?);
+        } else {
+            println!("{}", serde_json::to_string_pretty(&page.items)?);
+        }
     } else {
-        if issues.is_empty() {
+        if page.items.is_empty() {
             println!("No ready work found");
             return Ok(());
         }
 
         use colored::Colorize;
-        println!("{} Ready work: {} issue(s)\n", "✓".green(), issues.len());
-        for issue in issues {
+        println!("{} Ready work: {} issue(s)\n", "✓".green(), page.items.len());
+        for issue in page.items {
             print!("{}", tracer::utils::format_issue(&issue, false));
             println!();
         }
+        if let Some(cursor) = page.next_cursor {
+            println!("More results available: --cursor {}", cursor);
+        }
     }
 
     Ok(())
 }
 
-pub fn execute_blocked(_args: BlockedArgs, storage: &dyn Storage, json: bool) -> Result<()> {
-    let blocked = storage.get_blocked_issues()?;
+pub fn execute_blocked(args: BlockedArgs, storage: &dyn Storage, json: bool) -> Result<()> {
+    let paginated = args.page.is_paginated();
+    let limit = args.page.limit;
+    let filter = args.page.work_filter(args.priority, args.assignee, args.milestone)?;
+
+    let page = tracer::utils::into_page(storage.get_blocked_issues(&filter)?, filter.offset, limit);
 
     if json {
-        println!("{}", serde_json::to_string_pretty(&blocked)?);
+        if paginated {
+            println!("{}", serde_json::to_string_pretty(&page)?);
+        } else {
+            println!("{}", serde_json::to_string_pretty(&page.items)?);
+        }
     } else {
-        if blocked.is_empty() {
+        if page.items.is_empty() {
             println!("No blocked issues found");
             return Ok(());
         }
 
         use colored::Colorize;
-        println!("{} Blocked: {} issue(s)\n", "⚠".yellow(), blocked.len());
-        for bi in blocked {
+        println!("{} Blocked: {} issue(s)\n", "⚠".yellow(), page.items.len());
+        for bi in page.items {
             print!("{}", tracer::utils::format_issue(&bi.issue, false));
             println!("  {} Blocked by: {}", "⚠".red(), bi.blocked_by.join(", "));
             println!();
         }
+        if let Some(cursor) = page.next_cursor {
+            println!("More results available: --cursor {}", cursor);
+        }
     }
 
     Ok(())
 }
-
@@ -0,0 +1,235 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use tracer::schedule::Schedule;
+use tracer::storage::Storage;
+use tracer::types::*;
+
+#[derive(Subcommand)]
+pub enum RecurCommands {
+    /// Define a recurring issue
+    Add(Box<AddArgs>),
+
+    /// List recurring issue definitions
+    List,
+
+    /// Remove a recurring issue definition
+    Remove(RemoveArgs),
+
+    /// Create issues for every recurrence that is due (safe to run repeatedly)
+    Run(RunArgs),
+}
+
+#[derive(Args)]
+pub struct AddArgs {
+    /// Recurrence name (e.g. weekly-triage)
+    pub name: String,
+
+    /// Title of each created issue
+    #[arg(long)]
+    pub title: String,
+
+    /// Interval between occurrences (e.g. 1d, 2w)
+    #[arg(long, value_parser = tracer::schedule::parse_interval, conflicts_with = "cron", required_unless_present = "cron")]
+    pub every: Option<Schedule>,
+
+    /// Cron expression in UTC (minute hour day-of-month month day-of-week), e.g. "0 9 * * MON"
+    #[arg(long, value_parser = clap::value_parser!(Schedule))]
+    pub cron: Option<Schedule>,
+
+    /// Description of each created issue
+    #[arg(short, long)]
+    pub description: Option<String>,
+
+    /// Priority (0=highest, 4=lowest)
+    #[arg(short, long, default_value = "2")]
+    pub priority: i32,
+
+    /// Issue type
+    #[arg(short = 't', long, value_parser = clap::value_parser!(IssueType), default_value = "chore")]
+    pub issue_type: IssueType,
+
+    /// Labels (comma-separated)
+    #[arg(short, long, value_delimiter = ',')]
+    pub labels: Vec<String>,
+
+    /// Assignee
+    #[arg(short, long)]
+    pub assignee: Option<String>,
+
+    /// When the schedule starts (default: now; YYYY-MM-DD or RFC 3339)
+    #[arg(long, value_parser = tracer::utils::parse_date_spec)]
+    pub start: Option<DateTime<Utc>>,
+}
+
+#[derive(Args)]
+pub struct RemoveArgs {
+    /// Recurrence name
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct RunArgs {
+    /// Show what would be created without creating anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize)]
+struct Occurrence {
+    recurrence: String,
+    period: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issue_id: Option<String>,
+}
+
+pub fn execute_add(args: Box<AddArgs>, storage: &mut Box<dyn Storage>, json: bool) -> Result<()> {
+    if storage.get_recurrence(&args.name)?.is_some() {
+        anyhow::bail!("Recurrence {} already exists", args.name);
+    }
+    tracer::utils::check_priority(args.priority)?;
+    if let Some(Schedule::Every { .. }) = args.cron {
+        anyhow::bail!("--cron expects a cron expression; use --every for intervals");
+    }
+
+    let now = Utc::now();
+    let recurrence = Recurrence {
+        name: args.name,
+        schedule: args.every.or(args.cron).context("--every or --cron is required")?,
+        title: args.title,
+        description: args.description.unwrap_or_default(),
+        issue_type: args.issue_type,
+        priority: args.priority,
+        labels: args.labels,
+        assignee: args.assignee.unwrap_or_default(),
+        start_at: args.start.unwrap_or(now),
+        last_period: None,
+        created_at: now,
+    };
+    storage.save_recurrence(&recurrence)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&recurrence)?);
+    } else {
+        use colored::Colorize;
+        println!("✓ Created recurrence {} ({})", recurrence.name.bold().cyan(), recurrence.schedule);
+        println!("  Run {} (e.g. from cron) to create due issues", "tracer recur run".yellow());
+    }
+
+    Ok(())
+}
+
+pub fn execute_list(storage: &dyn Storage, json: bool) -> Result<()> {
+    let recurrences = storage.list_recurrences()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&recurrences)?);
+    } else {
+        if recurrences.is_empty() {
+            println!("No recurring issues defined");
+            return Ok(());
+        }
+
+        use colored::Colorize;
+        for recurrence in recurrences {
+            let last = recurrence.last_period
+                .map(|p| p.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "never".to_string());
+            println!("{} [{}] {} {}",
+                recurrence.name.bold().cyan(),
+                recurrence.schedule.to_string().yellow(),
+                recurrence.title,
+                format!("(last created: {})", last).dimmed()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub fn execute_remove(args: RemoveArgs, storage: &mut Box<dyn Storage>, json: bool) -> Result<()> {
+    storage.delete_recurrence(&args.name)?;
+
+    if json {
+        println!("{{\"status\": \"removed\"}}");
+    } else {
+        use colored::Colorize;
+        println!("✓ Removed recurrence {}", args.name.bold().cyan());
+    }
+
+    Ok(())
+}
+
+/// Create one issue per recurrence whose current period hasn't been materialized.
+/// Missed periods are not backfilled: only the most recent one is created.
+pub fn execute_run(args: RunArgs, storage: &mut Box<dyn Storage>, actor: &str, prefix: &str, json: bool) -> Result<()> {
+    let now = Utc::now();
+    let mut occurrences = Vec::new();
+
+    for recurrence in storage.list_recurrences()? {
+        let Some(period) = recurrence.schedule.latest_occurrence(recurrence.start_at, now) else {
+            continue;
+        };
+        if recurrence.last_period.is_some_and(|last| last >= period) {
+            continue;
+        }
+
+        // Another clone may already have created this occurrence (synced through JSONL)
+        let external_ref = recurrence.external_ref(period);
+        if storage.get_issue_by_external_ref(&external_ref)?.is_some() {
+            if !args.dry_run {
+                storage.claim_recurrence_period(&recurrence.name, period)?;
+            }
+            continue;
+        }
+
+        if args.dry_run {
+            occurrences.push(Occurrence { recurrence: recurrence.name, period, issue_id: None });
+            continue;
+        }
+
+        // Claim the period first so concurrent runs can't both create it
+        if !storage.claim_recurrence_period(&recurrence.name, period)? {
+            continue;
+        }
+
+        let issue = Issue {
+            description: recurrence.description.clone(),
+            priority: recurrence.priority,
+            issue_type: recurrence.issue_type,
+            assignee: recurrence.assignee.clone(),
+            created_at: now,
+            updated_at: now,
+            external_ref: Some(external_ref),
+            ..Issue::new(storage.generate_id(prefix)?, recurrence.title.clone())
+        };
+        storage.create_issue(&issue, actor)?;
+        for label in &recurrence.labels {
+            storage.add_label(&issue.id, label, actor)?;
+        }
+        occurrences.push(Occurrence { recurrence: recurrence.name, period, issue_id: Some(issue.id) });
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&occurrences)?);
+    } else {
+        use colored::Colorize;
+
+        if occurrences.is_empty() {
+            println!("No recurring issues due");
+            return Ok(());
+        }
+        for occurrence in occurrences {
+            let period = occurrence.period.format("%Y-%m-%d %H:%M").to_string();
+            match (args.dry_run, occurrence.issue_id) {
+                (true, None) => println!("Would create {} for {}", occurrence.recurrence.cyan(), period),
+                (_, Some(id)) => println!("✓ {} {} for {}", id.bold().cyan(), occurrence.recurrence, period),
+                (false, None) => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
@@ -0,0 +1,194 @@
+use anyhow::Result;
+use clap::Args;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracer::storage::sqlite::SqliteStorage;
+use tracer::storage::{Backend, Storage};
+use tracer::types::*;
+
+#[derive(Args)]
+pub struct RestoreArgs {
+    /// Backup to restore, e.g. one from .trace/backups/
+    pub snapshot: PathBuf,
+
+    /// Show what would change without restoring
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// What restoring a snapshot changes, compared with the current database
+#[derive(Serialize)]
+struct RestoreSummary {
+    snapshot: String,
+    /// Issues in the snapshot that the database no longer has
+    added: Vec<String>,
+    /// Issues created since the snapshot, which restoring discards
+    removed: Vec<String>,
+    /// Issues in both whose fields or dependencies differ
+    changed: Vec<String>,
+    events_before: usize,
+    events_after: usize,
+    milestones_before: usize,
+    milestones_after: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backup: Option<String>,
+    dry_run: bool,
+}
+
+impl RestoreSummary {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.events_before == self.events_after
+            && self.milestones_before == self.milestones_after
+    }
+}
+
+pub fn execute(args: RestoreArgs, db_path: &Path, json: bool) -> Result<()> {
+    if Backend::for_path(db_path) != Backend::Sqlite {
+        anyhow::bail!("{} uses the JSONL backend; restore its files from git instead", db_path.display());
+    }
+    if !args.snapshot.exists() {
+        anyhow::bail!("no backup at {}", args.snapshot.display());
+    }
+
+    // Keep other tracer processes from importing or exporting the JSONL files midway
+    let _lock = super::export::lock_jsonl(db_path)?;
+
+    // Work on a copy, so migrating an older snapshot leaves the backup itself untouched.
+    // It goes next to the other backups rather than in a shared temp directory,
+    // where another user could create the file first.
+    let staging = tracer::find_backups_dir(db_path).join(tracer::backup::backup_file_name(db_path, Some("restoring")));
+    let result = restore(&args, db_path, &staging);
+    for suffix in ["", "-wal", "-shm"] {
+        let mut path = staging.clone().into_os_string();
+        path.push(suffix);
+        let _ = std::fs::remove_file(path);
+    }
+    let summary = result?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+
+    use colored::Colorize;
+
+    if summary.is_empty() {
+        println!("{} The database already matches {}", "✓".green(), summary.snapshot);
+        return Ok(());
+    }
+
+    if summary.dry_run {
+        println!("Restoring {} would:", summary.snapshot);
+    } else {
+        println!("{} Restored {}:", "✓".green(), summary.snapshot);
+    }
+    print_ids("remove", "issue(s) created since the backup", &summary.removed);
+    print_ids("bring back", "deleted issue(s)", &summary.added);
+    print_ids("revert", "changed issue(s)", &summary.changed);
+    if summary.events_before != summary.events_after {
+        println!("  events: {} → {}", summary.events_before, summary.events_after);
+    }
+    if summary.milestones_before != summary.milestones_after {
+        println!("  milestones: {} → {}", summary.milestones_before, summary.milestones_after);
+    }
+    if let Some(backup) = &summary.backup {
+        println!("  The previous database was backed up to {}", backup.dimmed());
+    }
+
+    Ok(())
+}
+
+fn restore(args: &RestoreArgs, db_path: &Path, staging: &Path) -> Result<RestoreSummary> {
+    tracer::backup::backup_database(&args.snapshot, staging)?;
+    let mut snapshot = SqliteStorage::open_unmigrated(staging)?;
+    snapshot.migrate()?;
+
+    let current = SqliteStorage::new(db_path)?;
+    let mut summary = compare(&current, &snapshot)?;
+    summary.snapshot = args.snapshot.display().to_string();
+    summary.dry_run = args.dry_run;
+    drop(current);
+    drop(snapshot);
+    if args.dry_run || summary.is_empty() {
+        return Ok(summary);
+    }
+
+    let backup = tracer::backup::create_automatic(db_path, "pre-restore")?;
+    summary.backup = backup.map(|path| path.display().to_string());
+    tracer::backup::backup_database(staging, db_path)?;
+
+    // Rewrite the JSONL files from the restored database; otherwise the next
+    // auto-import would bring the discarded changes straight back
+    let mut storage: Box<dyn Storage> = Box::new(SqliteStorage::new(db_path)?);
+    super::export::export_all(&mut storage, &tracer::find_jsonl_path(db_path))?;
+    super::export::auto_export_milestones(&mut storage, &tracer::find_milestones_path(db_path))?;
+    super::export::export_archive(&mut storage, &tracer::find_archive_path(db_path))?;
+
+    Ok(summary)
+}
+
+fn compare(current: &dyn Storage, snapshot: &dyn Storage) -> Result<RestoreSummary> {
+    let mut current_issues: HashMap<String, Issue> = current.search_issues("", &IssueFilter::default())?
+        .into_iter()
+        .map(|issue| (issue.id.clone(), issue))
+        .collect();
+
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    for issue in snapshot.search_issues("", &IssueFilter::default())? {
+        match current_issues.remove(&issue.id) {
+            None => added.push(issue.id),
+            Some(existing) => {
+                let deps_differ = dependency_keys(&current.get_dependency_records(&issue.id)?)
+                    != dependency_keys(&snapshot.get_dependency_records(&issue.id)?);
+                if existing.updated_at != issue.updated_at
+                    || super::export::issue_differs(&existing, &issue)
+                    || deps_differ
+                {
+                    changed.push(issue.id);
+                }
+            }
+        }
+    }
+    let mut removed: Vec<String> = current_issues.into_keys().collect();
+    for ids in [&mut added, &mut removed, &mut changed] {
+        ids.sort();
+    }
+
+    Ok(RestoreSummary {
+        snapshot: String::new(),
+        added,
+        removed,
+        changed,
+        events_before: current.get_all_events(&EventFilter::default())?.len(),
+        events_after: snapshot.get_all_events(&EventFilter::default())?.len(),
+        milestones_before: current.list_milestones()?.len(),
+        milestones_after: snapshot.list_milestones()?.len(),
+        backup: None,
+        dry_run: false,
+    })
+}
+
+fn dependency_keys(dependencies: &[Dependency]) -> Vec<(String, String)> {
+    let mut keys: Vec<(String, String)> = dependencies.iter()
+        .map(|dep| (dep.depends_on_id.clone(), dep.dep_type.to_string()))
+        .collect();
+    keys.sort();
+    keys
+}
+
+fn print_ids(verb: &str, what: &str, ids: &[String]) {
+    if ids.is_empty() {
+        return;
+    }
+    const SHOWN: usize = 10;
+    let mut list = ids.iter().take(SHOWN).cloned().collect::<Vec<_>>().join(", ");
+    if ids.len() > SHOWN {
+        list.push_str(&format!(", and {} more", ids.len() - SHOWN));
+    }
+    println!("  {} {} {}: {}", verb, ids.len(), what, list);
+}
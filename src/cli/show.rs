@@ -1,6 +1,7 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use clap::Args;
 use tracer::storage::Storage;
+use tracer::storage::memory::InMemoryStorage;
 use chrono::{DateTime, Utc};
 
 #[derive(Args)]
@@ -14,14 +15,35 @@ pub struct ShowArgs {
 }
 
 pub fn execute(args: ShowArgs, storage: &dyn Storage, json: bool) -> Result<()> {
-    let issue = storage.get_issue(&args.id)?
-        .context(format!("Issue {} not found", args.id))?;
+    // Archived issues are shown from a storage holding just that issue
+    if storage.get_issue(&args.id)?.is_none() {
+        if let Some(archived) = storage.get_archived_issue(&args.id)? {
+            let archived_at = archived.archived_at;
+            let archive = InMemoryStorage::from_archive(vec![archived]);
+            return show(args, &archive, Some(archived_at), json);
+        }
+    }
+    show(args, storage, None, json)
+}
+
+fn show(args: ShowArgs, storage: &dyn Storage, archived_at: Option<DateTime<Utc>>, json: bool) -> Result<()> {
+    let mut issue = storage.get_issue(&args.id)?
+        .ok_or_else(|| tracer::Error::not_found("Issue", &args.id))?;
+    issue.work_logs = storage.get_work_logs(&args.id)?;
 
     if json {
-        println!("{}", serde_json::to_string_pretty(&issue)?);
+        let mut value = serde_json::to_value(&issue)?;
+        if let Some(archived_at) = archived_at {
+            value["archived_at"] = serde_json::to_value(archived_at)?;
+        }
+        println!("{}", serde_json::to_string_pretty(&value)?);
     } else {
         print!("{}", tracer::utils::format_issue(&issue, true));
 
+        if let Some(archived_at) = archived_at {
+            println!("\n  Archived: {}", archived_at.format("%Y-%m-%d"));
+        }
+
         // Show labels
         let labels = storage.get_labels(&args.id)?;
         if !labels.is_empty() {
@@ -37,6 +59,41 @@ pub fn execute(args: ShowArgs, storage: &dyn Storage, json: bool) -> Result<()>
             }
         }
 
+        // Show time spent against the estimate
+        if !issue.work_logs.is_empty() || issue.estimated_minutes.is_some() {
+            use colored::Colorize;
+            use tracer::utils::format_minutes;
+
+            let logged: i64 = issue.work_logs.iter().map(|l| l.minutes).sum();
+            print!("\n  Time: {} logged", format_minutes(logged));
+            if let Some(estimate) = issue.estimated_minutes {
+                print!(" / {} estimated", format_minutes(estimate as i64));
+                if estimate > 0 {
+                    let percent = logged * 100 / estimate as i64;
+                    let percent = format!("({}%)", percent);
+                    if logged > estimate as i64 { print!(" {}", percent.red()) } else { print!(" {}", percent) }
+                }
+            }
+            println!();
+            if let Some(running) = issue.work_logs.iter().find(|l| l.ended_at.is_none()) {
+                println!("    {} tracking since {} ({})",
+                    "⏱".yellow(),
+                    running.started_at.format("%Y-%m-%d %H:%M"),
+                    format_time_ago(&running.started_at)
+                );
+            }
+            for log in issue.work_logs.iter().filter(|l| l.ended_at.is_some()) {
+                let note = if log.note.is_empty() { String::new() } else { format!(": {}", log.note) };
+                println!("    {} {} by {} {}{}",
+                    log.started_at.format("%Y-%m-%d"),
+                    format_minutes(log.minutes),
+                    log.actor.cyan(),
+                    format!("({})", log.source).dimmed(),
+                    note
+                );
+            }
+        }
+
         // Show recent comments (always visible, not just with --full)
         let events = storage.get_events(&args.id, 20)?;
         let comments: Vec<_> = events.iter()
@@ -57,18 +114,16 @@ pub fn execute(args: ShowArgs, storage: &dyn Storage, json: bool) -> Result<()>
         }
 
         // Show all events if --full
-        if args.full {
-            if !events.is_empty() {
-                println!("\n  Recent Events:");
-                for event in events {
-                    println!("    [{}] {} by {}", 
-                        event.created_at.format("%Y-%m-%d %H:%M"),
-                        event.event_type,
-                        event.actor
-                    );
-                    if let Some(comment) = &event.comment {
-                        println!("      {}", comment);
-                    }
+        if args.full && !events.is_empty() {
+            println!("\n  Recent Events:");
+            for event in events {
+                println!("    [{}] {} by {}", 
+                    event.created_at.format("%Y-%m-%d %H:%M"),
+                    event.event_type,
+                    event.actor
+                );
+                if let Some(comment) = &event.comment {
+                    println!("      {}", comment);
                 }
             }
         }
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use clap::Args;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tracer::storage::{IssueUpdates, Storage};
+use tracer::types::*;
+
+#[derive(Args)]
+pub struct StaleArgs {
+    /// Days without activity before an issue counts as stale
+    #[arg(long, default_value = "7")]
+    pub days: i64,
+
+    /// Only consider issues with this status (default: all unclosed)
+    #[arg(long, value_parser = clap::value_parser!(Status))]
+    pub status: Option<Status>,
+
+    /// Reset stale in_progress issues to open and unassign them
+    #[arg(long)]
+    pub release: bool,
+}
+
+#[derive(Serialize)]
+struct StaleGroup {
+    assignee: String,
+    issues: Vec<StaleIssue>,
+}
+
+#[derive(Serialize)]
+struct StaleReport {
+    days: i64,
+    groups: Vec<StaleGroup>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    released: Vec<String>,
+}
+
+pub fn execute(args: StaleArgs, storage: &mut Box<dyn Storage>, actor: &str, json: bool) -> Result<()> {
+    if args.days < 0 {
+        anyhow::bail!("--days cannot be negative");
+    }
+
+    let inactive_since = Duration::try_days(args.days)
+        .and_then(|days| Utc::now().checked_sub_signed(days))
+        .ok_or_else(|| tracer::Error::Validation(format!("--days {} is out of range", args.days)))?;
+    if let Some(status) = &args.status {
+        storage.get_workflow()?.check_known(status)?;
+    }
+    let stale = storage.get_stale_issues(inactive_since, args.status)?;
+
+    let mut released = Vec::new();
+    if args.release {
+        for si in stale.iter().filter(|si| si.issue.status == Status::InProgress) {
+            let updates = IssueUpdates {
+                status: Some(Status::Open),
+                assignee: Some(String::new()),
+                bypass_workflow: true, // Releasing an abandoned claim is an administrative reset
+                ..Default::default()
+            };
+            storage.update_issue(&si.issue.id, &updates, actor)?;
+
+            let previous = if si.issue.assignee.is_empty() { "unassigned".to_string() } else { si.issue.assignee.clone() };
+            let comment = format!(
+                "Released stale claim ({}): no activity since {} (threshold {} days)",
+                previous,
+                si.last_activity.format("%Y-%m-%d %H:%M"),
+                args.days
+            );
+            storage.add_comment(&si.issue.id, actor, &comment)?;
+            released.push(si.issue.id.clone());
+        }
+    }
+
+    // Group by assignee (BTreeMap keeps the output order stable)
+    let mut by_assignee: BTreeMap<String, Vec<StaleIssue>> = BTreeMap::new();
+    for si in stale {
+        by_assignee.entry(si.issue.assignee.clone()).or_default().push(si);
+    }
+
+    if json {
+        let report = StaleReport {
+            days: args.days,
+            groups: by_assignee.into_iter()
+                .map(|(assignee, issues)| StaleGroup { assignee, issues })
+                .collect(),
+            released,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        use colored::Colorize;
+
+        if by_assignee.is_empty() {
+            println!("{} No stale issues (no activity in the last {} days)", "✓".green(), args.days);
+            return Ok(());
+        }
+
+        let total: usize = by_assignee.values().map(|issues| issues.len()).sum();
+        println!("{} Stale: {} issue(s) with no activity in {} days\n", "⚠".yellow(), total, args.days);
+
+        let now = Utc::now();
+        for (assignee, issues) in by_assignee {
+            let name = if assignee.is_empty() { "(unassigned)".to_string() } else { assignee };
+            println!("{} ({})", name.bold(), issues.len());
+            for si in issues {
+                let idle_days = now.signed_duration_since(si.last_activity).num_days();
+                let marker = if released.contains(&si.issue.id) { " → released".yellow().to_string() } else { String::new() };
+                println!("  {} {} [{}] {}{}",
+                    si.issue.id.cyan(),
+                    si.issue.title,
+                    si.issue.status,
+                    format!("(idle {} days)", idle_days).dimmed(),
+                    marker
+                );
+            }
+            println!();
+        }
+
+        if args.release && released.is_empty() {
+            println!("No stale in_progress issues to release");
+        } else if !released.is_empty() {
+            println!("✓ Released {} issue(s) back to open", released.len());
+        }
+    }
+
+    Ok(())
+}
@@ -1,11 +1,29 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use clap::Args;
 use tracer::storage::Storage;
+use tracer::types::*;
 
 #[derive(Args)]
-pub struct StatsArgs {}
+pub struct StatsArgs {
+    /// Show trends over time (opened/closed, cumulative flow, cycle time, throughput)
+    #[arg(long)]
+    pub history: bool,
+
+    /// Start of the history window (YYYY-MM-DD, RFC 3339, or relative like 30d)
+    #[arg(long, default_value = "30d", value_parser = tracer::utils::parse_date_spec, requires = "history")]
+    pub since: DateTime<Utc>,
+
+    /// History bucket size (day, week)
+    #[arg(long, default_value = "day", value_parser = clap::value_parser!(Bucket), requires = "history")]
+    pub bucket: Bucket,
+}
+
+pub fn execute(args: StatsArgs, storage: &dyn Storage, json: bool) -> Result<()> {
+    if args.history {
+        return execute_history(args, storage, json);
+    }
 
-pub fn execute(_args: StatsArgs, storage: &dyn Storage, json: bool) -> Result<()> {
     let stats = storage.get_statistics()?;
 
     if json {
@@ -24,8 +42,120 @@ pub fn execute(_args: StatsArgs, storage: &dyn Storage, json: bool) -> Result<()
         println!("  Ready to Work:     {}", stats.ready_issues.to_string().bold().green());
         println!();
         println!("  Avg Lead Time:     {:.1} hours", stats.average_lead_time_hours);
+        println!();
+        println!("{}", "Due Dates".bold());
+        println!();
+        println!("  Overdue:           {}", stats.overdue_issues.to_string().red());
+        println!("  Due in 7 Days:     {}", stats.due_soon_issues.to_string().yellow());
+        if let Some(next_due_at) = stats.next_due_at {
+            println!("  Next Due:          {}", next_due_at.format("%Y-%m-%d %H:%M"));
+        }
+
+        if !stats.estimation_by_type.is_empty() {
+            println!();
+            println!("{}", "Estimation Accuracy (logged / estimated, closed issues)".bold());
+            println!();
+            for (issue_type, accuracy) in &stats.estimation_by_type {
+                print_accuracy(issue_type, accuracy);
+            }
+            println!();
+            for (assignee, accuracy) in &stats.estimation_by_actor {
+                let name = if assignee.is_empty() { "(unassigned)" } else { assignee };
+                print_accuracy(name, accuracy);
+            }
+        }
     }
 
     Ok(())
 }
 
+
+fn print_accuracy(name: &str, accuracy: &EstimationAccuracy) {
+    use tracer::utils::format_minutes;
+    println!("  {:<18} {:.2}x  ({} / {} over {} issue(s))",
+        format!("{}:", name),
+        accuracy.ratio,
+        format_minutes(accuracy.actual_minutes),
+        format_minutes(accuracy.estimated_minutes),
+        accuracy.issues
+    );
+}
+
+fn execute_history(args: StatsArgs, storage: &dyn Storage, json: bool) -> Result<()> {
+    let until = Utc::now();
+    if args.since >= until {
+        anyhow::bail!("--since must be in the past");
+    }
+
+    // Replay the whole trail so statuses at the start of the window are known
+    let events = storage.get_all_events(&EventFilter {
+        until: Some(until),
+        ..Default::default()
+    })?;
+    let history = tracer::analytics::compute_history(&events, args.since, until, args.bucket);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&history)?);
+        return Ok(());
+    }
+
+    use colored::Colorize;
+
+    println!("{} (by {}, since {})", "Issue History".bold(), history.bucket, history.since.format("%Y-%m-%d"));
+    println!();
+
+    const BAR_WIDTH: i32 = 30;
+    let max = history.buckets.iter().map(|b| b.opened.max(b.closed)).max().unwrap_or(0).max(1);
+    let bar = |n: i32| "█".repeat(((n * BAR_WIDTH + max - 1) / max) as usize);
+
+    println!("  {:<10}  {:>6}  {:>6}", "", "opened".green(), "closed".dimmed());
+    for bucket in &history.buckets {
+        println!("  {:<10}  {:>6}  {:>6}  {}",
+            bucket.start.format("%Y-%m-%d"),
+            bucket.opened,
+            bucket.closed,
+            bar(bucket.opened).green()
+        );
+        println!("  {:<10}  {:>6}  {:>6}  {}", "", "", "", bar(bucket.closed).dimmed());
+    }
+
+    println!();
+    println!("{}", "Cumulative Flow (end of window)".bold());
+    println!();
+    match history.buckets.last() {
+        Some(last) if !last.status_counts.is_empty() => {
+            for (status, count) in &last.status_counts {
+                println!("  {:<18} {}", format!("{}:", status), count);
+            }
+        }
+        _ => println!("  {}", "No issues".dimmed()),
+    }
+
+    println!();
+    println!("{}", "Cycle Time (in_progress → closed)".bold());
+    println!();
+    let cycle = &history.cycle_time;
+    if cycle.count == 0 {
+        println!("  {}", "No issues closed after being in progress".dimmed());
+    } else {
+        println!("  Issues:            {}", cycle.count);
+        for (label, value) in [("p50", cycle.p50_hours), ("p75", cycle.p75_hours), ("p90", cycle.p90_hours), ("p95", cycle.p95_hours)] {
+            if let Some(hours) = value {
+                println!("  {:<18} {:.1} hours", format!("{}:", label), hours);
+            }
+        }
+    }
+
+    println!();
+    println!("{}", "Throughput by Actor".bold());
+    println!();
+    if history.throughput_by_actor.is_empty() {
+        println!("  {}", "No issues closed".dimmed());
+    } else {
+        for (actor, closed) in &history.throughput_by_actor {
+            println!("  {:<18} {} closed", actor.cyan(), closed);
+        }
+    }
+
+    Ok(())
+}
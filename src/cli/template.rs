@@ -0,0 +1,78 @@
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use std::path::Path;
+
+#[derive(Subcommand)]
+pub enum TemplateCommands {
+    /// List available templates
+    List,
+
+    /// Show a template definition
+    Show(ShowArgs),
+}
+
+#[derive(Args)]
+pub struct ShowArgs {
+    /// Template name
+    pub name: String,
+}
+
+pub fn execute_list(templates_dir: &Path, json: bool) -> Result<()> {
+    let names = tracer::templates::list_templates(templates_dir)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&names)?);
+    } else {
+        if names.is_empty() {
+            println!("No templates found in {}", templates_dir.display());
+            return Ok(());
+        }
+
+        use colored::Colorize;
+        for name in names {
+            println!("{}", name.cyan());
+        }
+    }
+
+    Ok(())
+}
+
+pub fn execute_show(args: ShowArgs, templates_dir: &Path, json: bool) -> Result<()> {
+    let template = tracer::templates::load_template(templates_dir, &args.name)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&template)?);
+    } else {
+        use colored::Colorize;
+
+        println!("{}", args.name.bold().cyan());
+        if let Some(title) = &template.title {
+            println!("  Title:    {}", title);
+        }
+        if let Some(issue_type) = template.issue_type {
+            println!("  Type:     {}", issue_type);
+        }
+        if let Some(priority) = template.priority {
+            println!("  Priority: P{}", priority);
+        }
+        if !template.labels.is_empty() {
+            println!("  Labels:   {}", template.labels.join(", "));
+        }
+        for (heading, text) in [("Description", &template.description), ("Design", &template.design), ("Acceptance Criteria", &template.acceptance_criteria)] {
+            if !text.is_empty() {
+                println!("\n  {}:", heading);
+                for line in text.lines() {
+                    println!("    {}", line);
+                }
+            }
+        }
+        if !template.children.is_empty() {
+            println!("\n  Children:");
+            for child in &template.children {
+                println!("    {}", child.title);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
 use clap::Args;
 use tracer::storage::{IssueUpdates, Storage};
 use tracer::types::*;
@@ -31,6 +32,22 @@ pub struct UpdateArgs {
     /// New assignee
     #[arg(long)]
     pub assignee: Option<String>,
+
+    /// New due date (YYYY-MM-DD, RFC 3339, or relative like 3d)
+    #[arg(long, value_parser = tracer::utils::parse_due_spec, conflicts_with = "clear_due")]
+    pub due: Option<DateTime<Utc>>,
+
+    /// Remove the due date
+    #[arg(long)]
+    pub clear_due: bool,
+
+    /// Move to a milestone
+    #[arg(long, conflicts_with = "clear_milestone")]
+    pub milestone: Option<String>,
+
+    /// Remove the issue from its milestone
+    #[arg(long)]
+    pub clear_milestone: bool,
 }
 
 #[derive(Args)]
@@ -52,16 +69,37 @@ pub struct CommentArgs {
     pub comment: String,
 }
 
+#[derive(Args)]
+pub struct LogArgs {
+    /// Issue ID
+    pub id: String,
+
+    /// Time spent (e.g. 45m, 2h, 1h30m)
+    #[arg(value_parser = tracer::utils::parse_work_duration)]
+    pub duration: i64,
+
+    /// What the time was spent on
+    #[arg(long)]
+    pub note: Option<String>,
+}
+
 pub fn execute_update(args: UpdateArgs, storage: &mut Box<dyn Storage>, actor: &str, json: bool) -> Result<()> {
     // Verify issue exists
     let issue = storage.get_issue(&args.id)?
-        .context(format!("Issue {} not found", args.id))?;
+        .ok_or_else(|| tracer::Error::not_found("Issue", &args.id))?;
 
-    // Auto-set assignee if status changes to in_progress and no assignee specified
+    if let Some(milestone) = &args.milestone {
+        storage.get_milestone(milestone)?
+            .ok_or_else(|| tracer::Error::not_found("Milestone", milestone))?;
+    }
+
+    // Starting work without naming an assignee claims the issue for the actor,
+    // unless someone else holds it (e.g. another agent claimed it first)
     let assignee = if args.assignee.is_some() {
         args.assignee
-    } else if args.status == Some(Status::InProgress) && issue.assignee.is_empty() {
-        Some(actor.to_string())
+    } else if args.status == Some(Status::InProgress) {
+        issue.check_claim(actor)?;
+        issue.assignee.is_empty().then(|| actor.to_string())
     } else {
         None
     };
@@ -78,6 +116,9 @@ pub fn execute_update(args: UpdateArgs, storage: &mut Box<dyn Storage>, actor: &
         assignee,
         estimated_minutes: None,
         external_ref: None,
+        due_at: if args.clear_due { Some(None) } else { args.due.map(Some) },
+        milestone: if args.clear_milestone { Some(None) } else { args.milestone.map(Some) },
+        bypass_workflow: false,
     };
 
     storage.update_issue(&args.id, &updates, actor)?;
@@ -99,7 +140,7 @@ pub fn execute_close(args: CloseArgs, storage: &mut Box<dyn Storage>, actor: &st
     for id in &args.ids {
         // Verify issue exists
         storage.get_issue(id)?
-            .context(format!("Issue {} not found", id))?;
+            .ok_or_else(|| tracer::Error::not_found("Issue", id))?;
         
         storage.close_issue(id, &args.reason, actor)?;
         closed.push(id.clone());
@@ -123,7 +164,7 @@ pub fn execute_close(args: CloseArgs, storage: &mut Box<dyn Storage>, actor: &st
 pub fn execute_comment(args: CommentArgs, storage: &mut Box<dyn Storage>, actor: &str, json: bool) -> Result<()> {
     // Verify issue exists
     storage.get_issue(&args.id)?
-        .context(format!("Issue {} not found", args.id))?;
+        .ok_or_else(|| tracer::Error::not_found("Issue", &args.id))?;
 
     storage.add_comment(&args.id, actor, &args.comment)?;
 
@@ -138,3 +179,41 @@ pub fn execute_comment(args: CommentArgs, storage: &mut Box<dyn Storage>, actor:
     Ok(())
 }
 
+
+pub fn execute_log(args: LogArgs, storage: &mut Box<dyn Storage>, actor: &str, json: bool) -> Result<()> {
+    let issue = storage.get_issue(&args.id)?
+        .ok_or_else(|| tracer::Error::not_found("Issue", &args.id))?;
+
+    let now = Utc::now();
+    let started_at = chrono::TimeDelta::try_minutes(args.duration)
+        .and_then(|duration| now.checked_sub_signed(duration))
+        .ok_or_else(|| tracer::Error::Validation(format!("duration is too long: {} minutes", args.duration)))?;
+    let log = WorkLog {
+        issue_id: args.id.clone(),
+        actor: actor.to_string(),
+        minutes: args.duration,
+        note: args.note.unwrap_or_default(),
+        started_at,
+        ended_at: Some(now),
+        source: WorkLogSource::Manual,
+    };
+    storage.add_work_log(&log)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&log)?);
+    } else {
+        use colored::Colorize;
+        let logged: i64 = storage.get_work_logs(&args.id)?.iter().map(|l| l.minutes).sum();
+        print!("✓ Logged {} on {} ({} total",
+            tracer::utils::format_minutes(log.minutes),
+            args.id.bold().cyan(),
+            tracer::utils::format_minutes(logged)
+        );
+        if let Some(estimate) = issue.estimated_minutes {
+            print!(" of {} estimated", tracer::utils::format_minutes(estimate as i64));
+        }
+        println!(")");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use tracer::storage::Storage;
+use tracer::types::*;
+
+#[derive(Subcommand)]
+pub enum WorkflowCommands {
+    /// Show statuses and allowed transitions
+    Show,
+
+    /// Add a custom status
+    AddStatus(AddStatusArgs),
+
+    /// Remove a custom status (and any transitions using it)
+    RemoveStatus(RemoveStatusArgs),
+
+    /// Allow a status transition
+    Allow(TransitionArgs),
+
+    /// Disallow a previously allowed status transition
+    Disallow(TransitionArgs),
+
+    /// Remove all custom statuses and transition rules
+    Reset,
+}
+
+#[derive(Args)]
+pub struct AddStatusArgs {
+    /// Status name (lowercase letters, digits, '_' or '-')
+    pub name: String,
+
+    /// Count this status as done (it no longer blocks dependents)
+    #[arg(long)]
+    pub done: bool,
+}
+
+#[derive(Args)]
+pub struct RemoveStatusArgs {
+    /// Status name
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct TransitionArgs {
+    /// Status to transition from
+    #[arg(value_parser = clap::value_parser!(Status))]
+    pub from: Status,
+
+    /// Status to transition to
+    #[arg(value_parser = clap::value_parser!(Status))]
+    pub to: Status,
+}
+
+pub fn execute_show(storage: &dyn Storage, json: bool) -> Result<()> {
+    let workflow = storage.get_workflow()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&workflow)?);
+    } else {
+        use colored::Colorize;
+
+        println!("{}", "Statuses".bold());
+        for status in workflow.all_statuses() {
+            let kind = if status.is_builtin() { "built-in" } else { "custom" };
+            let done = if workflow.is_done(&status) { ", done" } else { "" };
+            println!("  {:<16} {}", status.to_string().cyan(), format!("({}{})", kind, done).dimmed());
+        }
+
+        println!();
+        println!("{}", "Transitions".bold());
+        if workflow.transitions.is_empty() {
+            println!("  {}", "Any transition is allowed".dimmed());
+        } else {
+            for transition in &workflow.transitions {
+                println!("  {} → {}", transition.from.to_string().cyan(), transition.to.to_string().cyan());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn execute_add_status(args: AddStatusArgs, storage: &mut Box<dyn Storage>, json: bool) -> Result<()> {
+    let mut workflow = storage.get_workflow()?;
+    if workflow.statuses.iter().any(|s| s.name == args.name) {
+        anyhow::bail!("status {} already exists", args.name);
+    }
+
+    workflow.statuses.push(WorkflowStatus { name: args.name.clone(), done: args.done });
+    storage.set_workflow(&workflow)?;
+
+    print_result(&workflow, &format!("Added status {}", args.name), json)
+}
+
+pub fn execute_remove_status(args: RemoveStatusArgs, storage: &mut Box<dyn Storage>, json: bool) -> Result<()> {
+    let mut workflow = storage.get_workflow()?;
+    if !workflow.statuses.iter().any(|s| s.name == args.name) {
+        anyhow::bail!("no custom status named {}", args.name);
+    }
+
+    // Refuse to orphan issues that are currently in this status
+    let status = Status::Custom(args.name.clone());
+    let filter = IssueFilter {
+        status: Some(status.clone()),
+        ..Default::default()
+    };
+    let in_use = storage.search_issues("", &filter)?;
+    if !in_use.is_empty() {
+        let ids: Vec<&str> = in_use.iter().map(|i| i.id.as_str()).collect();
+        anyhow::bail!("status {} is in use by {} issue(s): {}", args.name, ids.len(), ids.join(", "));
+    }
+
+    workflow.statuses.retain(|s| s.name != args.name);
+    workflow.transitions.retain(|t| t.from != status && t.to != status);
+    storage.set_workflow(&workflow)?;
+
+    print_result(&workflow, &format!("Removed status {}", args.name), json)
+}
+
+pub fn execute_allow(args: TransitionArgs, storage: &mut Box<dyn Storage>, json: bool) -> Result<()> {
+    let mut workflow = storage.get_workflow()?;
+    let transition = Transition { from: args.from, to: args.to };
+    if !workflow.transitions.contains(&transition) {
+        workflow.transitions.push(transition.clone());
+    }
+    storage.set_workflow(&workflow)?;
+
+    print_result(&workflow, &format!("Allowed {} → {}", transition.from, transition.to), json)
+}
+
+pub fn execute_disallow(args: TransitionArgs, storage: &mut Box<dyn Storage>, json: bool) -> Result<()> {
+    let mut workflow = storage.get_workflow()?;
+    let transition = Transition { from: args.from, to: args.to };
+    if !workflow.transitions.contains(&transition) {
+        anyhow::bail!("transition {} → {} is not in the workflow", transition.from, transition.to);
+    }
+    workflow.transitions.retain(|t| t != &transition);
+    storage.set_workflow(&workflow)?;
+
+    let mut message = format!("Disallowed {} → {}", transition.from, transition.to);
+    if workflow.transitions.is_empty() {
+        message.push_str(" (no rules left: any transition is now allowed)");
+    }
+    print_result(&workflow, &message, json)
+}
+
+pub fn execute_reset(storage: &mut Box<dyn Storage>, json: bool) -> Result<()> {
+    let current = storage.get_workflow()?;
+    for custom in &current.statuses {
+        let filter = IssueFilter {
+            status: Some(Status::Custom(custom.name.clone())),
+            ..Default::default()
+        };
+        if !storage.search_issues("", &filter)?.is_empty() {
+            anyhow::bail!("status {} is still in use; move those issues before resetting", custom.name);
+        }
+    }
+
+    let workflow = Workflow::default();
+    storage.set_workflow(&workflow)?;
+
+    print_result(&workflow, "Reset workflow to defaults", json)
+}
+
+fn print_result(workflow: &Workflow, message: &str, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(workflow)?);
+    } else {
+        println!("✓ {}", message);
+    }
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use rusqlite::ffi;
+
+/// Error is what the storage layer returns, so library callers can tell a
+/// missing issue from a conflicting write or a corrupt database
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The issue, milestone or other record doesn't exist
+    #[error("{kind} {id} not found")]
+    NotFound { kind: &'static str, id: String },
+
+    /// The write clashes with existing data, e.g. a duplicate issue ID or dependency
+    #[error("{0}")]
+    Conflict(String),
+
+    /// Another actor already holds the issue being claimed
+    #[error("{id} is already claimed by {holder}")]
+    ClaimConflict { id: String, holder: String },
+
+    /// A field value or status change breaks a rule
+    #[error("{0}")]
+    Validation(String),
+
+    /// A dependency would make an issue depend on itself
+    #[error("dependency cycle: {0}")]
+    Cycle(String),
+
+    /// The database holds data this version can't read
+    #[error("invalid data in database: {0}")]
+    Schema(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// Another process holds the database lock
+    #[error("database is locked by another process")]
+    Locked,
+
+    /// Any other SQLite failure
+    #[error(transparent)]
+    Database(rusqlite::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    pub fn not_found(kind: &'static str, id: &str) -> Self {
+        Error::NotFound { kind, id: id.to_string() }
+    }
+
+    /// Stable machine-readable name for this kind of error, used in `--json` output
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::NotFound { .. } => "not_found",
+            Error::Conflict(_) => "conflict",
+            Error::ClaimConflict { .. } => "claim_conflict",
+            Error::Validation(_) => "validation",
+            Error::Cycle(_) => "cycle",
+            Error::Schema(_) => "schema",
+            Error::Io(_) => "io",
+            Error::Locked => "locked",
+            Error::Database(_) => "database",
+        }
+    }
+
+    /// The issue the error is about, when it names one
+    pub fn issue_id(&self) -> Option<&str> {
+        match self {
+            Error::NotFound { kind: "Issue", id } | Error::ClaimConflict { id, .. } => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Stable process exit code for this kind of error (1 is used for anything
+    /// that isn't a tracer::Error, 2 by clap for usage errors)
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::NotFound { .. } => 3,
+            Error::Conflict(_) => 4,
+            Error::Validation(_) => 5,
+            Error::Cycle(_) => 6,
+            Error::Schema(_) => 7,
+            Error::Io(_) => 8,
+            Error::Database(_) => 9,
+            Error::Locked => 10,
+            Error::ClaimConflict { .. } => 11,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        match &err {
+            rusqlite::Error::SqliteFailure(failure, message) if failure.code == ffi::ErrorCode::ConstraintViolation => {
+                let message = message.clone().unwrap_or_else(|| err.to_string());
+                match failure.extended_code {
+                    ffi::SQLITE_CONSTRAINT_PRIMARYKEY | ffi::SQLITE_CONSTRAINT_UNIQUE => Error::Conflict(message),
+                    ffi::SQLITE_CONSTRAINT_FOREIGNKEY => {
+                        Error::Validation("references an issue or milestone that does not exist".to_string())
+                    }
+                    _ => Error::Validation(message),
+                }
+            }
+            rusqlite::Error::SqliteFailure(failure, _)
+                if matches!(failure.code, ffi::ErrorCode::DatabaseBusy | ffi::ErrorCode::DatabaseLocked) =>
+            {
+                Error::Locked
+            }
+            rusqlite::Error::FromSqlConversionFailure(..) | rusqlite::Error::InvalidColumnType(..) => {
+                Error::Schema(err.to_string())
+            }
+            _ => Error::Database(err),
+        }
+    }
+}
+
+// Storage only reads JSON it wrote itself (config values, label lists), so a
+// parse failure means the stored data is bad
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Schema(err.to_string())
+    }
+}
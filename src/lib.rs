@@ -1,15 +1,21 @@
+pub mod analytics;
+pub mod backup;
+pub mod error;
+pub mod schedule;
+pub mod similarity;
 pub mod storage;
+pub mod templates;
 pub mod types;
 pub mod utils;
 
+pub use error::{Error, Result};
 pub use storage::Storage;
 pub use types::*;
 
-use anyhow::Result;
 use std::path::{Path, PathBuf};
 
 /// Find the database path using the standard search order
-pub fn find_database_path() -> Result<PathBuf> {
+pub fn find_database_path() -> anyhow::Result<PathBuf> {
     utils::find_database_path()
 }
 
@@ -18,9 +24,34 @@ pub fn find_jsonl_path(db_path: &Path) -> PathBuf {
     utils::find_jsonl_path(db_path)
 }
 
-/// Open a storage backend at the given path
-pub fn open_storage(path: &PathBuf) -> Result<Box<dyn Storage>> {
-    Ok(Box::new(storage::sqlite::SqliteStorage::new(path)?))
+/// Find the milestones JSONL path for a given database
+pub fn find_milestones_path(db_path: &Path) -> PathBuf {
+    utils::find_milestones_path(db_path)
+}
+
+/// Find the archived issues JSONL path for a given database
+pub fn find_archive_path(db_path: &Path) -> PathBuf {
+    utils::find_archive_path(db_path)
+}
+
+/// Find the templates directory for a given database
+pub fn find_templates_dir(db_path: &Path) -> PathBuf {
+    utils::find_templates_dir(db_path)
+}
+
+/// Find the backups directory for a given database
+pub fn find_backups_dir(db_path: &Path) -> PathBuf {
+    utils::find_backups_dir(db_path)
+}
+
+/// Open a storage backend at the given path (SQLite, or the JSONL backend for a `.json` store)
+pub fn open_storage(path: &Path) -> Result<Box<dyn Storage>> {
+    storage::Backend::for_path(path).open(path)
+}
+
+/// Open an empty storage backend that lives only in memory (for tests and embedding)
+pub fn open_in_memory() -> Box<dyn Storage> {
+    Box::new(storage::memory::InMemoryStorage::new())
 }
 
 /// Get the current actor name
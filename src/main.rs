@@ -2,11 +2,61 @@ mod cli;
 
 use anyhow::Result;
 use clap::Parser;
-use tracer::{find_database_path, find_jsonl_path, storage::sqlite::SqliteStorage};
+use tracer::{find_database_path, find_jsonl_path, find_milestones_path, storage::Backend};
 
-fn main() -> Result<()> {
-    let cli = cli::Cli::parse();
+fn main() {
+    // --json is checked on the raw arguments so usage errors can be reported as JSON too
+    let json = std::env::args().any(|arg| arg == "--json");
 
+    let cli = match cli::Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) if json && err.use_stderr() => {
+            // First paragraph of clap's report, without the usage and help hints
+            let rendered = err.render().to_string();
+            let message: Vec<&str> = rendered.lines()
+                .take_while(|line| !line.trim().is_empty())
+                .map(str::trim)
+                .collect();
+            print_json_error("usage", message.join(" ").trim_start_matches("error: "), None);
+            std::process::exit(2);
+        }
+        Err(err) => err.exit(),
+    };
+
+    if let Err(err) = run(cli) {
+        if json {
+            let tracer_err = tracer_error(&err);
+            let code = tracer_err.map_or("error", tracer::Error::code);
+            print_json_error(code, &format!("{:#}", err), tracer_err.and_then(tracer::Error::issue_id));
+        } else {
+            eprintln!("Error: {:?}", err);
+        }
+        std::process::exit(exit_code(&err));
+    }
+}
+
+/// Print `{"error": {"code", "message", "issue_id"}}` to stdout, where callers
+/// using --json already read results
+fn print_json_error(code: &str, message: &str, issue_id: Option<&str>) {
+    let mut error = serde_json::json!({ "code": code, "message": message });
+    if let Some(issue_id) = issue_id {
+        error["issue_id"] = issue_id.into();
+    }
+    println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "error": error })).unwrap_or_default());
+}
+
+/// The first tracer::Error in the error chain, if any
+fn tracer_error(err: &anyhow::Error) -> Option<&tracer::Error> {
+    err.chain().find_map(|cause| cause.downcast_ref::<tracer::Error>())
+}
+
+/// Exit code for a failed command: the kind of the first tracer::Error in the
+/// error chain, or 1 for anything else
+fn exit_code(err: &anyhow::Error) -> i32 {
+    tracer_error(err).map_or(1, tracer::Error::exit_code)
+}
+
+fn run(cli: cli::Cli) -> Result<()> {
     // Handle init command separately (doesn't need existing database)
     if let cli::Commands::Init(args) = cli.command {
         return cli::init::execute(args);
@@ -24,8 +74,28 @@ fn main() -> Result<()> {
         find_database_path()?
     };
 
-    // Open storage
-    let mut storage: Box<dyn tracer::Storage> = Box::new(SqliteStorage::new(&db_path)?);
+    // Handle migrate command before opening storage, which would apply migrations itself
+    if let cli::Commands::Migrate(args) = cli.command {
+        return cli::migrate::execute(args, &db_path, cli.json);
+    }
+
+    // Handle backup and restore commands, which copy the database file itself
+    if let cli::Commands::Backup(args) = cli.command {
+        return cli::backup::execute(args, &db_path, cli.json);
+    }
+    if let cli::Commands::Restore(args) = cli.command {
+        return cli::restore::execute(args, &db_path, cli.json);
+    }
+
+    // Handle doctor command before opening storage, which would auto-import and hide drift
+    if let cli::Commands::Doctor(args) = cli.command {
+        let actor = cli.actor.unwrap_or_else(tracer::get_actor);
+        return cli::doctor::execute(args, &db_path, &actor, cli.json);
+    }
+
+    // Open storage (the backend follows from the database file init created)
+    let backend = Backend::for_path(&db_path);
+    let mut storage = backend.open(&db_path)?;
 
     // Get actor name
     let actor = if let Some(actor) = cli.actor {
@@ -37,19 +107,42 @@ fn main() -> Result<()> {
     // Get prefix from config or default to "bd"
     let prefix = storage.get_config("prefix")?.unwrap_or_else(|| "bd".to_string());
 
-    // Auto-import if JSONL is newer
+    // Auto-import if JSONL is newer (milestones first, since issues reference them).
+    // The JSONL backend reads and writes these files itself, so it needs no syncing.
+    let sync = backend == Backend::Sqlite;
+    let milestones_path = find_milestones_path(&db_path);
     let jsonl_path = find_jsonl_path(&db_path);
-    if jsonl_path.exists() {
-        let _ = cli::export::auto_import(&mut storage, &jsonl_path, &actor);
+
+    // Hold the JSONL lock while importing and exporting, so concurrent tracer processes
+    // take turns reading and rewriting the files instead of overwriting each other
+    let mut jsonl_lock = if sync { Some(cli::export::lock_jsonl(&db_path)?) } else { None };
+    if sync {
+        let _ = cli::export::auto_import_milestones(&mut storage, &milestones_path);
+        let _ = cli::export::auto_import_archive(&mut storage, &tracer::find_archive_path(&db_path));
+        if jsonl_path.exists() {
+            let _ = cli::export::auto_import(&mut storage, &jsonl_path, &db_path, &actor);
+        }
+    }
+
+    // Release it while the command runs, except for archive, which rewrites the files
+    // inside its transaction (taking the lock there would invert the lock order)
+    if !matches!(cli.command, cli::Commands::Archive(_)) {
+        jsonl_lock = None;
     }
 
-    // Execute command
-    let result = match cli.command {
+    // Execute mutating commands in one transaction, so a failure partway leaves the
+    // database untouched; read-only commands skip it and don't wait for the write lock
+    let read_only = cli.command.is_read_only();
+    let command = |storage: &mut Box<dyn tracer::Storage>| match cli.command {
         cli::Commands::Init(_) => unreachable!(), // Handled above
         cli::Commands::Learn(_) => unreachable!(), // Handled above
+        cli::Commands::Migrate(_) => unreachable!(), // Handled above
+        cli::Commands::Doctor(_) => unreachable!(), // Handled above
+        cli::Commands::Backup(_) => unreachable!(), // Handled above
+        cli::Commands::Restore(_) => unreachable!(), // Handled above
         
         cli::Commands::Create(args) => {
-            cli::create::execute(args, &mut storage, &actor, &prefix, cli.json)
+            cli::create::execute(args, storage, &actor, &prefix, &tracer::find_templates_dir(&db_path), cli.json)
         }
         
         cli::Commands::List(args) => {
@@ -61,15 +154,19 @@ fn main() -> Result<()> {
         }
         
         cli::Commands::Update(args) => {
-            cli::update::execute_update(args, &mut storage, &actor, cli.json)
+            cli::update::execute_update(args, storage, &actor, cli.json)
         }
         
         cli::Commands::Close(args) => {
-            cli::update::execute_close(args, &mut storage, &actor, cli.json)
+            cli::update::execute_close(args, storage, &actor, cli.json)
         }
         
         cli::Commands::Comment(args) => {
-            cli::update::execute_comment(args, &mut storage, &actor, cli.json)
+            cli::update::execute_comment(args, storage, &actor, cli.json)
+        }
+        
+        cli::Commands::Log(args) => {
+            cli::update::execute_log(args, storage, &actor, cli.json)
         }
         
         cli::Commands::Ready(args) => {
@@ -83,10 +180,10 @@ fn main() -> Result<()> {
         cli::Commands::Dep(dep_cmd) => {
             match dep_cmd {
                 cli::dep::DepCommands::Add(args) => {
-                    cli::dep::execute_add(args, &mut storage, &actor, cli.json)
+                    cli::dep::execute_add(args, storage, &actor, cli.json)
                 }
                 cli::dep::DepCommands::Remove(args) => {
-                    cli::dep::execute_remove(args, &mut storage, &actor, cli.json)
+                    cli::dep::execute_remove(args, storage, &actor, cli.json)
                 }
                 cli::dep::DepCommands::Tree(args) => {
                     cli::dep::execute_tree(args, storage.as_ref(), cli.json)
@@ -102,24 +199,140 @@ fn main() -> Result<()> {
         }
         
         cli::Commands::Import(args) => {
-            cli::export::execute_import(args, &mut storage, &actor)
+            if sync {
+                tracer::backup::create_automatic(&db_path, "pre-import")?;
+            }
+            cli::export::execute_import(args, storage, &actor)
         }
         
         cli::Commands::Stats(args) => {
             cli::stats::execute(args, storage.as_ref(), cli.json)
         }
+        
+        cli::Commands::Batch(args) => {
+            cli::batch::execute(args, storage, &actor, &prefix)
+        }
+        
+        cli::Commands::Bulk(bulk_cmd) => {
+            match bulk_cmd {
+                cli::bulk::BulkCommands::Update(args) => {
+                    cli::bulk::execute_update(args, storage, &actor, cli.json)
+                }
+            }
+        }
+        
+        cli::Commands::Merge(args) => {
+            cli::merge::execute(args, storage, &actor, cli.json)
+        }
+        
+        cli::Commands::Duplicates(args) => {
+            cli::duplicates::execute(args, storage.as_ref(), cli.json)
+        }
+        
+        cli::Commands::Activity(args) => {
+            cli::activity::execute(args, storage.as_ref(), cli.json)
+        }
+        
+        cli::Commands::Archive(args) => {
+            cli::archive::execute(args, storage, sync.then_some(db_path.as_path()), cli.json)
+        }
+        
+        cli::Commands::Stale(args) => {
+            cli::stale::execute(args, storage, &actor, cli.json)
+        }
+        
+        cli::Commands::Milestone(milestone_cmd) => {
+            match milestone_cmd {
+                cli::milestone::MilestoneCommands::Create(args) => {
+                    cli::milestone::execute_create(args, storage, cli.json)
+                }
+                cli::milestone::MilestoneCommands::List(args) => {
+                    cli::milestone::execute_list(args, storage.as_ref(), cli.json)
+                }
+                cli::milestone::MilestoneCommands::Show(args) => {
+                    cli::milestone::execute_show(args, storage.as_ref(), cli.json)
+                }
+                cli::milestone::MilestoneCommands::Close(args) => {
+                    cli::milestone::execute_close(args, storage, cli.json)
+                }
+            }
+        }
+        
+        cli::Commands::Recur(recur_cmd) => {
+            match recur_cmd {
+                cli::recur::RecurCommands::Add(args) => {
+                    cli::recur::execute_add(args, storage, cli.json)
+                }
+                cli::recur::RecurCommands::List => {
+                    cli::recur::execute_list(storage.as_ref(), cli.json)
+                }
+                cli::recur::RecurCommands::Remove(args) => {
+                    cli::recur::execute_remove(args, storage, cli.json)
+                }
+                cli::recur::RecurCommands::Run(args) => {
+                    cli::recur::execute_run(args, storage, &actor, &prefix, cli.json)
+                }
+            }
+        }
+        
+        cli::Commands::Template(template_cmd) => {
+            let templates_dir = tracer::find_templates_dir(&db_path);
+            match template_cmd {
+                cli::template::TemplateCommands::List => {
+                    cli::template::execute_list(&templates_dir, cli.json)
+                }
+                cli::template::TemplateCommands::Show(args) => {
+                    cli::template::execute_show(args, &templates_dir, cli.json)
+                }
+            }
+        }
+        
+        cli::Commands::Workflow(workflow_cmd) => {
+            match workflow_cmd {
+                cli::workflow::WorkflowCommands::Show => {
+                    cli::workflow::execute_show(storage.as_ref(), cli.json)
+                }
+                cli::workflow::WorkflowCommands::AddStatus(args) => {
+                    cli::workflow::execute_add_status(args, storage, cli.json)
+                }
+                cli::workflow::WorkflowCommands::RemoveStatus(args) => {
+                    cli::workflow::execute_remove_status(args, storage, cli.json)
+                }
+                cli::workflow::WorkflowCommands::Allow(args) => {
+                    cli::workflow::execute_allow(args, storage, cli.json)
+                }
+                cli::workflow::WorkflowCommands::Disallow(args) => {
+                    cli::workflow::execute_disallow(args, storage, cli.json)
+                }
+                cli::workflow::WorkflowCommands::Reset => {
+                    cli::workflow::execute_reset(storage, cli.json)
+                }
+            }
+        }
     };
+    let result = if read_only {
+        command(&mut storage)
+    } else {
+        tracer::storage::with_transaction(&mut storage, command)
+    };
+
+    // Auto-export if there are dirty issues (auto_export clears their dirty flags)
+    if result.is_ok() && sync {
+        let lock = match jsonl_lock.take() {
+            Some(lock) => Ok(lock),
+            None => cli::export::lock_jsonl(&db_path),
+        };
+        match lock {
+            Ok(_lock) => {
+                if let Err(e) = cli::export::auto_export(&mut storage, &jsonl_path) {
+                    eprintln!("Warning: Failed to auto-export: {}", e);
+                }
 
-    // Auto-export if there are dirty issues
-    if result.is_ok() {
-        let dirty = storage.get_dirty_issues()?;
-        if !dirty.is_empty() {
-            if let Err(e) = cli::export::auto_export(storage.as_ref(), &jsonl_path) {
-                eprintln!("Warning: Failed to auto-export: {}", e);
-            } else {
-                // Clear dirty flags after successful export
-                storage.clear_dirty_issues()?;
+                if let Err(e) = cli::export::auto_export_milestones(&mut storage, &milestones_path) {
+                    eprintln!("Warning: Failed to auto-export milestones: {}", e);
+                }
             }
+            Err(e) => eprintln!("Warning: Failed to auto-export: {}", e),
         }
     }
 
@@ -0,0 +1,229 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Schedule says when a recurring issue is due: either a fixed interval from
+/// the recurrence's start ("every 1w") or a five-field cron expression in UTC
+/// ("0 9 * * MON")
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Schedule {
+    Every { minutes: i64 },
+    Cron(CronSchedule),
+}
+
+impl Schedule {
+    /// The most recent scheduled time at or before `now`, or None if the
+    /// schedule hasn't fired since `start`
+    pub fn latest_occurrence(&self, start: DateTime<Utc>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if now < start {
+            return None;
+        }
+        match self {
+            Schedule::Every { minutes } => {
+                let elapsed = now.signed_duration_since(start).num_minutes();
+                Some(start + Duration::minutes(elapsed / minutes * minutes))
+            }
+            Schedule::Cron(cron) => cron.latest_at_or_before(now).filter(|at| *at >= start),
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Every { minutes } => {
+                let (amount, unit) = if minutes % (7 * 24 * 60) == 0 {
+                    (minutes / (7 * 24 * 60), "w")
+                } else if minutes % (24 * 60) == 0 {
+                    (minutes / (24 * 60), "d")
+                } else if minutes % 60 == 0 {
+                    (minutes / 60, "h")
+                } else {
+                    (*minutes, "m")
+                };
+                write!(f, "every {}{}", amount, unit)
+            }
+            Schedule::Cron(cron) => write!(f, "{}", cron.expression),
+        }
+    }
+}
+
+impl std::str::FromStr for Schedule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(interval) = s.strip_prefix("every ") {
+            return parse_interval(interval.trim());
+        }
+        Ok(Schedule::Cron(s.parse()?))
+    }
+}
+
+impl TryFrom<String> for Schedule {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Schedule> for String {
+    fn from(schedule: Schedule) -> Self {
+        schedule.to_string()
+    }
+}
+
+/// Parse an interval like "30m", "12h", "1d" or "2w"
+pub fn parse_interval(spec: &str) -> Result<Schedule> {
+    let split = spec.char_indices().last().map(|(idx, _)| idx).unwrap_or(0);
+    let (amount, unit) = spec.split_at(split);
+    let amount: i64 = amount.parse()
+        .with_context(|| format!("invalid interval: {} (expected e.g. 1d or 2w)", spec))?;
+    let per_unit = match unit {
+        "m" => 1,
+        "h" => 60,
+        "d" => 24 * 60,
+        "w" => 7 * 24 * 60,
+        _ => anyhow::bail!("invalid interval: {} (units are m, h, d, w)", spec),
+    };
+    let minutes = amount.checked_mul(per_unit)
+        .with_context(|| format!("interval is too long: {}", spec))?;
+    if minutes <= 0 {
+        anyhow::bail!("interval must be positive: {}", spec);
+    }
+    Ok(Schedule::Every { minutes })
+}
+
+/// CronSchedule is a standard five-field cron expression:
+/// minute hour day-of-month month day-of-week
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days_of_month: Vec<u32>,
+    months: Vec<u32>,
+    days_of_week: Vec<u32>, // 0 = Sunday
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+impl CronSchedule {
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if !self.months.contains(&date.month()) {
+            return false;
+        }
+        let dom = self.days_of_month.contains(&date.day());
+        let dow = self.days_of_week.contains(&date.weekday().num_days_from_sunday());
+        // Like cron: when both day fields are restricted, either may match
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+
+    fn latest_at_or_before(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let today = now.date_naive();
+        // Any valid expression fires at least once in a leap-year cycle
+        for days_back in 0..=(4 * 366) {
+            let date = today - Duration::days(days_back);
+            if !self.matches_date(date) {
+                continue;
+            }
+            for hour in self.hours.iter().rev() {
+                for minute in self.minutes.iter().rev() {
+                    let at = date.and_hms_opt(*hour, *minute, 0)?.and_utc();
+                    if at <= now {
+                        return Some(at);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl std::str::FromStr for CronSchedule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        if fields.len() != 5 {
+            anyhow::bail!("invalid schedule: {} (expected \"every 1w\" or a cron expression like \"0 9 * * MON\")", s);
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7, "day of week", &DAY_NAMES)?;
+        // 7 is also Sunday
+        if days_of_week.contains(&7) {
+            days_of_week.retain(|d| *d != 7);
+            if !days_of_week.contains(&0) {
+                days_of_week.insert(0, 0);
+            }
+        }
+
+        Ok(CronSchedule {
+            expression: fields.join(" "),
+            minutes: parse_field(fields[0], 0, 59, "minute", &[])?,
+            hours: parse_field(fields[1], 0, 23, "hour", &[])?,
+            days_of_month: parse_field(fields[2], 1, 31, "day of month", &[])?,
+            months: parse_field(fields[3], 1, 12, "month", &MONTH_NAMES)?,
+            days_of_week,
+            // As in cron, a field starting with "*" (including "*/N") doesn't restrict the day
+            dom_restricted: !fields[2].starts_with('*'),
+            dow_restricted: !fields[4].starts_with('*'),
+        })
+    }
+}
+
+const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+const MONTH_NAMES: [&str; 13] = ["", "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+
+/// Parse one cron field: `*`, `N`, `A-B`, any of those with `/STEP`, or a comma list.
+/// `names` maps symbolic values (e.g. MON, JAN) to their index
+fn parse_field(field: &str, min: u32, max: u32, name: &str, names: &[&str]) -> Result<Vec<u32>> {
+    let mut values = Vec::new();
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse()
+                    .with_context(|| format!("invalid {} step: {}", name, part))?;
+                if step == 0 {
+                    anyhow::bail!("invalid {} step: {}", name, part);
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (parse_value(a, name, names)?, parse_value(b, name, names)?)
+        } else {
+            let value = parse_value(range, name, names)?;
+            // "N/STEP" runs from N to the end of the range
+            (value, if step > 1 { max } else { value })
+        };
+
+        if start < min || end > max || start > end {
+            anyhow::bail!("{} out of range in {} (allowed {}-{})", name, part, min, max);
+        }
+        values.extend((start..=end).step_by(step as usize));
+    }
+    values.sort_unstable();
+    values.dedup();
+    Ok(values)
+}
+
+fn parse_value(value: &str, name: &str, names: &[&str]) -> Result<u32> {
+    let upper = value.to_uppercase();
+    if let Some(idx) = names.iter().position(|n| !n.is_empty() && *n == upper) {
+        return Ok(idx as u32);
+    }
+    value.parse().with_context(|| format!("invalid {}: {}", name, value))
+}
@@ -0,0 +1,148 @@
+use crate::types::*;
+use std::collections::HashMap;
+
+/// Issues scoring at or above this cosine similarity are reported as likely duplicates
+pub const DEFAULT_THRESHOLD: f64 = 0.5;
+
+/// Title tokens count this many times as much as description tokens
+const TITLE_WEIGHT: f64 = 2.0;
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "in", "into", "is", "it",
+    "of", "on", "or", "should", "that", "the", "this", "to", "when", "with",
+];
+
+/// Lowercase, split on non-alphanumerics, drop stopwords and single characters,
+/// and strip a trailing plural "s" so "tests" and "test" match
+pub fn normalize_tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(|t| t.to_lowercase())
+        .filter(|t| t.chars().count() > 1 && !STOPWORDS.contains(&t.as_str()))
+        .map(|t| match t.strip_suffix('s') {
+            Some(stem) if stem.chars().count() > 2 && !stem.ends_with('s') => stem.to_string(),
+            _ => t,
+        })
+        .collect()
+}
+
+/// TF-IDF index over a set of issues, compared by cosine similarity
+pub struct SimilarityIndex {
+    idf: HashMap<String, f64>,
+    vectors: Vec<(String, HashMap<String, f64>)>,
+}
+
+impl SimilarityIndex {
+    pub fn new(issues: &[Issue]) -> Self {
+        let term_counts: Vec<(String, HashMap<String, f64>)> = issues.iter()
+            .map(|issue| (issue.id.clone(), term_frequencies(&issue.title, &issue.description)))
+            .collect();
+
+        let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+        for (_, terms) in &term_counts {
+            for term in terms.keys() {
+                *document_frequency.entry(term.as_str()).or_insert(0) += 1;
+            }
+        }
+
+        // Smoothed IDF, so a term shared by every issue still carries some weight
+        let n = issues.len() as f64;
+        let idf: HashMap<String, f64> = document_frequency.into_iter()
+            .map(|(term, df)| (term.to_string(), ((n + 1.0) / (df as f64 + 1.0)).ln() + 1.0))
+            .collect();
+
+        let vectors = term_counts.into_iter()
+            .map(|(id, terms)| (id, weigh(&terms, &idf)))
+            .collect();
+
+        SimilarityIndex { idf, vectors }
+    }
+
+    /// Score a new title/description against every indexed issue, best match first
+    pub fn find_similar(&self, title: &str, description: &str, threshold: f64) -> Vec<(String, f64)> {
+        let query = weigh(&term_frequencies(title, description), &self.idf);
+        let mut matches: Vec<(String, f64)> = self.vectors.iter()
+            .map(|(id, vector)| (id.clone(), cosine(&query, vector)))
+            .filter(|(_, score)| *score >= threshold)
+            .collect();
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        matches
+    }
+
+    /// Group indexed issues whose pairwise similarity reaches the threshold
+    /// (transitively), returning clusters of two or more issue IDs with each
+    /// member's best score against the rest of its cluster
+    pub fn clusters(&self, threshold: f64) -> Vec<Vec<(String, f64)>> {
+        let n = self.vectors.len();
+        let mut parent: Vec<usize> = (0..n).collect();
+        let mut best = vec![0.0f64; n];
+
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let score = cosine(&self.vectors[i].1, &self.vectors[j].1);
+                if score >= threshold {
+                    best[i] = best[i].max(score);
+                    best[j] = best[j].max(score);
+                    let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                    if a != b {
+                        parent[b] = a;
+                    }
+                }
+            }
+        }
+
+        let mut groups: HashMap<usize, Vec<(String, f64)>> = HashMap::new();
+        for (i, (id, _)) in self.vectors.iter().enumerate() {
+            let root = find(&mut parent, i);
+            groups.entry(root).or_default().push((id.clone(), best[i]));
+        }
+
+        let mut clusters: Vec<Vec<(String, f64)>> = groups.into_values()
+            .filter(|group| group.len() > 1)
+            .collect();
+        for cluster in &mut clusters {
+            cluster.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        }
+        clusters.sort_by(|a, b| b[0].1.total_cmp(&a[0].1).then_with(|| a[0].0.cmp(&b[0].0)));
+        clusters
+    }
+}
+
+fn term_frequencies(title: &str, description: &str) -> HashMap<String, f64> {
+    let mut terms: HashMap<String, f64> = HashMap::new();
+    for token in normalize_tokens(title) {
+        *terms.entry(token).or_insert(0.0) += TITLE_WEIGHT;
+    }
+    for token in normalize_tokens(description) {
+        *terms.entry(token).or_insert(0.0) += 1.0;
+    }
+    terms
+}
+
+fn weigh(terms: &HashMap<String, f64>, idf: &HashMap<String, f64>) -> HashMap<String, f64> {
+    // Terms unseen in the corpus get the maximum IDF; they can't match anything anyway
+    let max_idf = idf.values().copied().fold(1.0, f64::max);
+    terms.iter()
+        .map(|(term, tf)| (term.clone(), tf * idf.get(term).copied().unwrap_or(max_idf)))
+        .collect()
+}
+
+fn cosine(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    let dot: f64 = a.iter()
+        .filter_map(|(term, weight)| b.get(term).map(|other| weight * other))
+        .sum();
+    let norm_a = a.values().map(|v| v * v).sum::<f64>().sqrt();
+    let norm_b = b.values().map(|v| v * v).sum::<f64>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
@@ -0,0 +1,414 @@
+use super::memory::{InMemoryStorage, Snapshot};
+use super::{IssueUpdates, Storage};
+use crate::error::{Error, Result};
+use crate::types::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// JsonlStorage keeps a project in text files only, next to the store file
+/// (`.trace/<prefix>.json`):
+///
+/// - `issues.jsonl`: issues with their dependencies and work logs, in the same
+///   format as `tracer export`
+/// - `milestones.jsonl`: milestones
+/// - `archive.jsonl`: archived issues (see `tracer archive`)
+/// - the store file itself: config, metadata, ID counters, labels, recurrences and events
+///
+/// Everything is loaded into memory on open. Changes are written back when the
+/// outermost transaction commits (or straight away outside a transaction), each
+/// file via a temporary file and a rename. An exclusive lock on `<store>.lock` is
+/// held while the storage is open, so processes take turns; opening waits for
+/// the busy timeout before failing with `Error::Locked`.
+pub struct JsonlStorage {
+    path: PathBuf,
+    issues_path: PathBuf,
+    milestones_path: PathBuf,
+    archive_path: PathBuf,
+    inner: InMemoryStorage,
+    depth: usize,  // Open transactions
+    changed: bool, // Unsaved changes
+    _lock: File,
+}
+
+/// Contents of the store file
+#[derive(Default, Serialize, Deserialize)]
+struct StoreFile {
+    backend: String,
+    #[serde(default)]
+    config: BTreeMap<String, String>,
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+    #[serde(default)]
+    counters: BTreeMap<String, i64>,
+    #[serde(default)]
+    labels: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    recurrences: Vec<Recurrence>,
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+const BACKEND_NAME: &str = "jsonl";
+
+impl JsonlStorage {
+    /// Open the store at `path`, creating an empty one if it doesn't exist yet.
+    /// Waits for other processes as long as `$TRACE_BUSY_TIMEOUT` says.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::with_busy_timeout(path, crate::utils::busy_timeout())
+    }
+
+    /// Like `open`, waiting up to `busy_timeout` for another process to close the store
+    pub fn with_busy_timeout<P: AsRef<Path>>(path: P, busy_timeout: Duration) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let lock = super::lock_file(&path.with_extension("lock"), busy_timeout)?;
+
+        let issues_path = crate::utils::find_jsonl_path(&path);
+        let milestones_path = crate::utils::find_milestones_path(&path);
+        let archive_path = crate::utils::find_archive_path(&path);
+
+        let store: StoreFile = if path.exists() {
+            serde_json::from_slice(&std::fs::read(&path)?)?
+        } else {
+            StoreFile { backend: BACKEND_NAME.to_string(), ..Default::default() }
+        };
+        if store.backend != BACKEND_NAME {
+            return Err(Error::Schema(format!("{} is not a tracer JSONL store (backend: {})", path.display(), store.backend)));
+        }
+
+        let snapshot = Snapshot {
+            issues: read_jsonl(&issues_path)?,
+            labels: store.labels,
+            milestones: read_jsonl(&milestones_path)?,
+            recurrences: store.recurrences,
+            events: store.events,
+            counters: store.counters,
+            config: store.config,
+            metadata: store.metadata,
+            archived: read_jsonl(&archive_path)?,
+        };
+
+        Ok(Self {
+            path,
+            issues_path,
+            milestones_path,
+            archive_path,
+            inner: InMemoryStorage::from_snapshot(snapshot),
+            depth: 0,
+            changed: false,
+            _lock: lock,
+        })
+    }
+
+    /// Apply a change, saving it unless a transaction is open
+    fn write<T>(&mut self, f: impl FnOnce(&mut InMemoryStorage) -> Result<T>) -> Result<T> {
+        let value = f(&mut self.inner)?;
+        self.changed = true;
+        if self.depth == 0 {
+            self.save()?;
+        }
+        Ok(value)
+    }
+
+    fn save(&mut self) -> Result<()> {
+        let snapshot = self.inner.snapshot();
+
+        let mut issues = snapshot.issues;
+        issues.sort_by(|a, b| a.id.cmp(&b.id));
+        crate::utils::write_atomic(&self.issues_path, &to_jsonl(&issues)?)?;
+
+        // Don't create empty files in projects that don't use milestones or the archive
+        if !snapshot.milestones.is_empty() || self.milestones_path.exists() {
+            crate::utils::write_atomic(&self.milestones_path, &to_jsonl(&snapshot.milestones)?)?;
+        }
+        if !snapshot.archived.is_empty() || self.archive_path.exists() {
+            crate::utils::write_atomic(&self.archive_path, &to_jsonl(&snapshot.archived)?)?;
+        }
+
+        let store = StoreFile {
+            backend: BACKEND_NAME.to_string(),
+            config: snapshot.config,
+            metadata: snapshot.metadata,
+            counters: snapshot.counters,
+            labels: snapshot.labels,
+            recurrences: snapshot.recurrences,
+            events: snapshot.events,
+        };
+        let mut data = serde_json::to_vec_pretty(&store)?;
+        data.push(b'\n');
+        crate::utils::write_atomic(&self.path, &data)?;
+
+        self.changed = false;
+        Ok(())
+    }
+}
+
+fn read_jsonl<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = std::fs::read_to_string(path)?;
+    let mut items = Vec::new();
+    for (idx, line) in data.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let item = serde_json::from_str(line)
+            .map_err(|err| Error::Schema(format!("{} line {}: {}", path.display(), idx + 1, err)))?;
+        items.push(item);
+    }
+    Ok(items)
+}
+
+fn to_jsonl<T: Serialize>(items: &[T]) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    for item in items {
+        serde_json::to_writer(&mut data, item)?;
+        writeln!(data)?;
+    }
+    Ok(data)
+}
+
+impl Storage for JsonlStorage {
+    fn create_issue(&mut self, issue: &Issue, actor: &str) -> Result<()> {
+        self.write(|s| s.create_issue(issue, actor))
+    }
+
+    fn get_issue(&self, id: &str) -> Result<Option<Issue>> {
+        self.inner.get_issue(id)
+    }
+
+    fn update_issue(&mut self, id: &str, updates: &IssueUpdates, actor: &str) -> Result<()> {
+        self.write(|s| s.update_issue(id, updates, actor))
+    }
+
+    fn close_issue(&mut self, id: &str, reason: &str, actor: &str) -> Result<()> {
+        self.write(|s| s.close_issue(id, reason, actor))
+    }
+
+    fn search_issues(&self, query: &str, filter: &IssueFilter) -> Result<Vec<Issue>> {
+        self.inner.search_issues(query, filter)
+    }
+
+    fn add_dependency(&mut self, dep: &Dependency, actor: &str) -> Result<()> {
+        self.write(|s| s.add_dependency(dep, actor))
+    }
+
+    fn remove_dependency(&mut self, issue_id: &str, depends_on_id: &str, actor: &str) -> Result<()> {
+        self.write(|s| s.remove_dependency(issue_id, depends_on_id, actor))
+    }
+
+    fn get_dependencies(&self, issue_id: &str) -> Result<Vec<Issue>> {
+        self.inner.get_dependencies(issue_id)
+    }
+
+    fn get_dependents(&self, issue_id: &str) -> Result<Vec<Issue>> {
+        self.inner.get_dependents(issue_id)
+    }
+
+    fn get_dependency_records(&self, issue_id: &str) -> Result<Vec<Dependency>> {
+        self.inner.get_dependency_records(issue_id)
+    }
+
+    fn get_all_dependency_records(&self) -> Result<Vec<Dependency>> {
+        self.inner.get_all_dependency_records()
+    }
+
+    fn get_dependency_tree(&self, issue_id: &str, max_depth: i32) -> Result<Vec<TreeNode>> {
+        self.inner.get_dependency_tree(issue_id, max_depth)
+    }
+
+    fn detect_cycles(&self) -> Result<Vec<Vec<Issue>>> {
+        self.inner.detect_cycles()
+    }
+
+    fn add_label(&mut self, issue_id: &str, label: &str, actor: &str) -> Result<()> {
+        self.write(|s| s.add_label(issue_id, label, actor))
+    }
+
+    fn remove_label(&mut self, issue_id: &str, label: &str, actor: &str) -> Result<()> {
+        self.write(|s| s.remove_label(issue_id, label, actor))
+    }
+
+    fn get_labels(&self, issue_id: &str) -> Result<Vec<String>> {
+        self.inner.get_labels(issue_id)
+    }
+
+    fn get_issues_by_label(&self, label: &str) -> Result<Vec<Issue>> {
+        self.inner.get_issues_by_label(label)
+    }
+
+    fn get_ready_work(&self, filter: &WorkFilter) -> Result<Vec<Issue>> {
+        self.inner.get_ready_work(filter)
+    }
+
+    fn get_blocked_issues(&self, filter: &WorkFilter) -> Result<Vec<BlockedIssue>> {
+        self.inner.get_blocked_issues(filter)
+    }
+
+    fn get_stale_issues(&self, inactive_since: DateTime<Utc>, status: Option<Status>) -> Result<Vec<StaleIssue>> {
+        self.inner.get_stale_issues(inactive_since, status)
+    }
+
+    fn save_milestone(&mut self, milestone: &Milestone) -> Result<()> {
+        self.write(|s| s.save_milestone(milestone))
+    }
+
+    fn get_milestone(&self, name: &str) -> Result<Option<Milestone>> {
+        self.inner.get_milestone(name)
+    }
+
+    fn list_milestones(&self) -> Result<Vec<Milestone>> {
+        self.inner.list_milestones()
+    }
+
+    fn get_milestone_report(&self, name: &str) -> Result<Option<MilestoneReport>> {
+        self.inner.get_milestone_report(name)
+    }
+
+    fn save_recurrence(&mut self, recurrence: &Recurrence) -> Result<()> {
+        self.write(|s| s.save_recurrence(recurrence))
+    }
+
+    fn get_recurrence(&self, name: &str) -> Result<Option<Recurrence>> {
+        self.inner.get_recurrence(name)
+    }
+
+    fn list_recurrences(&self) -> Result<Vec<Recurrence>> {
+        self.inner.list_recurrences()
+    }
+
+    fn delete_recurrence(&mut self, name: &str) -> Result<()> {
+        self.write(|s| s.delete_recurrence(name))
+    }
+
+    fn claim_recurrence_period(&mut self, name: &str, period: DateTime<Utc>) -> Result<bool> {
+        self.write(|s| s.claim_recurrence_period(name, period))
+    }
+
+    fn get_issue_by_external_ref(&self, external_ref: &str) -> Result<Option<Issue>> {
+        self.inner.get_issue_by_external_ref(external_ref)
+    }
+
+    fn add_work_log(&mut self, log: &WorkLog) -> Result<()> {
+        self.write(|s| s.add_work_log(log))
+    }
+
+    fn get_work_logs(&self, issue_id: &str) -> Result<Vec<WorkLog>> {
+        self.inner.get_work_logs(issue_id)
+    }
+
+    fn add_comment(&mut self, issue_id: &str, actor: &str, comment: &str) -> Result<()> {
+        self.write(|s| s.add_comment(issue_id, actor, comment))
+    }
+
+    fn move_comments(&mut self, from_issue_id: &str, to_issue_id: &str) -> Result<usize> {
+        self.write(|s| s.move_comments(from_issue_id, to_issue_id))
+    }
+
+    fn get_events(&self, issue_id: &str, limit: usize) -> Result<Vec<Event>> {
+        self.inner.get_events(issue_id, limit)
+    }
+
+    fn get_all_events(&self, filter: &EventFilter) -> Result<Vec<Event>> {
+        self.inner.get_all_events(filter)
+    }
+
+    fn compact_events(&mut self, before: DateTime<Utc>) -> Result<usize> {
+        self.write(|s| s.compact_events(before))
+    }
+
+    fn get_statistics(&self) -> Result<Statistics> {
+        self.inner.get_statistics()
+    }
+
+    fn archive_issues(&mut self, closed_before: DateTime<Utc>) -> Result<Vec<String>> {
+        self.write(|s| s.archive_issues(closed_before))
+    }
+
+    fn save_archived_issue(&mut self, archived: &ArchivedIssue) -> Result<()> {
+        self.write(|s| s.save_archived_issue(archived))
+    }
+
+    fn get_archived_issue(&self, id: &str) -> Result<Option<ArchivedIssue>> {
+        self.inner.get_archived_issue(id)
+    }
+
+    fn list_archived_issues(&self) -> Result<Vec<ArchivedIssue>> {
+        self.inner.list_archived_issues()
+    }
+
+    // Dirty flags only matter for exporting from SQLite, so they aren't saved
+    fn get_dirty_issues(&self) -> Result<Vec<String>> {
+        self.inner.get_dirty_issues()
+    }
+
+    fn clear_dirty_issues(&mut self) -> Result<()> {
+        self.inner.clear_dirty_issues()
+    }
+
+    fn clear_dirty_issues_by_id(&mut self, issue_ids: &[String]) -> Result<()> {
+        self.inner.clear_dirty_issues_by_id(issue_ids)
+    }
+
+    fn set_config(&mut self, key: &str, value: &str) -> Result<()> {
+        self.write(|s| s.set_config(key, value))
+    }
+
+    fn get_config(&self, key: &str) -> Result<Option<String>> {
+        self.inner.get_config(key)
+    }
+
+    fn get_workflow(&self) -> Result<Workflow> {
+        self.inner.get_workflow()
+    }
+
+    fn set_workflow(&mut self, workflow: &Workflow) -> Result<()> {
+        self.write(|s| s.set_workflow(workflow))
+    }
+
+    fn set_metadata(&mut self, key: &str, value: &str) -> Result<()> {
+        self.write(|s| s.set_metadata(key, value))
+    }
+
+    fn get_metadata(&self, key: &str) -> Result<Option<String>> {
+        self.inner.get_metadata(key)
+    }
+
+    fn generate_id(&mut self, prefix: &str) -> Result<String> {
+        self.write(|s| s.generate_id(prefix))
+    }
+
+    fn begin_transaction(&mut self) -> Result<()> {
+        self.inner.begin_transaction()?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        self.inner.commit_transaction()?;
+        self.depth -= 1;
+        if self.depth == 0 && self.changed {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn rollback_transaction(&mut self) -> Result<()> {
+        self.inner.rollback_transaction()?;
+        self.depth -= 1;
+        if self.depth == 0 {
+            // Back to the state that was last saved
+            self.changed = false;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,949 @@
+use super::{IssueUpdates, Storage};
+use crate::error::{Error, Result};
+use crate::types::*;
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// InMemoryStorage keeps everything in process memory. It follows the same
+/// rules as `SqliteStorage` (including the errors its constraints produce), so
+/// tests and embedders can use it without touching the filesystem.
+#[derive(Default)]
+pub struct InMemoryStorage {
+    state: State,
+    savepoints: Vec<State>, // One snapshot per open transaction
+}
+
+/// The contents of an `InMemoryStorage`, for backends that persist it elsewhere
+#[derive(Default)]
+pub(super) struct Snapshot {
+    pub issues: Vec<Issue>, // Dependencies and work logs are carried on each issue
+    pub labels: BTreeMap<String, Vec<String>>,
+    pub milestones: Vec<Milestone>,
+    pub recurrences: Vec<Recurrence>,
+    pub events: Vec<Event>,
+    pub counters: BTreeMap<String, i64>,
+    pub config: BTreeMap<String, String>,
+    pub metadata: BTreeMap<String, String>,
+    pub archived: Vec<ArchivedIssue>,
+}
+
+#[derive(Clone, Default)]
+struct State {
+    issues: Vec<Issue>, // In insertion order, like SQLite rowids
+    index: HashMap<String, usize>,
+    dependencies: Vec<Dependency>,
+    labels: HashMap<String, BTreeSet<String>>,
+    milestones: BTreeMap<String, Milestone>,
+    recurrences: BTreeMap<String, Recurrence>,
+    work_logs: Vec<WorkLog>,
+    events: Vec<Event>,
+    last_event_id: i64,
+    dirty: Vec<String>,
+    counters: HashMap<String, i64>,
+    config: HashMap<String, String>,
+    metadata: HashMap<String, String>,
+    archived: BTreeMap<String, ArchivedIssue>,
+}
+
+impl InMemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A read-only view of archived issues as if they were live, e.g. to filter
+    /// or display them with the usual queries
+    pub fn from_archive(archived: Vec<ArchivedIssue>) -> Self {
+        let mut snapshot = Snapshot::default();
+        for archived in archived {
+            snapshot.labels.insert(archived.issue.id.clone(), archived.labels);
+            snapshot.events.extend(archived.events);
+            snapshot.issues.push(archived.issue);
+        }
+        Self::from_snapshot(snapshot)
+    }
+
+    /// Build a storage from persisted data. Events and dirty flags aren't
+    /// recorded, and ID counters are raised past any ID already in use.
+    pub(super) fn from_snapshot(snapshot: Snapshot) -> Self {
+        let mut state = State::default();
+        let archived_ids = snapshot.archived.iter().map(|archived| archived.issue.id.as_str());
+        for id in snapshot.issues.iter().map(|issue| issue.id.as_str()).chain(archived_ids) {
+            if let Some((prefix, number)) = id.rsplit_once('-') {
+                if let Ok(number) = number.parse::<i64>() {
+                    let counter = state.counters.entry(prefix.to_string()).or_insert(0);
+                    *counter = (*counter).max(number);
+                }
+            }
+        }
+        for mut issue in snapshot.issues {
+            state.dependencies.append(&mut issue.dependencies);
+            state.work_logs.append(&mut issue.work_logs);
+            state.index.insert(issue.id.clone(), state.issues.len());
+            state.issues.push(issue);
+        }
+        for (prefix, last_id) in snapshot.counters {
+            let counter = state.counters.entry(prefix).or_insert(0);
+            *counter = (*counter).max(last_id);
+        }
+        state.labels = snapshot.labels.into_iter()
+            .map(|(issue_id, labels)| (issue_id, labels.into_iter().collect()))
+            .collect();
+        state.milestones = snapshot.milestones.into_iter().map(|m| (m.name.clone(), m)).collect();
+        state.recurrences = snapshot.recurrences.into_iter().map(|r| (r.name.clone(), r)).collect();
+        state.last_event_id = snapshot.events.iter().map(|e| e.id).max().unwrap_or(0);
+        state.events = snapshot.events;
+        state.config = snapshot.config.into_iter().collect();
+        state.metadata = snapshot.metadata.into_iter().collect();
+        state.archived = snapshot.archived.into_iter().map(|a| (a.issue.id.clone(), a)).collect();
+
+        Self { state, savepoints: Vec::new() }
+    }
+
+    /// Everything this storage holds, with each issue's dependencies and work logs filled in
+    pub(super) fn snapshot(&self) -> Snapshot {
+        let issues = self.state.issues.iter()
+            .map(|issue| Issue {
+                dependencies: self.state.dependencies.iter().filter(|d| d.issue_id == issue.id).cloned().collect(),
+                work_logs: self.state.work_logs.iter().filter(|log| log.issue_id == issue.id).cloned().collect(),
+                ..issue.clone()
+            })
+            .collect();
+
+        Snapshot {
+            issues,
+            labels: self.state.labels.iter()
+                .filter(|(_, labels)| !labels.is_empty())
+                .map(|(issue_id, labels)| (issue_id.clone(), labels.iter().cloned().collect()))
+                .collect(),
+            milestones: self.list_milestones().unwrap_or_default(),
+            recurrences: self.state.recurrences.values().cloned().collect(),
+            events: self.state.events.clone(),
+            counters: self.state.counters.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+            config: self.state.config.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            metadata: self.state.metadata.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            archived: self.state.archived.values().cloned().collect(),
+        }
+    }
+
+    fn issue(&self, id: &str) -> Option<&Issue> {
+        self.state.index.get(id).map(|&idx| &self.state.issues[idx])
+    }
+
+    /// Delete an issue and everything attached to it, as SQLite's cascades do
+    fn remove_issue(&mut self, id: &str) {
+        self.state.issues.retain(|issue| issue.id != id);
+        self.state.index = self.state.issues.iter().enumerate().map(|(idx, issue)| (issue.id.clone(), idx)).collect();
+        self.state.dependencies.retain(|dep| dep.issue_id != id && dep.depends_on_id != id);
+        self.state.labels.remove(id);
+        self.state.work_logs.retain(|log| log.issue_id != id);
+        self.state.events.retain(|event| event.issue_id != id);
+        self.state.dirty.retain(|dirty| dirty != id);
+    }
+
+    /// Fail the way SQLite's foreign keys do when a row refers to a missing issue
+    fn check_issue(&self, id: &str) -> Result<()> {
+        if self.state.index.contains_key(id) {
+            Ok(())
+        } else {
+            Err(Error::Validation("references an issue or milestone that does not exist".to_string()))
+        }
+    }
+
+    /// Run a multi-step write so that a failure partway leaves nothing behind
+    fn atomic<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let snapshot = self.state.clone();
+        let result = f(self);
+        if result.is_err() {
+            self.state = snapshot;
+        }
+        result
+    }
+
+    fn mark_dirty(&mut self, issue_id: &str) -> Result<()> {
+        self.check_issue(issue_id)?;
+        if !self.state.dirty.iter().any(|id| id == issue_id) {
+            self.state.dirty.push(issue_id.to_string());
+        }
+        Ok(())
+    }
+
+    fn add_event(&mut self, issue_id: &str, event_type: EventType, actor: &str, old_value: Option<&str>, new_value: Option<&str>, comment: Option<&str>) -> Result<()> {
+        self.check_issue(issue_id)?;
+        self.state.last_event_id += 1;
+        self.state.events.push(Event {
+            id: self.state.last_event_id,
+            issue_id: issue_id.to_string(),
+            event_type,
+            actor: actor.to_string(),
+            old_value: old_value.map(str::to_string),
+            new_value: new_value.map(str::to_string),
+            comment: comment.map(str::to_string),
+            created_at: Utc::now(),
+        });
+        Ok(())
+    }
+
+    /// Open an automatic work log entry when an issue moves into in_progress
+    fn start_tracking(&mut self, issue_id: &str, actor: &str, at: DateTime<Utc>) {
+        let running = self.state.work_logs.iter().any(|log| log.issue_id == issue_id && log.ended_at.is_none());
+        if !running {
+            self.state.work_logs.push(WorkLog {
+                issue_id: issue_id.to_string(),
+                actor: actor.to_string(),
+                minutes: 0,
+                note: String::new(),
+                started_at: at,
+                ended_at: None,
+                source: WorkLogSource::Auto,
+            });
+        }
+    }
+
+    /// Close the running automatic entry (if any) when an issue leaves in_progress
+    fn stop_tracking(&mut self, issue_id: &str, at: DateTime<Utc>) {
+        if let Some(log) = self.state.work_logs.iter_mut().find(|log| log.issue_id == issue_id && log.ended_at.is_none()) {
+            log.minutes = at.signed_duration_since(log.started_at).num_minutes().max(0);
+            log.ended_at = Some(at);
+        }
+    }
+
+    /// IDs of the issues still blocking `issue_id`, i.e. `blocks` dependencies not in a done status
+    fn open_blockers(&self, issue_id: &str, workflow: &Workflow) -> Vec<String> {
+        self.state.dependencies.iter()
+            .filter(|dep| dep.issue_id == issue_id && dep.dep_type == DependencyType::Blocks)
+            .filter(|dep| self.issue(&dep.depends_on_id).is_some_and(|blocker| !workflow.is_done(&blocker.status)))
+            .map(|dep| dep.depends_on_id.clone())
+            .collect()
+    }
+
+    fn matches_work_filter(issue: &Issue, filter: &WorkFilter) -> bool {
+        filter.priority.is_none_or(|p| issue.priority == p)
+            && filter.assignee.as_ref().is_none_or(|a| &issue.assignee == a)
+            && filter.milestone.as_ref().is_none_or(|m| issue.milestone.as_ref() == Some(m))
+    }
+
+    fn build_tree(&self, issue_id: &str, depth: i32, max_depth: i32, nodes: &mut Vec<TreeNode>, visited: &mut HashSet<String>) -> Result<()> {
+        if depth >= max_depth || visited.contains(issue_id) {
+            return Ok(());
+        }
+
+        visited.insert(issue_id.to_string());
+
+        if let Some(issue) = self.issue(issue_id) {
+            nodes.push(TreeNode {
+                issue: issue.clone(),
+                depth,
+                truncated: depth >= max_depth - 1,
+            });
+
+            for dep in self.get_dependencies(issue_id)? {
+                self.build_tree(&dep.id, depth + 1, max_depth, nodes, visited)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn dfs_cycle(&self, node: &str, graph: &HashMap<String, Vec<String>>, visited: &mut HashSet<String>, rec_stack: &mut HashSet<String>, path: &mut Vec<String>, cycles: &mut Vec<Vec<Issue>>) {
+        visited.insert(node.to_string());
+        rec_stack.insert(node.to_string());
+        path.push(node.to_string());
+
+        if let Some(neighbors) = graph.get(node) {
+            for neighbor in neighbors {
+                if !visited.contains(neighbor) {
+                    self.dfs_cycle(neighbor, graph, visited, rec_stack, path, cycles);
+                } else if rec_stack.contains(neighbor) {
+                    let cycle_start = path.iter().position(|n| n == neighbor).expect("Cycle node must be in path");
+                    let cycle_issues: Vec<Issue> = path[cycle_start..].iter()
+                        .filter_map(|id| self.issue(id).cloned())
+                        .collect();
+                    if !cycle_issues.is_empty() {
+                        cycles.push(cycle_issues);
+                    }
+                }
+            }
+        }
+
+        path.pop();
+        rec_stack.remove(node);
+    }
+}
+
+impl Storage for InMemoryStorage {
+    fn create_issue(&mut self, issue: &Issue, actor: &str) -> Result<()> {
+        self.atomic(|this| {
+            issue.validate()?;
+            if this.state.index.contains_key(&issue.id) {
+                return Err(Error::Conflict(format!("Issue {} already exists", issue.id)));
+            }
+
+            let mut stored = issue.clone();
+            stored.dependencies = Vec::new();
+            stored.work_logs = Vec::new();
+            this.state.index.insert(stored.id.clone(), this.state.issues.len());
+            this.state.issues.push(stored);
+
+            this.add_event(&issue.id, EventType::Created, actor, None, Some(&issue.status.to_string()), None)?;
+            this.mark_dirty(&issue.id)?;
+            Ok(())
+        })
+    }
+
+    fn get_issue(&self, id: &str) -> Result<Option<Issue>> {
+        Ok(self.issue(id).cloned())
+    }
+
+    fn update_issue(&mut self, id: &str, updates: &IssueUpdates, actor: &str) -> Result<()> {
+        self.atomic(|this| {
+            let now = Utc::now();
+            let current = this.issue(id)
+                .ok_or_else(|| Error::not_found("Issue", id))?
+                .status
+                .clone();
+
+            if let Some(status) = &updates.status {
+                if !updates.bypass_workflow {
+                    this.get_workflow()?.check_transition(&current, status)?;
+                }
+                this.add_event(id, EventType::StatusChanged, actor, Some(&current.to_string()), Some(&status.to_string()), None)?;
+
+                if current != *status {
+                    if current == Status::InProgress {
+                        this.stop_tracking(id, Utc::now());
+                    }
+                    if *status == Status::InProgress && !updates.bypass_workflow {
+                        this.start_tracking(id, actor, Utc::now());
+                    }
+                }
+            }
+
+            if let Some(&idx) = this.state.index.get(id) {
+                let issue = &mut this.state.issues[idx];
+                issue.updated_at = now;
+                if let Some(title) = &updates.title {
+                    issue.title = title.clone();
+                }
+                if let Some(desc) = &updates.description {
+                    issue.description = desc.clone();
+                }
+                if let Some(design) = &updates.design {
+                    issue.design = design.clone();
+                }
+                if let Some(ac) = &updates.acceptance_criteria {
+                    issue.acceptance_criteria = ac.clone();
+                }
+                if let Some(notes) = &updates.notes {
+                    issue.notes = notes.clone();
+                }
+                if let Some(status) = &updates.status {
+                    // Keep closed_at in step with the status, as close_issue does
+                    if issue.status != *status {
+                        if *status == Status::Closed {
+                            issue.closed_at = Some(now);
+                        } else if issue.status == Status::Closed {
+                            issue.closed_at = None;
+                        }
+                    }
+                    issue.status = status.clone();
+                }
+                if let Some(priority) = updates.priority {
+                    issue.priority = priority;
+                }
+                if let Some(issue_type) = updates.issue_type {
+                    issue.issue_type = issue_type;
+                }
+                if let Some(assignee) = &updates.assignee {
+                    issue.assignee = assignee.clone();
+                }
+                if let Some(estimated_minutes) = updates.estimated_minutes {
+                    issue.estimated_minutes = estimated_minutes;
+                }
+                if let Some(external_ref) = &updates.external_ref {
+                    issue.external_ref = external_ref.clone();
+                }
+                if let Some(due_at) = updates.due_at {
+                    issue.due_at = due_at;
+                }
+                if let Some(milestone) = &updates.milestone {
+                    issue.milestone = milestone.clone();
+                }
+            }
+
+            this.add_event(id, EventType::Updated, actor, None, None, None)?;
+            this.mark_dirty(id)?;
+            Ok(())
+        })
+    }
+
+    fn close_issue(&mut self, id: &str, reason: &str, actor: &str) -> Result<()> {
+        self.atomic(|this| {
+            let idx = *this.state.index.get(id).ok_or_else(|| Error::not_found("Issue", id))?;
+            let current = this.state.issues[idx].status.clone();
+            this.get_workflow()?.check_transition(&current, &Status::Closed)?;
+
+            let now = Utc::now();
+            let issue = &mut this.state.issues[idx];
+            issue.status = Status::Closed;
+            issue.closed_at = Some(now);
+            issue.updated_at = now;
+            this.add_event(id, EventType::Closed, actor, None, None, Some(reason))?;
+            this.stop_tracking(id, now);
+            this.mark_dirty(id)?;
+            Ok(())
+        })
+    }
+
+    fn search_issues(&self, _query: &str, filter: &IssueFilter) -> Result<Vec<Issue>> {
+        let workflow = self.get_workflow()?;
+        let now = Utc::now();
+        let after = |value: Option<DateTime<Utc>>, bound: Option<DateTime<Utc>>| {
+            bound.is_none_or(|bound| value.is_some_and(|value| value >= bound))
+        };
+        let before = |value: Option<DateTime<Utc>>, bound: Option<DateTime<Utc>>| {
+            bound.is_none_or(|bound| value.is_some_and(|value| value < bound))
+        };
+
+        let mut issues: Vec<Issue> = self.state.issues.iter()
+            .filter(|i| filter.status.as_ref().is_none_or(|s| &i.status == s))
+            .filter(|i| filter.priority.is_none_or(|p| i.priority == p))
+            .filter(|i| filter.issue_type.is_none_or(|t| i.issue_type == t))
+            .filter(|i| filter.assignee.as_ref().is_none_or(|a| &i.assignee == a))
+            .filter(|i| after(Some(i.created_at), filter.created_after) && before(Some(i.created_at), filter.created_before))
+            .filter(|i| after(Some(i.updated_at), filter.updated_after) && before(Some(i.updated_at), filter.updated_before))
+            .filter(|i| after(i.closed_at, filter.closed_after) && before(i.closed_at, filter.closed_before))
+            .filter(|i| before(i.due_at, filter.due_before))
+            .filter(|i| filter.milestone.as_ref().is_none_or(|m| i.milestone.as_ref() == Some(m)))
+            .filter(|i| !filter.overdue || (before(i.due_at, Some(now)) && !workflow.is_done(&i.status)))
+            .filter(|i| {
+                // Any of the labels matches
+                filter.labels.is_empty() || self.state.labels.get(&i.id)
+                    .is_some_and(|labels| filter.labels.iter().any(|l| labels.contains(l)))
+            })
+            .cloned()
+            .collect();
+
+        sort_issues(&mut issues, &filter.sort);
+        Ok(paginate(issues, filter.limit, filter.offset))
+    }
+
+    fn add_dependency(&mut self, dep: &Dependency, actor: &str) -> Result<()> {
+        if dep.issue_id == dep.depends_on_id {
+            return Err(Error::Cycle(format!("{} cannot depend on itself", dep.issue_id)));
+        }
+        self.atomic(|this| {
+            if this.state.dependencies.iter().any(|d| d.issue_id == dep.issue_id && d.depends_on_id == dep.depends_on_id) {
+                return Err(Error::Conflict(format!("dependency {} -> {} already exists", dep.issue_id, dep.depends_on_id)));
+            }
+            this.check_issue(&dep.issue_id)?;
+            this.check_issue(&dep.depends_on_id)?;
+            this.state.dependencies.push(Dependency {
+                created_by: actor.to_string(),
+                ..dep.clone()
+            });
+            this.add_event(&dep.issue_id, EventType::DependencyAdded, actor, None, Some(&dep.depends_on_id), None)?;
+            this.mark_dirty(&dep.issue_id)?;
+            Ok(())
+        })
+    }
+
+    fn remove_dependency(&mut self, issue_id: &str, depends_on_id: &str, actor: &str) -> Result<()> {
+        self.atomic(|this| {
+            this.state.dependencies.retain(|d| !(d.issue_id == issue_id && d.depends_on_id == depends_on_id));
+            this.add_event(issue_id, EventType::DependencyRemoved, actor, None, Some(depends_on_id), None)?;
+            this.mark_dirty(issue_id)?;
+            Ok(())
+        })
+    }
+
+    fn get_dependencies(&self, issue_id: &str) -> Result<Vec<Issue>> {
+        Ok(self.state.dependencies.iter()
+            .filter(|d| d.issue_id == issue_id)
+            .filter_map(|d| self.issue(&d.depends_on_id).cloned())
+            .collect())
+    }
+
+    fn get_dependents(&self, issue_id: &str) -> Result<Vec<Issue>> {
+        Ok(self.state.dependencies.iter()
+            .filter(|d| d.depends_on_id == issue_id)
+            .filter_map(|d| self.issue(&d.issue_id).cloned())
+            .collect())
+    }
+
+    fn get_dependency_records(&self, issue_id: &str) -> Result<Vec<Dependency>> {
+        Ok(self.state.dependencies.iter().filter(|d| d.issue_id == issue_id).cloned().collect())
+    }
+
+    fn get_all_dependency_records(&self) -> Result<Vec<Dependency>> {
+        Ok(self.state.dependencies.clone())
+    }
+
+    fn get_dependency_tree(&self, issue_id: &str, max_depth: i32) -> Result<Vec<TreeNode>> {
+        let mut nodes = Vec::new();
+        let mut visited = HashSet::new();
+        self.build_tree(issue_id, 0, max_depth, &mut nodes, &mut visited)?;
+        Ok(nodes)
+    }
+
+    fn detect_cycles(&self) -> Result<Vec<Vec<Issue>>> {
+        let mut graph: HashMap<String, Vec<String>> = HashMap::new();
+        for dep in &self.state.dependencies {
+            graph.entry(dep.issue_id.clone()).or_default().push(dep.depends_on_id.clone());
+        }
+
+        let mut cycles = Vec::new();
+        let mut visited = HashSet::new();
+        let mut rec_stack = HashSet::new();
+
+        for node in graph.keys() {
+            if !visited.contains(node) {
+                self.dfs_cycle(node, &graph, &mut visited, &mut rec_stack, &mut Vec::new(), &mut cycles);
+            }
+        }
+
+        Ok(cycles)
+    }
+
+    fn add_label(&mut self, issue_id: &str, label: &str, actor: &str) -> Result<()> {
+        self.atomic(|this| {
+            this.check_issue(issue_id)?;
+            this.state.labels.entry(issue_id.to_string()).or_default().insert(label.to_string());
+            this.add_event(issue_id, EventType::LabelAdded, actor, None, Some(label), None)?;
+            this.mark_dirty(issue_id)?;
+            Ok(())
+        })
+    }
+
+    fn remove_label(&mut self, issue_id: &str, label: &str, actor: &str) -> Result<()> {
+        self.atomic(|this| {
+            if let Some(labels) = this.state.labels.get_mut(issue_id) {
+                labels.remove(label);
+            }
+            this.add_event(issue_id, EventType::LabelRemoved, actor, None, Some(label), None)?;
+            this.mark_dirty(issue_id)?;
+            Ok(())
+        })
+    }
+
+    fn get_labels(&self, issue_id: &str) -> Result<Vec<String>> {
+        Ok(self.state.labels.get(issue_id).map(|labels| labels.iter().cloned().collect()).unwrap_or_default())
+    }
+
+    fn get_issues_by_label(&self, label: &str) -> Result<Vec<Issue>> {
+        Ok(self.state.issues.iter()
+            .filter(|i| self.state.labels.get(&i.id).is_some_and(|labels| labels.contains(label)))
+            .cloned()
+            .collect())
+    }
+
+    fn get_ready_work(&self, filter: &WorkFilter) -> Result<Vec<Issue>> {
+        let workflow = self.get_workflow()?;
+        let mut issues: Vec<Issue> = self.state.issues.iter()
+            .filter(|i| i.status == Status::Open && Self::matches_work_filter(i, filter))
+            .filter(|i| self.open_blockers(&i.id, &workflow).is_empty())
+            .cloned()
+            .collect();
+
+        sort_issues(&mut issues, &filter.sort);
+        Ok(paginate(issues, filter.limit, filter.offset))
+    }
+
+    fn get_blocked_issues(&self, filter: &WorkFilter) -> Result<Vec<BlockedIssue>> {
+        let workflow = self.get_workflow()?;
+        let mut issues: Vec<Issue> = self.state.issues.iter()
+            .filter(|i| Self::matches_work_filter(i, filter))
+            .filter(|i| !self.open_blockers(&i.id, &workflow).is_empty())
+            .cloned()
+            .collect();
+
+        sort_issues(&mut issues, &filter.sort);
+        Ok(paginate(issues, filter.limit, filter.offset).into_iter()
+            .map(|issue| {
+                let blocked_by = self.open_blockers(&issue.id, &workflow);
+                BlockedIssue { issue, blocked_by_count: blocked_by.len() as i32, blocked_by }
+            })
+            .collect())
+    }
+
+    fn get_stale_issues(&self, inactive_since: DateTime<Utc>, status: Option<Status>) -> Result<Vec<StaleIssue>> {
+        let workflow = self.get_workflow()?;
+        let mut stale: Vec<StaleIssue> = self.state.issues.iter()
+            .filter(|i| !workflow.is_done(&i.status))
+            .filter(|i| status.as_ref().is_none_or(|s| &i.status == s))
+            .filter_map(|i| {
+                let latest_event = self.state.events.iter()
+                    .filter(|e| e.issue_id == i.id)
+                    .map(|e| e.created_at)
+                    .max();
+                let last_activity = latest_event.map_or(i.updated_at, |at| at.max(i.updated_at));
+                (last_activity < inactive_since).then(|| StaleIssue { issue: i.clone(), last_activity })
+            })
+            .collect();
+
+        stale.sort_by(|a, b| {
+            a.issue.assignee.cmp(&b.issue.assignee)
+                .then(a.last_activity.cmp(&b.last_activity))
+                .then(a.issue.id.cmp(&b.issue.id))
+        });
+        Ok(stale)
+    }
+
+    fn save_milestone(&mut self, milestone: &Milestone) -> Result<()> {
+        milestone.validate()?;
+        self.state.milestones.insert(milestone.name.clone(), milestone.clone());
+        Ok(())
+    }
+
+    fn get_milestone(&self, name: &str) -> Result<Option<Milestone>> {
+        Ok(self.state.milestones.get(name).cloned())
+    }
+
+    fn list_milestones(&self) -> Result<Vec<Milestone>> {
+        let mut milestones: Vec<Milestone> = self.state.milestones.values().cloned().collect();
+        milestones.sort_by(|a, b| {
+            nulls_last(a.target_date, b.target_date, false).then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(milestones)
+    }
+
+    fn get_milestone_report(&self, name: &str) -> Result<Option<MilestoneReport>> {
+        let Some(milestone) = self.get_milestone(name)? else {
+            return Ok(None);
+        };
+
+        let workflow = self.get_workflow()?;
+        let (mut total, mut open, mut remaining, mut unestimated) = (0, 0, 0, 0);
+        for issue in self.state.issues.iter().filter(|i| i.milestone.as_deref() == Some(name)) {
+            total += 1;
+            if !workflow.is_done(&issue.status) {
+                open += 1;
+                match issue.estimated_minutes {
+                    Some(minutes) => remaining += minutes as i64,
+                    None => unestimated += 1,
+                }
+            }
+        }
+
+        Ok(Some(MilestoneReport {
+            milestone,
+            total_issues: total,
+            open_issues: open,
+            closed_issues: total - open,
+            remaining_estimated_minutes: remaining,
+            unestimated_open_issues: unestimated,
+        }))
+    }
+
+    fn save_recurrence(&mut self, recurrence: &Recurrence) -> Result<()> {
+        self.state.recurrences.insert(recurrence.name.clone(), recurrence.clone());
+        Ok(())
+    }
+
+    fn get_recurrence(&self, name: &str) -> Result<Option<Recurrence>> {
+        Ok(self.state.recurrences.get(name).cloned())
+    }
+
+    fn list_recurrences(&self) -> Result<Vec<Recurrence>> {
+        Ok(self.state.recurrences.values().cloned().collect())
+    }
+
+    fn delete_recurrence(&mut self, name: &str) -> Result<()> {
+        if self.state.recurrences.remove(name).is_none() {
+            return Err(Error::not_found("Recurrence", name));
+        }
+        Ok(())
+    }
+
+    fn claim_recurrence_period(&mut self, name: &str, period: DateTime<Utc>) -> Result<bool> {
+        match self.state.recurrences.get_mut(name) {
+            Some(recurrence) if recurrence.last_period.is_none_or(|last| last < period) => {
+                recurrence.last_period = Some(period);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn get_issue_by_external_ref(&self, external_ref: &str) -> Result<Option<Issue>> {
+        Ok(self.state.issues.iter().find(|i| i.external_ref.as_deref() == Some(external_ref)).cloned())
+    }
+
+    fn add_work_log(&mut self, log: &WorkLog) -> Result<()> {
+        self.atomic(|this| {
+            if log.minutes < 0 {
+                return Err(Error::Validation("work log minutes cannot be negative".to_string()));
+            }
+            this.check_issue(&log.issue_id)?;
+            this.state.work_logs.push(log.clone());
+            this.mark_dirty(&log.issue_id)?;
+            Ok(())
+        })
+    }
+
+    fn get_work_logs(&self, issue_id: &str) -> Result<Vec<WorkLog>> {
+        let mut logs: Vec<WorkLog> = self.state.work_logs.iter().filter(|log| log.issue_id == issue_id).cloned().collect();
+        logs.sort_by_key(|log| log.started_at); // Stable, so ties keep insertion order
+        Ok(logs)
+    }
+
+    fn add_comment(&mut self, issue_id: &str, actor: &str, comment: &str) -> Result<()> {
+        if self.issue(issue_id).is_none() {
+            return Err(Error::not_found("Issue", issue_id));
+        }
+        self.add_event(issue_id, EventType::Commented, actor, None, None, Some(comment))
+    }
+
+    fn move_comments(&mut self, from_issue_id: &str, to_issue_id: &str) -> Result<usize> {
+        let is_comment = |e: &Event| e.issue_id == from_issue_id && e.event_type == EventType::Commented;
+        let count = self.state.events.iter().filter(|e| is_comment(e)).count();
+        if count > 0 {
+            self.check_issue(to_issue_id)?;
+        }
+        for event in self.state.events.iter_mut().filter(|e| is_comment(e)) {
+            event.issue_id = to_issue_id.to_string();
+        }
+        Ok(count)
+    }
+
+    fn get_events(&self, issue_id: &str, limit: usize) -> Result<Vec<Event>> {
+        let mut events: Vec<Event> = self.state.events.iter().filter(|e| e.issue_id == issue_id).cloned().collect();
+        events.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+        events.truncate(limit);
+        Ok(events)
+    }
+
+    fn get_all_events(&self, filter: &EventFilter) -> Result<Vec<Event>> {
+        let mut events: Vec<Event> = self.state.events.iter()
+            .filter(|e| filter.since.is_none_or(|since| e.created_at >= since))
+            .filter(|e| filter.until.is_none_or(|until| e.created_at < until))
+            .filter(|e| filter.actor.as_ref().is_none_or(|actor| &e.actor == actor))
+            .filter(|e| filter.event_types.is_empty() || filter.event_types.contains(&e.event_type))
+            .cloned()
+            .collect();
+        events.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+        Ok(events)
+    }
+
+    fn compact_events(&mut self, before: DateTime<Utc>) -> Result<usize> {
+        let count = self.state.events.len();
+        self.state.events = super::compact_routine_events(std::mem::take(&mut self.state.events), before);
+        Ok(count - self.state.events.len())
+    }
+
+    fn get_statistics(&self) -> Result<Statistics> {
+        let workflow = self.get_workflow()?;
+        let issues = &self.state.issues;
+        let count = |pred: &dyn Fn(&Issue) -> bool| issues.iter().filter(|i| pred(i)).count() as i32;
+
+        let lead_times: Vec<f64> = issues.iter()
+            .filter_map(|i| i.closed_at.map(|closed| closed.signed_duration_since(i.created_at)))
+            .map(|lead| lead.num_milliseconds() as f64 / 3_600_000.0)
+            .collect();
+        let avg_lead_time = if lead_times.is_empty() { 0.0 } else { lead_times.iter().sum::<f64>() / lead_times.len() as f64 };
+
+        let now = Utc::now();
+        let soon = now + chrono::Duration::days(7);
+        let due_open = |i: &&Issue| i.due_at.is_some() && !workflow.is_done(&i.status);
+
+        let mut estimation_by_type: BTreeMap<String, EstimationAccuracy> = BTreeMap::new();
+        let mut estimation_by_actor: BTreeMap<String, EstimationAccuracy> = BTreeMap::new();
+        for issue in issues.iter().filter(|i| i.status == Status::Closed) {
+            let Some(estimated) = issue.estimated_minutes else { continue };
+            let actual: i64 = self.state.work_logs.iter()
+                .filter(|log| log.issue_id == issue.id && log.ended_at.is_some())
+                .map(|log| log.minutes)
+                .sum();
+            if actual <= 0 {
+                continue;
+            }
+            for entry in [estimation_by_type.entry(issue.issue_type.to_string()).or_default(), estimation_by_actor.entry(issue.assignee.clone()).or_default()] {
+                entry.issues += 1;
+                entry.estimated_minutes += estimated as i64;
+                entry.actual_minutes += actual;
+            }
+        }
+        for entry in estimation_by_type.values_mut().chain(estimation_by_actor.values_mut()) {
+            if entry.estimated_minutes > 0 {
+                entry.ratio = entry.actual_minutes as f64 / entry.estimated_minutes as f64;
+            }
+        }
+
+        Ok(Statistics {
+            total_issues: issues.len() as i32,
+            open_issues: count(&|i| i.status == Status::Open),
+            in_progress_issues: count(&|i| i.status == Status::InProgress),
+            closed_issues: count(&|i| i.status == Status::Closed),
+            blocked_issues: count(&|i| !self.open_blockers(&i.id, &workflow).is_empty()),
+            ready_issues: count(&|i| i.status == Status::Open && self.open_blockers(&i.id, &workflow).is_empty()),
+            average_lead_time_hours: avg_lead_time,
+            overdue_issues: issues.iter().filter(due_open).filter(|i| i.due_at < Some(now)).count() as i32,
+            due_soon_issues: issues.iter().filter(due_open).filter(|i| i.due_at >= Some(now) && i.due_at < Some(soon)).count() as i32,
+            next_due_at: issues.iter().filter(due_open).filter_map(|i| i.due_at).filter(|due| *due >= now).min(),
+            estimation_by_type,
+            estimation_by_actor,
+        })
+    }
+
+    fn get_dirty_issues(&self) -> Result<Vec<String>> {
+        Ok(self.state.dirty.clone())
+    }
+
+    fn clear_dirty_issues(&mut self) -> Result<()> {
+        self.state.dirty.clear();
+        Ok(())
+    }
+
+    fn clear_dirty_issues_by_id(&mut self, issue_ids: &[String]) -> Result<()> {
+        self.state.dirty.retain(|id| !issue_ids.contains(id));
+        Ok(())
+    }
+
+    fn archive_issues(&mut self, closed_before: DateTime<Utc>) -> Result<Vec<String>> {
+        let candidates = self.state.issues.iter()
+            .filter(|i| i.status == Status::Closed && i.closed_at.is_some_and(|closed| closed < closed_before))
+            .map(|i| i.id.clone())
+            .collect();
+        let ids = super::archivable(candidates, &self.state.dependencies);
+
+        let archived_at = Utc::now();
+        for id in &ids {
+            let issue = Issue {
+                dependencies: self.get_dependency_records(id)?,
+                work_logs: self.get_work_logs(id)?,
+                ..self.issue(id).cloned().ok_or_else(|| Error::not_found("Issue", id))?
+            };
+            let events = self.state.events.iter().filter(|e| &e.issue_id == id).cloned().collect();
+            let archived = ArchivedIssue {
+                issue,
+                labels: self.get_labels(id)?,
+                events: super::compact_routine_events(events, archived_at),
+                archived_at,
+            };
+            self.remove_issue(id);
+            self.state.archived.insert(id.clone(), archived);
+        }
+        Ok(ids)
+    }
+
+    fn save_archived_issue(&mut self, archived: &ArchivedIssue) -> Result<()> {
+        self.remove_issue(&archived.issue.id);
+        self.state.archived.insert(archived.issue.id.clone(), archived.clone());
+        Ok(())
+    }
+
+    fn get_archived_issue(&self, id: &str) -> Result<Option<ArchivedIssue>> {
+        Ok(self.state.archived.get(id).cloned())
+    }
+
+    fn list_archived_issues(&self) -> Result<Vec<ArchivedIssue>> {
+        Ok(self.state.archived.values().cloned().collect())
+    }
+
+    fn set_config(&mut self, key: &str, value: &str) -> Result<()> {
+        self.state.config.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn get_config(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.state.config.get(key).cloned())
+    }
+
+    fn get_workflow(&self) -> Result<Workflow> {
+        match self.get_config("workflow")? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(Workflow::default()),
+        }
+    }
+
+    fn set_workflow(&mut self, workflow: &Workflow) -> Result<()> {
+        workflow.validate()?;
+        self.set_config("workflow", &serde_json::to_string(workflow)?)
+    }
+
+    fn set_metadata(&mut self, key: &str, value: &str) -> Result<()> {
+        self.state.metadata.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn get_metadata(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.state.metadata.get(key).cloned())
+    }
+
+    fn generate_id(&mut self, prefix: &str) -> Result<String> {
+        let counter = self.state.counters.entry(prefix.to_string()).or_insert(0);
+        *counter += 1;
+        Ok(format!("{}-{}", prefix, counter))
+    }
+
+    fn begin_transaction(&mut self) -> Result<()> {
+        self.savepoints.push(self.state.clone());
+        Ok(())
+    }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        self.savepoints.pop().ok_or_else(|| Error::Validation("no transaction is open".to_string()))?;
+        Ok(())
+    }
+
+    fn rollback_transaction(&mut self) -> Result<()> {
+        self.state = self.savepoints.pop().ok_or_else(|| Error::Validation("no transaction is open".to_string()))?;
+        Ok(())
+    }
+}
+
+/// Order issues the way `SqliteStorage`'s ORDER BY does, with the ID as final tiebreaker
+pub(super) fn sort_issues(issues: &mut [Issue], sort: &[SortKey]) {
+    let default_order = SortKey::default_order();
+    let keys = if sort.is_empty() { &default_order[..] } else { sort };
+
+    issues.sort_by(|a, b| {
+        keys.iter()
+            .map(|key| compare(a, b, key))
+            .find(|ord| ord.is_ne())
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+}
+
+fn compare(a: &Issue, b: &Issue, key: &SortKey) -> Ordering {
+    let ord = match key.field {
+        // Prefix, then the numeric suffix, so bd-10 sorts after bd-9
+        SortField::Id => id_sort_key(&a.id).cmp(&id_sort_key(&b.id)),
+        SortField::Title => a.title.to_ascii_lowercase().cmp(&b.title.to_ascii_lowercase()),
+        SortField::Status => a.status.to_string().cmp(&b.status.to_string()),
+        SortField::Priority => a.priority.cmp(&b.priority),
+        SortField::Created => a.created_at.cmp(&b.created_at),
+        SortField::Updated => a.updated_at.cmp(&b.updated_at),
+        // Nullable fields: issues without a value always sort last
+        SortField::Closed => return nulls_last(a.closed_at, b.closed_at, key.descending),
+        SortField::Estimate => return nulls_last(a.estimated_minutes, b.estimated_minutes, key.descending),
+        SortField::Due => return nulls_last(a.due_at, b.due_at, key.descending),
+    };
+    if key.descending { ord.reverse() } else { ord }
+}
+
+fn nulls_last<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Split an ID into its prefix and number; like SQLite's CAST, a suffix without
+/// leading digits counts as 0
+fn id_sort_key(id: &str) -> (&str, i64) {
+    let (prefix, suffix) = id.split_once('-').unwrap_or(("", id));
+    let digits = suffix.len() - suffix.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    (prefix, suffix[..digits].parse().unwrap_or(0))
+}
+
+pub(super) fn paginate<T>(items: Vec<T>, limit: Option<usize>, offset: Option<usize>) -> Vec<T> {
+    items.into_iter()
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
@@ -1,7 +1,79 @@
+pub mod jsonl;
+pub mod memory;
 pub mod sqlite;
 
+use crate::error::{Error, Result};
 use crate::types::*;
-use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{File, TryLockError};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How long to wait for another process to release a lock before failing with
+/// `Error::Locked`, unless `$TRACE_BUSY_TIMEOUT` says otherwise
+pub const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest pause between two attempts to take a lock
+const MAX_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Backend is the kind of storage a database path refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// A SQLite database (`.trace/<prefix>.db`), synced to `issues.jsonl` for git
+    #[default]
+    Sqlite,
+    /// Plain text files only: `issues.jsonl` plus a `.trace/<prefix>.json` store
+    Jsonl,
+}
+
+impl Backend {
+    /// The backend for a database path: `.json` store files use the JSONL backend
+    pub fn for_path(path: &Path) -> Backend {
+        match path.extension() {
+            Some(ext) if ext == "json" => Backend::Jsonl,
+            _ => Backend::Sqlite,
+        }
+    }
+
+    /// File extension of the database path for this backend
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Backend::Sqlite => "db",
+            Backend::Jsonl => "json",
+        }
+    }
+
+    /// Open (or create) the storage at `path`
+    pub fn open(&self, path: &Path) -> Result<Box<dyn Storage>> {
+        Ok(match self {
+            Backend::Sqlite => Box::new(sqlite::SqliteStorage::new(path)?),
+            Backend::Jsonl => Box::new(jsonl::JsonlStorage::open(path)?),
+        })
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::Sqlite => write!(f, "sqlite"),
+            Backend::Jsonl => write!(f, "jsonl"),
+        }
+    }
+}
+
+impl std::str::FromStr for Backend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sqlite" => Ok(Backend::Sqlite),
+            "jsonl" => Ok(Backend::Jsonl),
+            _ => anyhow::bail!("invalid backend: {} (expected sqlite or jsonl)", s),
+        }
+    }
+}
 
 /// Storage defines the interface for issue storage backends
 pub trait Storage {
@@ -30,15 +102,50 @@ pub trait Storage {
 
     // Ready Work & Blocking
     fn get_ready_work(&self, filter: &WorkFilter) -> Result<Vec<Issue>>;
-    fn get_blocked_issues(&self) -> Result<Vec<BlockedIssue>>;
+    fn get_blocked_issues(&self, filter: &WorkFilter) -> Result<Vec<BlockedIssue>>;
+    fn get_stale_issues(&self, inactive_since: DateTime<Utc>, status: Option<Status>) -> Result<Vec<StaleIssue>>;
+
+    // Milestones
+    fn save_milestone(&mut self, milestone: &Milestone) -> Result<()>; // Insert or replace
+    fn get_milestone(&self, name: &str) -> Result<Option<Milestone>>;
+    fn list_milestones(&self) -> Result<Vec<Milestone>>;
+    fn get_milestone_report(&self, name: &str) -> Result<Option<MilestoneReport>>;
+
+    // Recurring issues
+    fn save_recurrence(&mut self, recurrence: &Recurrence) -> Result<()>; // Insert or replace
+    fn get_recurrence(&self, name: &str) -> Result<Option<Recurrence>>;
+    fn list_recurrences(&self) -> Result<Vec<Recurrence>>;
+    fn delete_recurrence(&mut self, name: &str) -> Result<()>;
+    /// Atomically advance last_period; returns false if another run already claimed this period
+    fn claim_recurrence_period(&mut self, name: &str, period: DateTime<Utc>) -> Result<bool>;
+    fn get_issue_by_external_ref(&self, external_ref: &str) -> Result<Option<Issue>>;
+
+    // Work logs
+    fn add_work_log(&mut self, log: &WorkLog) -> Result<()>;
+    fn get_work_logs(&self, issue_id: &str) -> Result<Vec<WorkLog>>; // Oldest first
 
     // Events
     fn add_comment(&mut self, issue_id: &str, actor: &str, comment: &str) -> Result<()>;
+    fn move_comments(&mut self, from_issue_id: &str, to_issue_id: &str) -> Result<usize>; // Keeps original actor and time
     fn get_events(&self, issue_id: &str, limit: usize) -> Result<Vec<Event>>;
+    fn get_all_events(&self, filter: &EventFilter) -> Result<Vec<Event>>; // Oldest first
+
+    fn compact_events(&mut self, before: DateTime<Utc>) -> Result<usize>; // Drops routine events (see compact_routine_events); returns how many
 
     // Statistics
     fn get_statistics(&self) -> Result<Statistics>;
 
+    // Archive
+    fn archive_issues(&mut self, closed_before: DateTime<Utc>) -> Result<Vec<String>>; // Moves closed issues nothing live depends on; returns their IDs
+    fn save_archived_issue(&mut self, archived: &ArchivedIssue) -> Result<()>; // Insert or replace, removing any live copy
+    fn get_archived_issue(&self, id: &str) -> Result<Option<ArchivedIssue>>;
+    fn list_archived_issues(&self) -> Result<Vec<ArchivedIssue>>; // Sorted by ID
+
+    /// Archived issues matching the filter, with the same semantics as `search_issues`
+    fn search_archived_issues(&self, query: &str, filter: &IssueFilter) -> Result<Vec<Issue>> {
+        memory::InMemoryStorage::from_archive(self.list_archived_issues()?).search_issues(query, filter)
+    }
+
     // Dirty tracking (for incremental JSONL export)
     fn get_dirty_issues(&self) -> Result<Vec<String>>;
     fn clear_dirty_issues(&mut self) -> Result<()>;
@@ -48,16 +155,126 @@ pub trait Storage {
     fn set_config(&mut self, key: &str, value: &str) -> Result<()>;
     fn get_config(&self, key: &str) -> Result<Option<String>>;
 
+    // Workflow (custom statuses and allowed transitions, stored in config)
+    fn get_workflow(&self) -> Result<Workflow>;
+    fn set_workflow(&mut self, workflow: &Workflow) -> Result<()>;
+
     // Metadata (for internal state like import hashes)
     fn set_metadata(&mut self, key: &str, value: &str) -> Result<()>;
     fn get_metadata(&self, key: &str) -> Result<Option<String>>;
 
     // ID generation
     fn generate_id(&mut self, prefix: &str) -> Result<String>;
+
+    // Transactions (may nest; an inner rollback only undoes the inner work)
+    fn begin_transaction(&mut self) -> Result<()>;
+    fn commit_transaction(&mut self) -> Result<()>;
+    fn rollback_transaction(&mut self) -> Result<()>;
+}
+
+/// Run `f` in a transaction: commit if it succeeds, roll everything back if it fails.
+/// `f` may return any error type that a storage error converts into (e.g. anyhow)
+pub fn with_transaction<T, E: From<Error>>(
+    storage: &mut Box<dyn Storage>,
+    f: impl FnOnce(&mut Box<dyn Storage>) -> Result<T, E>,
+) -> Result<T, E> {
+    storage.begin_transaction()?;
+    match f(storage) {
+        Ok(value) => {
+            storage.commit_transaction()?;
+            Ok(value)
+        }
+        Err(err) => {
+            // Surface the original error; a failed rollback can't be handled better here
+            let _ = storage.rollback_transaction();
+            Err(err)
+        }
+    }
+}
+
+/// Take an exclusive advisory lock on `path` (created if missing), waiting up to
+/// `timeout` for other processes to release it. The lock is held until the
+/// returned file is dropped.
+pub fn lock_file(path: &Path, timeout: Duration) -> Result<File> {
+    let file = File::create(path)?;
+    retry_while_locked(timeout, || match file.try_lock() {
+        Ok(()) => Ok(()),
+        Err(TryLockError::WouldBlock) => Err(Error::Locked),
+        Err(TryLockError::Error(err)) => Err(err.into()),
+    })?;
+    Ok(file)
+}
+
+/// Run `op` until it returns anything but `Error::Locked`, backing off
+/// exponentially between attempts, or until `timeout` has passed
+pub(crate) fn retry_while_locked<T>(timeout: Duration, mut op: impl FnMut() -> Result<T>) -> Result<T> {
+    let deadline = Instant::now() + timeout;
+    let mut backoff = Duration::from_millis(2);
+    loop {
+        match op() {
+            Err(Error::Locked) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(Error::Locked);
+                }
+                // Sleep for a random half to all of the backoff, so processes that
+                // collided don't keep retrying in step
+                let jitter = f64::from(Utc::now().timestamp_subsec_nanos() % 1000) / 1000.0;
+                let pause = backoff / 2 + (backoff / 2).mul_f64(jitter);
+                std::thread::sleep(pause.min(deadline - now));
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Sort and page through issues gathered from more than one query, in the same
+/// order `search_issues` would return them
+pub fn sort_and_paginate(mut issues: Vec<Issue>, filter: &IssueFilter) -> Vec<Issue> {
+    memory::sort_issues(&mut issues, &filter.sort);
+    memory::paginate(issues, filter.limit, filter.offset)
+}
+
+/// Which of the candidate issues can be archived: those no issue staying live
+/// depends on, so archiving never leaves a dangling dependency
+pub(crate) fn archivable(candidates: Vec<String>, dependencies: &[Dependency]) -> Vec<String> {
+    let mut archivable: HashSet<String> = candidates.iter().cloned().collect();
+    loop {
+        let kept: Vec<String> = dependencies.iter()
+            .filter(|dep| archivable.contains(&dep.depends_on_id) && !archivable.contains(&dep.issue_id))
+            .map(|dep| dep.depends_on_id.clone())
+            .collect();
+        if kept.is_empty() {
+            break;
+        }
+        for id in kept {
+            archivable.remove(&id);
+        }
+    }
+    candidates.into_iter().filter(|id| archivable.contains(id)).collect()
+}
+
+/// Compact the audit trail: an `updated` event directly followed by another
+/// `updated` event on the same issue, both before `before`, is dropped, so each
+/// run of edits keeps only its last event. Comments and every other kind of
+/// event are kept. `events` must be oldest first.
+pub(crate) fn compact_routine_events(events: Vec<Event>, before: DateTime<Utc>) -> Vec<Event> {
+    let routine = |event: &Event| event.event_type == EventType::Updated && event.created_at < before;
+    let mut keep = vec![true; events.len()];
+    let mut previous: HashMap<&str, usize> = HashMap::new();
+    for (idx, event) in events.iter().enumerate() {
+        if let Some(prev) = previous.insert(&event.issue_id, idx) {
+            if routine(&events[prev]) && routine(event) {
+                keep[prev] = false;
+            }
+        }
+    }
+    events.into_iter().zip(keep).filter(|(_, keep)| *keep).map(|(event, _)| event).collect()
 }
 
 /// IssueUpdates represents fields that can be updated on an issue
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct IssueUpdates {
     pub title: Option<String>,
     pub description: Option<String>,
@@ -70,5 +287,8 @@ pub struct IssueUpdates {
     pub assignee: Option<String>,
     pub estimated_minutes: Option<Option<i32>>, // None = don't update, Some(None) = clear field
     pub external_ref: Option<Option<String>>,
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    pub milestone: Option<Option<String>>,
+    pub bypass_workflow: bool, // Imports replay existing state, so transition rules don't apply
 }
 
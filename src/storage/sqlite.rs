@@ -1,126 +1,302 @@
 use super::{IssueUpdates, Storage};
+use crate::error::{Error, Result};
 use crate::types::*;
-use anyhow::Result;
-use chrono::Utc;
-use rusqlite::{params, Connection, OptionalExtension};
-use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
+use std::time::Duration;
 
 pub struct SqliteStorage {
     conn: Connection,
+    busy_timeout: Duration, // How long to wait for another process's write lock
+    depth: usize,           // Open transactions
 }
 
 impl SqliteStorage {
+    /// Open a database, applying any pending schema migrations (after backing up
+    /// an existing database to the backups directory). Waits for other processes'
+    /// locks as long as `$TRACE_BUSY_TIMEOUT` says (see `utils::busy_timeout`).
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::with_busy_timeout(path, crate::utils::busy_timeout())
+    }
+
+    /// Like `new`, waiting up to `busy_timeout` whenever another process holds the
+    /// write lock before failing with `Error::Locked`
+    pub fn with_busy_timeout<P: AsRef<Path>>(path: P, busy_timeout: Duration) -> Result<Self> {
+        let mut storage = Self::connect(&path, busy_timeout)?;
+
+        // Keep a copy of an existing database from before its schema changes
+        let has_tables: bool = storage.conn.query_row("SELECT COUNT(*) > 0 FROM sqlite_master", [], |row| row.get(0))?;
+        if has_tables && !storage.pending_migrations()?.is_empty() {
+            crate::backup::create_automatic(path.as_ref(), "pre-migration")?;
+        }
+
+        storage.migrate()?;
+        storage.sync_counters()?;
+        Ok(storage)
+    }
+
+    /// Open a database without migrating it, e.g. to report its schema version.
+    /// Fails if the database was created by a newer tracer.
+    pub fn open_unmigrated<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::connect(path, crate::utils::busy_timeout())
+    }
+
+    fn connect<P: AsRef<Path>>(path: P, busy_timeout: Duration) -> Result<Self> {
         // Create parent directory if it doesn't exist
         if let Some(parent) = path.as_ref().parent() {
             std::fs::create_dir_all(parent)?;
         }
 
         let conn = Connection::open(path)?;
-        
+
+        // Wait for other processes' locks instead of failing straight away
+        // (this applies to the pragmas below too)
+        conn.busy_timeout(busy_timeout)?;
+
         // Enable WAL mode for better concurrency
         conn.pragma_update(None, "journal_mode", "WAL")?;
         conn.pragma_update(None, "foreign_keys", "ON")?;
 
-        // Initialize schema
-        Self::init_schema(&conn)?;
-        
-        Ok(Self { conn })
+        let storage = Self { conn, busy_timeout, depth: 0 };
+        let version = storage.schema_version()?;
+        if version > SCHEMA_VERSION {
+            return Err(Error::Schema(format!(
+                "database is at schema version {}, but this tracer only supports up to {}; upgrade tracer to open it",
+                version, SCHEMA_VERSION
+            )));
+        }
+        Ok(storage)
     }
 
-    fn init_schema(conn: &Connection) -> Result<()> {
-        conn.execute_batch(SCHEMA)?;
-        Self::migrate_tables(conn)?;
-        Ok(())
+    /// The schema version the database is at (0 for a new or pre-versioning database)
+    pub fn schema_version(&self) -> Result<u32> {
+        Ok(self.conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
     }
 
-    fn migrate_tables(conn: &Connection) -> Result<()> {
-        // Check if dirty_issues table exists
-        let dirty_exists: bool = conn
-            .query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='dirty_issues'",
-                [],
-                |row| row.get(0),
-            )
-            .map(|count: i64| count > 0)?;
+    /// Migrations newer than the database's schema version, oldest first
+    pub fn pending_migrations(&self) -> Result<Vec<&'static Migration>> {
+        let version = self.schema_version()?;
+        Ok(MIGRATIONS.iter().filter(|m| m.version > version).collect())
+    }
 
-        if !dirty_exists {
-            conn.execute_batch(
-                "CREATE TABLE dirty_issues (
-                    issue_id TEXT PRIMARY KEY,
-                    marked_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-                    FOREIGN KEY (issue_id) REFERENCES issues(id) ON DELETE CASCADE
-                );
-                CREATE INDEX idx_dirty_issues_marked_at ON dirty_issues(marked_at);"
-            )?;
+    /// Apply all pending migrations in one transaction and return them. If any
+    /// migration fails, the database is left at its previous version.
+    pub fn migrate(&mut self) -> Result<Vec<&'static Migration>> {
+        // Don't take the write lock when there is nothing to do, so opening an
+        // up-to-date database never waits for other writers
+        if self.pending_migrations()?.is_empty() {
+            return Ok(Vec::new());
         }
 
-        // Check if issue_counters table exists and sync
-        let counters_exists: bool = conn
-            .query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='issue_counters'",
-                [],
-                |row| row.get(0),
-            )
-            .map(|count: i64| count > 0)?;
+        // Take the write lock up front, so concurrent processes migrate one at a time
+        let tx = self.conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let version: u32 = tx.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let pending: Vec<&'static Migration> = MIGRATIONS.iter().filter(|m| m.version > version).collect();
+        for migration in &pending {
+            (migration.apply)(&tx)?;
+            tx.pragma_update(None, "user_version", migration.version)?;
+        }
+        tx.commit()?;
+        Ok(pending)
+    }
 
-        if !counters_exists {
-            conn.execute_batch(
-                "CREATE TABLE issue_counters (
-                    prefix TEXT PRIMARY KEY,
-                    last_id INTEGER NOT NULL DEFAULT 0
-                );"
-            )?;
+    /// Seed the ID counters from existing issues, e.g. after importing into a
+    /// fresh database
+    fn sync_counters(&self) -> Result<()> {
+        // Check first, so opening an already-seeded database doesn't need the write lock
+        let seeded: bool = self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM issue_counters) OR NOT EXISTS (SELECT 1 FROM issues)",
+            [],
+            |row| row.get(0),
+        )?;
+        if seeded {
+            return Ok(());
         }
 
-        // Sync counters from existing issues if empty
-        let counter_count: i64 = conn.query_row("SELECT COUNT(*) FROM issue_counters", [], |row| row.get(0))?;
-        if counter_count == 0 {
-            conn.execute(
-                "INSERT INTO issue_counters (prefix, last_id)
-                 SELECT substr(id, 1, instr(id, '-') - 1) as prefix,
-                        MAX(CAST(substr(id, instr(id, '-') + 1) AS INTEGER)) as max_id
-                 FROM issues
-                 WHERE instr(id, '-') > 0
-                 GROUP BY prefix",
-                [],
-            )?;
+        // One statement, so processes opening the database at once can't both seed it
+        self.conn.execute(
+            &format!(
+                "INSERT INTO issue_counters (prefix, last_id) SELECT prefix, max_id FROM ({})
+                 WHERE NOT EXISTS (SELECT 1 FROM issue_counters)",
+                MAX_IDS_BY_PREFIX
+            ),
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Check invariants the schema doesn't enforce by itself: SQLite's own
+    /// integrity check, rows pointing at issues that don't exist, `closed_at`
+    /// agreeing with the status, and ID counters staying ahead of existing IDs
+    pub fn check_integrity(&self) -> Result<Vec<Finding>> {
+        let mut findings = Vec::new();
+
+        let mut stmt = self.conn.prepare("PRAGMA integrity_check")?;
+        let messages = stmt.query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for message in messages.into_iter().filter(|message| message != "ok") {
+            findings.push(Finding::new("integrity_check", message, None, false));
         }
 
-        // Check if external_ref column exists
-        let has_external_ref: bool = conn
-            .prepare("PRAGMA table_info(issues)")?
-            .query_map([], |row| row.get::<_, String>(1))?
-            .filter_map(|r| r.ok())
-            .any(|name| name == "external_ref");
+        let mut orphans: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (table, rowid) in self.foreign_key_violations()? {
+            if table == "dependencies" {
+                let (issue_id, depends_on_id): (String, String) = self.conn.query_row(
+                    "SELECT issue_id, depends_on_id FROM dependencies WHERE rowid = ?1",
+                    params![rowid],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )?;
+                let message = format!("dependency {} -> {} references an issue that does not exist", issue_id, depends_on_id);
+                findings.push(Finding::new("orphaned_row", message, None, true));
+            } else {
+                let issue_id: String = self.conn.query_row(
+                    &format!("SELECT issue_id FROM {} WHERE rowid = ?1", table),
+                    params![rowid],
+                    |row| row.get(0),
+                )?;
+                orphans.entry(table).or_default().push(issue_id);
+            }
+        }
+        for (table, mut issue_ids) in orphans {
+            let count = issue_ids.len();
+            issue_ids.sort();
+            issue_ids.dedup();
+            let message = format!("{} {} row(s) belong to issues that do not exist: {}", count, table, issue_ids.join(", "));
+            findings.push(Finding::new("orphaned_row", message, None, true));
+        }
 
-        if !has_external_ref {
-            conn.execute("ALTER TABLE issues ADD COLUMN external_ref TEXT", [])?;
+        let mut stmt = self.conn.prepare("SELECT id FROM issues WHERE status = 'closed' AND closed_at IS NULL ORDER BY id")?;
+        for id in stmt.query_map([], |row| row.get::<_, String>(0))? {
+            let id = id?;
+            findings.push(Finding::new("closed_at", format!("{} is closed but has no closed_at", id), Some(&id), true));
         }
 
-        // Check if metadata table exists
-        let metadata_exists: bool = conn
-            .query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='metadata'",
+        let mut stmt = self.conn.prepare(
+            "SELECT id, status FROM issues WHERE status != 'closed' AND closed_at IS NOT NULL ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (id, status) = row?;
+            let message = format!("{} is {} but has a closed_at", id, status);
+            findings.push(Finding::new("closed_at", message, Some(&id), true));
+        }
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT m.prefix, m.max_id, COALESCE(c.last_id, 0)
+             FROM ({}) m LEFT JOIN issue_counters c ON c.prefix = m.prefix
+             WHERE COALESCE(c.last_id, 0) < m.max_id
+             ORDER BY m.prefix",
+            MAX_IDS_BY_PREFIX
+        ))?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?)))?;
+        for row in rows {
+            let (prefix, max_id, last_id) = row?;
+            let message = format!(
+                "ID counter for {} is at {}, behind the existing {}-{}, so new issues would reuse IDs",
+                prefix, last_id, prefix, max_id
+            );
+            findings.push(Finding::new("issue_counter", message, None, true));
+        }
+
+        Ok(findings)
+    }
+
+    /// Repair everything `check_integrity` reports as fixable, in one transaction:
+    /// delete orphaned rows, make `closed_at` agree with the status and raise
+    /// lagging ID counters. Returns the number of rows changed.
+    pub fn repair_integrity(&mut self) -> Result<usize> {
+        self.atomic(|this| {
+            let mut changed = 0;
+
+            for (table, rowid) in this.foreign_key_violations()? {
+                if table == "dependencies" {
+                    // The issue that keeps the dependency may still exist; re-export it without it
+                    this.conn.execute(
+                        "INSERT OR IGNORE INTO dirty_issues (issue_id)
+                         SELECT issue_id FROM dependencies
+                         WHERE rowid = ?1 AND issue_id IN (SELECT id FROM issues)",
+                        params![rowid],
+                    )?;
+                }
+                changed += this.conn.execute(&format!("DELETE FROM {} WHERE rowid = ?1", table), params![rowid])?;
+            }
+
+            for condition in [
+                "status = 'closed' AND closed_at IS NULL",
+                "status != 'closed' AND closed_at IS NOT NULL",
+            ] {
+                this.conn.execute(
+                    &format!("INSERT OR IGNORE INTO dirty_issues (issue_id) SELECT id FROM issues WHERE {}", condition),
+                    [],
+                )?;
+            }
+            // Closed issues without a closing time are taken to have closed at their last update
+            changed += this.conn.execute(
+                "UPDATE issues SET closed_at = updated_at WHERE status = 'closed' AND closed_at IS NULL",
                 [],
-                |row| row.get(0),
-            )
-            .map(|count: i64| count > 0)?;
+            )?;
+            changed += this.conn.execute(
+                "UPDATE issues SET closed_at = NULL WHERE status != 'closed' AND closed_at IS NOT NULL",
+                [],
+            )?;
 
-        if !metadata_exists {
-            conn.execute_batch(
-                "CREATE TABLE metadata (
-                    key TEXT PRIMARY KEY,
-                    value TEXT NOT NULL,
-                    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
-                );"
+            changed += this.conn.execute(
+                &format!(
+                    "INSERT INTO issue_counters (prefix, last_id)
+                     SELECT m.prefix, m.max_id FROM ({}) m
+                     LEFT JOIN issue_counters c ON c.prefix = m.prefix
+                     WHERE COALESCE(c.last_id, 0) < m.max_id
+                     ON CONFLICT(prefix) DO UPDATE SET last_id = excluded.last_id",
+                    MAX_IDS_BY_PREFIX
+                ),
+                [],
             )?;
-        }
 
+            Ok(changed)
+        })
+    }
+
+    /// Everything recorded about an issue, packed up for the archive
+    fn build_archived_issue(&self, id: &str, archived_at: DateTime<Utc>) -> Result<ArchivedIssue> {
+        let mut issue = self.get_issue(id)?.ok_or_else(|| Error::not_found("Issue", id))?;
+        issue.dependencies = self.get_dependency_records(id)?;
+        issue.work_logs = self.get_work_logs(id)?;
+
+        let mut labels = self.get_labels(id)?;
+        labels.sort();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, issue_id, event_type, actor, old_value, new_value, comment, created_at
+             FROM events WHERE issue_id = ?1 ORDER BY id"
+        )?;
+        let events = stmt.query_map(params![id], event_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ArchivedIssue {
+            issue,
+            labels,
+            events: super::compact_routine_events(events, archived_at),
+            archived_at,
+        })
+    }
+
+    fn insert_archived_issue(&self, archived: &ArchivedIssue) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO archived_issues (id, data, archived_at) VALUES (?1, ?2, ?3)",
+            params![archived.issue.id, serde_json::to_string(archived)?, archived.archived_at],
+        )?;
         Ok(())
     }
 
+    /// Rows whose foreign keys point at missing rows, as (table, rowid)
+    fn foreign_key_violations(&self) -> Result<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare("PRAGMA foreign_key_check")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
     fn get_next_id(&mut self, prefix: &str) -> Result<String> {
         let next_num: i64 = self.conn.query_row(
             "INSERT INTO issue_counters (prefix, last_id) VALUES (?1, 1)
@@ -132,6 +308,37 @@ impl SqliteStorage {
         Ok(format!("{}-{}", prefix, next_num))
     }
 
+    /// SQL condition that is true when `column` holds a status that still blocks
+    /// dependents, i.e. anything other than `closed` or a custom done status
+    fn not_done_condition(&self, column: &str) -> Result<String> {
+        let done: Vec<String> = self.get_workflow()?.done_statuses().iter()
+            .map(|status| format!("'{}'", status.to_string().replace('\'', "''")))
+            .collect();
+        Ok(format!("{} NOT IN ({})", column, done.join(", ")))
+    }
+
+    /// Run a statement that needs the write lock, retrying with backoff for as
+    /// long as the busy timeout allows. SQLite's busy handler already waits in
+    /// most cases; this covers the ones where it gives up straight away.
+    fn retry_while_locked(&self, sql: &str) -> Result<()> {
+        super::retry_while_locked(self.busy_timeout, || Ok(self.conn.execute_batch(sql)?))
+    }
+
+    /// Run a multi-statement write in a transaction, so a failure partway leaves nothing behind
+    fn atomic<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.begin_transaction()?;
+        match f(self) {
+            Ok(value) => {
+                self.commit_transaction()?;
+                Ok(value)
+            }
+            Err(err) => {
+                let _ = self.rollback_transaction();
+                Err(err)
+            }
+        }
+    }
+
     fn mark_dirty(&mut self, issue_id: &str) -> Result<()> {
         self.conn.execute(
             "INSERT OR IGNORE INTO dirty_issues (issue_id) VALUES (?1)",
@@ -140,6 +347,35 @@ impl SqliteStorage {
         Ok(())
     }
 
+    /// Open an automatic work log entry when an issue moves into in_progress
+    fn start_tracking(&mut self, issue_id: &str, actor: &str, at: DateTime<Utc>) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO work_logs (issue_id, actor, minutes, note, started_at, ended_at, source)
+             SELECT ?1, ?2, 0, '', ?3, NULL, 'auto'
+             WHERE NOT EXISTS (SELECT 1 FROM work_logs WHERE issue_id = ?1 AND ended_at IS NULL)",
+            params![issue_id, actor, at],
+        )?;
+        Ok(())
+    }
+
+    /// Close the running automatic entry (if any) when an issue leaves in_progress
+    fn stop_tracking(&mut self, issue_id: &str, at: DateTime<Utc>) -> Result<()> {
+        let running: Option<(i64, DateTime<Utc>)> = self.conn.query_row(
+            "SELECT id, started_at FROM work_logs WHERE issue_id = ?1 AND ended_at IS NULL",
+            params![issue_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?;
+
+        if let Some((log_id, started_at)) = running {
+            let minutes = at.signed_duration_since(started_at).num_minutes().max(0);
+            self.conn.execute(
+                "UPDATE work_logs SET minutes = ?1, ended_at = ?2 WHERE id = ?3",
+                params![minutes, at, log_id],
+            )?;
+        }
+        Ok(())
+    }
+
     fn add_event(&mut self, issue_id: &str, event_type: EventType, actor: &str, old_value: Option<&str>, new_value: Option<&str>, comment: Option<&str>) -> Result<()> {
         self.conn.execute(
             "INSERT INTO events (issue_id, event_type, actor, old_value, new_value, comment, created_at)
@@ -160,150 +396,190 @@ impl SqliteStorage {
 
 impl Storage for SqliteStorage {
     fn create_issue(&mut self, issue: &Issue, actor: &str) -> Result<()> {
-        issue.validate()?;
-
-        self.conn.execute(
-            "INSERT INTO issues (id, title, description, design, acceptance_criteria, notes, status, priority, issue_type, assignee, estimated_minutes, created_at, updated_at, external_ref)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
-            params![
-                issue.id,
-                issue.title,
-                issue.description,
-                issue.design,
-                issue.acceptance_criteria,
-                issue.notes,
-                issue.status.to_string(),
-                issue.priority,
-                issue.issue_type.to_string(),
-                if issue.assignee.is_empty() { None } else { Some(&issue.assignee) },
-                issue.estimated_minutes,
-                issue.created_at,
-                issue.updated_at,
-                issue.external_ref,
-            ],
-        )?;
-
-        self.add_event(&issue.id, EventType::Created, actor, None, None, None)?;
-        self.mark_dirty(&issue.id)?;
-        Ok(())
+        self.atomic(|this| {
+            issue.validate()?;
+
+            this.conn.execute(
+                "INSERT INTO issues (id, title, description, design, acceptance_criteria, notes, status, priority, issue_type, assignee, estimated_minutes, created_at, updated_at, closed_at, external_ref, due_at, milestone)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+                params![
+                    issue.id,
+                    issue.title,
+                    issue.description,
+                    issue.design,
+                    issue.acceptance_criteria,
+                    issue.notes,
+                    issue.status.to_string(),
+                    issue.priority,
+                    issue.issue_type.to_string(),
+                    if issue.assignee.is_empty() { None } else { Some(&issue.assignee) },
+                    issue.estimated_minutes,
+                    issue.created_at,
+                    issue.updated_at,
+                    issue.closed_at,
+                    issue.external_ref,
+                    issue.due_at,
+                    issue.milestone,
+                ],
+            ).map_err(|err| on_conflict(err, || format!("Issue {} already exists", issue.id)))?;
+
+            this.add_event(&issue.id, EventType::Created, actor, None, Some(&issue.status.to_string()), None)?;
+            this.mark_dirty(&issue.id)?;
+            Ok(())
+        })
     }
 
     fn get_issue(&self, id: &str) -> Result<Option<Issue>> {
         let issue = self.conn
             .query_row(
-                "SELECT id, title, description, design, acceptance_criteria, notes, status, priority, issue_type, assignee, estimated_minutes, created_at, updated_at, closed_at, external_ref
-                 FROM issues WHERE id = ?1",
+                &format!("SELECT {} FROM issues i WHERE i.id = ?1", ISSUE_COLUMNS),
                 params![id],
-                |row| {
-                    Ok(Issue {
-                        id: row.get(0)?,
-                        title: row.get(1)?,
-                        description: row.get(2)?,
-                        design: row.get(3)?,
-                        acceptance_criteria: row.get(4)?,
-                        notes: row.get(5)?,
-                        status: row.get::<_, String>(6)?.parse().expect("Invalid status in database"),
-                        priority: row.get(7)?,
-                        issue_type: row.get::<_, String>(8)?.parse().expect("Invalid issue type in database"),
-                        assignee: row.get::<_, Option<String>>(9)?.unwrap_or_default(),
-                        estimated_minutes: row.get(10)?,
-                        created_at: row.get(11)?,
-                        updated_at: row.get(12)?,
-                        closed_at: row.get(13)?,
-                        external_ref: row.get(14)?,
-                        dependencies: Vec::new(),
-                    })
-                },
+                issue_from_row,
             )
             .optional()?;
         Ok(issue)
     }
 
     fn update_issue(&mut self, id: &str, updates: &IssueUpdates, actor: &str) -> Result<()> {
-        let mut sql = String::from("UPDATE issues SET updated_at = ?1");
-        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(Utc::now())];
-        let mut param_idx = 2;
-
-        if let Some(title) = &updates.title {
-            sql.push_str(&format!(", title = ?{}", param_idx));
-            params.push(Box::new(title.clone()));
-            param_idx += 1;
-        }
-        if let Some(desc) = &updates.description {
-            sql.push_str(&format!(", description = ?{}", param_idx));
-            params.push(Box::new(desc.clone()));
-            param_idx += 1;
-        }
-        if let Some(design) = &updates.design {
-            sql.push_str(&format!(", design = ?{}", param_idx));
-            params.push(Box::new(design.clone()));
-            param_idx += 1;
-        }
-        if let Some(ac) = &updates.acceptance_criteria {
-            sql.push_str(&format!(", acceptance_criteria = ?{}", param_idx));
-            params.push(Box::new(ac.clone()));
-            param_idx += 1;
-        }
-        if let Some(notes) = &updates.notes {
-            sql.push_str(&format!(", notes = ?{}", param_idx));
-            params.push(Box::new(notes.clone()));
-            param_idx += 1;
-        }
-        if let Some(status) = updates.status {
-            sql.push_str(&format!(", status = ?{}", param_idx));
-            params.push(Box::new(status.to_string()));
-            param_idx += 1;
-            self.add_event(id, EventType::StatusChanged, actor, None, Some(&status.to_string()), None)?;
-        }
-        if let Some(priority) = updates.priority {
-            sql.push_str(&format!(", priority = ?{}", param_idx));
-            params.push(Box::new(priority));
-            param_idx += 1;
-        }
-        if let Some(issue_type) = updates.issue_type {
-            sql.push_str(&format!(", issue_type = ?{}", param_idx));
-            params.push(Box::new(issue_type.to_string()));
-            param_idx += 1;
-        }
-        if let Some(assignee) = &updates.assignee {
-            sql.push_str(&format!(", assignee = ?{}", param_idx));
-            params.push(Box::new(assignee.clone()));
-            param_idx += 1;
-        }
+        self.atomic(|this| {
+            let current = this.get_issue(id)?
+                .ok_or_else(|| Error::not_found("Issue", id))?
+                .status;
+
+            let mut sql = String::from("UPDATE issues SET updated_at = ?1");
+            let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(Utc::now())];
+            let mut param_idx = 2;
+
+            if let Some(title) = &updates.title {
+                sql.push_str(&format!(", title = ?{}", param_idx));
+                params.push(Box::new(title.clone()));
+                param_idx += 1;
+            }
+            if let Some(desc) = &updates.description {
+                sql.push_str(&format!(", description = ?{}", param_idx));
+                params.push(Box::new(desc.clone()));
+                param_idx += 1;
+            }
+            if let Some(design) = &updates.design {
+                sql.push_str(&format!(", design = ?{}", param_idx));
+                params.push(Box::new(design.clone()));
+                param_idx += 1;
+            }
+            if let Some(ac) = &updates.acceptance_criteria {
+                sql.push_str(&format!(", acceptance_criteria = ?{}", param_idx));
+                params.push(Box::new(ac.clone()));
+                param_idx += 1;
+            }
+            if let Some(notes) = &updates.notes {
+                sql.push_str(&format!(", notes = ?{}", param_idx));
+                params.push(Box::new(notes.clone()));
+                param_idx += 1;
+            }
+            if let Some(status) = &updates.status {
+                if !updates.bypass_workflow {
+                    this.get_workflow()?.check_transition(&current, status)?;
+                }
+                sql.push_str(&format!(", status = ?{}", param_idx));
+                params.push(Box::new(status.to_string()));
+                param_idx += 1;
+                this.add_event(id, EventType::StatusChanged, actor, Some(&current.to_string()), Some(&status.to_string()), None)?;
+
+                if current != *status {
+                    // Keep closed_at in step with the status, as close_issue does
+                    if *status == Status::Closed {
+                        sql.push_str(&format!(", closed_at = ?{}", param_idx));
+                        params.push(Box::new(Utc::now()));
+                        param_idx += 1;
+                    } else if current == Status::Closed {
+                        sql.push_str(", closed_at = NULL");
+                    }
+                    if current == Status::InProgress {
+                        this.stop_tracking(id, Utc::now())?;
+                    }
+                    if *status == Status::InProgress && !updates.bypass_workflow {
+                        this.start_tracking(id, actor, Utc::now())?;
+                    }
+                }
+            }
+            if let Some(priority) = updates.priority {
+                sql.push_str(&format!(", priority = ?{}", param_idx));
+                params.push(Box::new(priority));
+                param_idx += 1;
+            }
+            if let Some(issue_type) = updates.issue_type {
+                sql.push_str(&format!(", issue_type = ?{}", param_idx));
+                params.push(Box::new(issue_type.to_string()));
+                param_idx += 1;
+            }
+            if let Some(assignee) = &updates.assignee {
+                sql.push_str(&format!(", assignee = ?{}", param_idx));
+                params.push(Box::new(assignee.clone()));
+                param_idx += 1;
+            }
+            if let Some(estimated_minutes) = updates.estimated_minutes {
+                sql.push_str(&format!(", estimated_minutes = ?{}", param_idx));
+                params.push(Box::new(estimated_minutes));
+                param_idx += 1;
+            }
+            if let Some(external_ref) = &updates.external_ref {
+                sql.push_str(&format!(", external_ref = ?{}", param_idx));
+                params.push(Box::new(external_ref.clone()));
+                param_idx += 1;
+            }
+            if let Some(due_at) = updates.due_at {
+                sql.push_str(&format!(", due_at = ?{}", param_idx));
+                params.push(Box::new(due_at));
+                param_idx += 1;
+            }
+            if let Some(milestone) = &updates.milestone {
+                sql.push_str(&format!(", milestone = ?{}", param_idx));
+                params.push(Box::new(milestone.clone()));
+                param_idx += 1;
+            }
 
-        sql.push_str(&format!(" WHERE id = ?{}", param_idx));
-        params.push(Box::new(id.to_string()));
+            sql.push_str(&format!(" WHERE id = ?{}", param_idx));
+            params.push(Box::new(id.to_string()));
 
-        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|b| b.as_ref()).collect();
-        self.conn.execute(&sql, params_refs.as_slice())?;
+            let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|b| b.as_ref()).collect();
+            this.conn.execute(&sql, params_refs.as_slice())?;
 
-        self.add_event(id, EventType::Updated, actor, None, None, None)?;
-        self.mark_dirty(id)?;
-        Ok(())
+            this.add_event(id, EventType::Updated, actor, None, None, None)?;
+            this.mark_dirty(id)?;
+            Ok(())
+        })
     }
 
     fn close_issue(&mut self, id: &str, reason: &str, actor: &str) -> Result<()> {
-        let now = Utc::now();
-        self.conn.execute(
-            "UPDATE issues SET status = 'closed', closed_at = ?1, updated_at = ?2 WHERE id = ?3",
-            params![now, now, id],
-        )?;
-        self.add_event(id, EventType::Closed, actor, None, None, Some(reason))?;
-        self.mark_dirty(id)?;
-        Ok(())
+        self.atomic(|this| {
+            let current = this.get_issue(id)?
+                .ok_or_else(|| Error::not_found("Issue", id))?
+                .status;
+            this.get_workflow()?.check_transition(&current, &Status::Closed)?;
+
+            let now = Utc::now();
+            this.conn.execute(
+                "UPDATE issues SET status = 'closed', closed_at = ?1, updated_at = ?2 WHERE id = ?3",
+                params![now, now, id],
+            )?;
+            this.add_event(id, EventType::Closed, actor, None, None, Some(reason))?;
+            this.stop_tracking(id, now)?;
+            this.mark_dirty(id)?;
+            Ok(())
+        })
     }
 
     fn search_issues(&self, _query: &str, filter: &IssueFilter) -> Result<Vec<Issue>> {
-        let mut sql = String::from(
-            "SELECT DISTINCT i.id, i.title, i.description, i.design, i.acceptance_criteria, i.notes, i.status, i.priority, i.issue_type, i.assignee, i.estimated_minutes, i.created_at, i.updated_at, i.closed_at, i.external_ref
-             FROM issues i"
+        let mut sql = format!(
+            "SELECT DISTINCT {}
+             FROM issues i",
+            ISSUE_COLUMNS
         );
 
         let mut conditions = Vec::new();
         let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
         if filter.status.is_some() || filter.priority.is_some() || filter.issue_type.is_some() || filter.assignee.is_some() {
-            if let Some(status) = filter.status {
+            if let Some(status) = &filter.status {
                 conditions.push(format!("i.status = ?{}", params.len() + 1));
                 params.push(Box::new(status.to_string()));
             }
@@ -321,6 +597,33 @@ impl Storage for SqliteStorage {
             }
         }
 
+        let date_conditions = [
+            ("i.created_at >=", filter.created_after),
+            ("i.created_at <", filter.created_before),
+            ("i.updated_at >=", filter.updated_after),
+            ("i.updated_at <", filter.updated_before),
+            ("i.closed_at >=", filter.closed_after),
+            ("i.closed_at <", filter.closed_before),
+            ("i.due_at <", filter.due_before),
+        ];
+        for (condition, value) in date_conditions {
+            if let Some(value) = value {
+                conditions.push(format!("{} ?{}", condition, params.len() + 1));
+                params.push(Box::new(value));
+            }
+        }
+
+        if let Some(milestone) = &filter.milestone {
+            conditions.push(format!("i.milestone = ?{}", params.len() + 1));
+            params.push(Box::new(milestone.clone()));
+        }
+
+        if filter.overdue {
+            conditions.push(format!("i.due_at < ?{}", params.len() + 1));
+            params.push(Box::new(Utc::now()));
+            conditions.push(self.not_done_condition("i.status")?);
+        }
+
         if !filter.labels.is_empty() {
             sql.push_str(" LEFT JOIN labels l ON i.id = l.issue_id");
             let placeholders: Vec<String> = filter.labels.iter().enumerate()
@@ -337,127 +640,76 @@ impl Storage for SqliteStorage {
             sql.push_str(&conditions.join(" AND "));
         }
 
-        sql.push_str(" ORDER BY i.priority ASC, i.created_at DESC");
-
-        if let Some(limit) = filter.limit {
-            sql.push_str(&format!(" LIMIT {}", limit));
-        }
+        sql.push_str(&order_by_clause(&filter.sort));
+        sql.push_str(&limit_clause(filter.limit, filter.offset));
 
         let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|b| b.as_ref()).collect();
         let mut stmt = self.conn.prepare(&sql)?;
-        let issues = stmt.query_map(params_refs.as_slice(), |row| {
-            Ok(Issue {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                design: row.get(3)?,
-                acceptance_criteria: row.get(4)?,
-                notes: row.get(5)?,
-                status: row.get::<_, String>(6)?.parse().expect("Invalid enum value in database"),
-                priority: row.get(7)?,
-                issue_type: row.get::<_, String>(8)?.parse().expect("Invalid enum value in database"),
-                assignee: row.get::<_, Option<String>>(9)?.unwrap_or_default(),
-                estimated_minutes: row.get(10)?,
-                created_at: row.get(11)?,
-                updated_at: row.get(12)?,
-                closed_at: row.get(13)?,
-                external_ref: row.get(14)?,
-                dependencies: Vec::new(),
-            })
-        })?
+        let issues = stmt.query_map(params_refs.as_slice(), issue_from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
         Ok(issues)
     }
 
     fn add_dependency(&mut self, dep: &Dependency, actor: &str) -> Result<()> {
-        self.conn.execute(
-            "INSERT INTO dependencies (issue_id, depends_on_id, type, created_at, created_by)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![
-                dep.issue_id,
-                dep.depends_on_id,
-                dep.dep_type.to_string(),
-                dep.created_at,
-                actor,
-            ],
-        )?;
-        self.add_event(&dep.issue_id, EventType::DependencyAdded, actor, None, Some(&dep.depends_on_id), None)?;
-        self.mark_dirty(&dep.issue_id)?;
-        Ok(())
+        if dep.issue_id == dep.depends_on_id {
+            return Err(Error::Cycle(format!("{} cannot depend on itself", dep.issue_id)));
+        }
+        self.atomic(|this| {
+            this.conn.execute(
+                "INSERT INTO dependencies (issue_id, depends_on_id, type, created_at, created_by)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    dep.issue_id,
+                    dep.depends_on_id,
+                    dep.dep_type.to_string(),
+                    dep.created_at,
+                    actor,
+                ],
+            ).map_err(|err| on_conflict(err, || format!("dependency {} -> {} already exists", dep.issue_id, dep.depends_on_id)))?;
+            this.add_event(&dep.issue_id, EventType::DependencyAdded, actor, None, Some(&dep.depends_on_id), None)?;
+            this.mark_dirty(&dep.issue_id)?;
+            Ok(())
+        })
     }
 
     fn remove_dependency(&mut self, issue_id: &str, depends_on_id: &str, actor: &str) -> Result<()> {
-        self.conn.execute(
-            "DELETE FROM dependencies WHERE issue_id = ?1 AND depends_on_id = ?2",
-            params![issue_id, depends_on_id],
-        )?;
-        self.add_event(issue_id, EventType::DependencyRemoved, actor, None, Some(depends_on_id), None)?;
-        self.mark_dirty(issue_id)?;
-        Ok(())
+        self.atomic(|this| {
+            this.conn.execute(
+                "DELETE FROM dependencies WHERE issue_id = ?1 AND depends_on_id = ?2",
+                params![issue_id, depends_on_id],
+            )?;
+            this.add_event(issue_id, EventType::DependencyRemoved, actor, None, Some(depends_on_id), None)?;
+            this.mark_dirty(issue_id)?;
+            Ok(())
+        })
     }
 
     fn get_dependencies(&self, issue_id: &str) -> Result<Vec<Issue>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT i.id, i.title, i.description, i.design, i.acceptance_criteria, i.notes, i.status, i.priority, i.issue_type, i.assignee, i.estimated_minutes, i.created_at, i.updated_at, i.closed_at, i.external_ref
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}
              FROM issues i
              JOIN dependencies d ON i.id = d.depends_on_id
-             WHERE d.issue_id = ?1"
-        )?;
+             WHERE d.issue_id = ?1",
+            ISSUE_COLUMNS
+        ))?;
         
-        let issues = stmt.query_map(params![issue_id], |row| {
-            Ok(Issue {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                design: row.get(3)?,
-                acceptance_criteria: row.get(4)?,
-                notes: row.get(5)?,
-                status: row.get::<_, String>(6)?.parse().expect("Invalid enum value in database"),
-                priority: row.get(7)?,
-                issue_type: row.get::<_, String>(8)?.parse().expect("Invalid enum value in database"),
-                assignee: row.get::<_, Option<String>>(9)?.unwrap_or_default(),
-                estimated_minutes: row.get(10)?,
-                created_at: row.get(11)?,
-                updated_at: row.get(12)?,
-                closed_at: row.get(13)?,
-                external_ref: row.get(14)?,
-                dependencies: Vec::new(),
-            })
-        })?
+        let issues = stmt.query_map(params![issue_id], issue_from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
         Ok(issues)
     }
 
     fn get_dependents(&self, issue_id: &str) -> Result<Vec<Issue>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT i.id, i.title, i.description, i.design, i.acceptance_criteria, i.notes, i.status, i.priority, i.issue_type, i.assignee, i.estimated_minutes, i.created_at, i.updated_at, i.closed_at, i.external_ref
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}
              FROM issues i
              JOIN dependencies d ON i.id = d.issue_id
-             WHERE d.depends_on_id = ?1"
-        )?;
+             WHERE d.depends_on_id = ?1",
+            ISSUE_COLUMNS
+        ))?;
         
-        let issues = stmt.query_map(params![issue_id], |row| {
-            Ok(Issue {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                design: row.get(3)?,
-                acceptance_criteria: row.get(4)?,
-                notes: row.get(5)?,
-                status: row.get::<_, String>(6)?.parse().expect("Invalid enum value in database"),
-                priority: row.get(7)?,
-                issue_type: row.get::<_, String>(8)?.parse().expect("Invalid enum value in database"),
-                assignee: row.get::<_, Option<String>>(9)?.unwrap_or_default(),
-                estimated_minutes: row.get(10)?,
-                created_at: row.get(11)?,
-                updated_at: row.get(12)?,
-                closed_at: row.get(13)?,
-                external_ref: row.get(14)?,
-                dependencies: Vec::new(),
-            })
-        })?
+        let issues = stmt.query_map(params![issue_id], issue_from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
         Ok(issues)
@@ -473,7 +725,7 @@ impl Storage for SqliteStorage {
             Ok(Dependency {
                 issue_id: row.get(0)?,
                 depends_on_id: row.get(1)?,
-                dep_type: row.get::<_, String>(2)?.parse().expect("Invalid enum value in database"),
+                dep_type: parse_column(row, 2)?,
                 created_at: row.get(3)?,
                 created_by: row.get(4)?,
             })
@@ -492,7 +744,7 @@ impl Storage for SqliteStorage {
             Ok(Dependency {
                 issue_id: row.get(0)?,
                 depends_on_id: row.get(1)?,
-                dep_type: row.get::<_, String>(2)?.parse().expect("Invalid enum value in database"),
+                dep_type: parse_column(row, 2)?,
                 created_at: row.get(3)?,
                 created_by: row.get(4)?,
             })
@@ -532,23 +784,27 @@ impl Storage for SqliteStorage {
     }
 
     fn add_label(&mut self, issue_id: &str, label: &str, actor: &str) -> Result<()> {
-        self.conn.execute(
-            "INSERT OR IGNORE INTO labels (issue_id, label) VALUES (?1, ?2)",
-            params![issue_id, label],
-        )?;
-        self.add_event(issue_id, EventType::LabelAdded, actor, None, Some(label), None)?;
-        self.mark_dirty(issue_id)?;
-        Ok(())
+        self.atomic(|this| {
+            this.conn.execute(
+                "INSERT OR IGNORE INTO labels (issue_id, label) VALUES (?1, ?2)",
+                params![issue_id, label],
+            )?;
+            this.add_event(issue_id, EventType::LabelAdded, actor, None, Some(label), None)?;
+            this.mark_dirty(issue_id)?;
+            Ok(())
+        })
     }
 
     fn remove_label(&mut self, issue_id: &str, label: &str, actor: &str) -> Result<()> {
-        self.conn.execute(
-            "DELETE FROM labels WHERE issue_id = ?1 AND label = ?2",
-            params![issue_id, label],
-        )?;
-        self.add_event(issue_id, EventType::LabelRemoved, actor, None, Some(label), None)?;
-        self.mark_dirty(issue_id)?;
-        Ok(())
+        self.atomic(|this| {
+            this.conn.execute(
+                "DELETE FROM labels WHERE issue_id = ?1 AND label = ?2",
+                params![issue_id, label],
+            )?;
+            this.add_event(issue_id, EventType::LabelRemoved, actor, None, Some(label), None)?;
+            this.mark_dirty(issue_id)?;
+            Ok(())
+        })
     }
 
     fn get_labels(&self, issue_id: &str) -> Result<Vec<String>> {
@@ -559,49 +815,33 @@ impl Storage for SqliteStorage {
     }
 
     fn get_issues_by_label(&self, label: &str) -> Result<Vec<Issue>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT i.id, i.title, i.description, i.design, i.acceptance_criteria, i.notes, i.status, i.priority, i.issue_type, i.assignee, i.estimated_minutes, i.created_at, i.updated_at, i.closed_at, i.external_ref
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}
              FROM issues i
              JOIN labels l ON i.id = l.issue_id
-             WHERE l.label = ?1"
-        )?;
+             WHERE l.label = ?1",
+            ISSUE_COLUMNS
+        ))?;
         
-        let issues = stmt.query_map(params![label], |row| {
-            Ok(Issue {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                design: row.get(3)?,
-                acceptance_criteria: row.get(4)?,
-                notes: row.get(5)?,
-                status: row.get::<_, String>(6)?.parse().expect("Invalid enum value in database"),
-                priority: row.get(7)?,
-                issue_type: row.get::<_, String>(8)?.parse().expect("Invalid enum value in database"),
-                assignee: row.get::<_, Option<String>>(9)?.unwrap_or_default(),
-                estimated_minutes: row.get(10)?,
-                created_at: row.get(11)?,
-                updated_at: row.get(12)?,
-                closed_at: row.get(13)?,
-                external_ref: row.get(14)?,
-                dependencies: Vec::new(),
-            })
-        })?
+        let issues = stmt.query_map(params![label], issue_from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
         Ok(issues)
     }
 
     fn get_ready_work(&self, filter: &WorkFilter) -> Result<Vec<Issue>> {
-        let mut sql = String::from(
-            "SELECT DISTINCT i.id, i.title, i.description, i.design, i.acceptance_criteria, i.notes, i.status, i.priority, i.issue_type, i.assignee, i.estimated_minutes, i.created_at, i.updated_at, i.closed_at, i.external_ref
+        let mut sql = format!(
+            "SELECT DISTINCT {}
              FROM issues i
              WHERE i.status = 'open'
              AND i.id NOT IN (
                  SELECT d.issue_id
                  FROM dependencies d
                  JOIN issues blocker ON d.depends_on_id = blocker.id
-                 WHERE d.type = 'blocks' AND blocker.status != 'closed'
-             )"
+                 WHERE d.type = 'blocks' AND {}
+             )",
+            ISSUE_COLUMNS,
+            self.not_done_condition("blocker.status")?
         );
 
         let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
@@ -614,71 +854,58 @@ impl Storage for SqliteStorage {
             sql.push_str(&format!(" AND i.assignee = ?{}", params.len() + 1));
             params.push(Box::new(assignee.clone()));
         }
-
-        sql.push_str(" ORDER BY i.priority ASC, i.created_at DESC");
-
-        if let Some(limit) = filter.limit {
-            sql.push_str(&format!(" LIMIT {}", limit));
+        if let Some(milestone) = &filter.milestone {
+            sql.push_str(&format!(" AND i.milestone = ?{}", params.len() + 1));
+            params.push(Box::new(milestone.clone()));
         }
 
+        sql.push_str(&order_by_clause(&filter.sort));
+        sql.push_str(&limit_clause(filter.limit, filter.offset));
+
         let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|b| b.as_ref()).collect();
         let mut stmt = self.conn.prepare(&sql)?;
-        let issues = stmt.query_map(params_refs.as_slice(), |row| {
-            Ok(Issue {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                design: row.get(3)?,
-                acceptance_criteria: row.get(4)?,
-                notes: row.get(5)?,
-                status: row.get::<_, String>(6)?.parse().expect("Invalid enum value in database"),
-                priority: row.get(7)?,
-                issue_type: row.get::<_, String>(8)?.parse().expect("Invalid enum value in database"),
-                assignee: row.get::<_, Option<String>>(9)?.unwrap_or_default(),
-                estimated_minutes: row.get(10)?,
-                created_at: row.get(11)?,
-                updated_at: row.get(12)?,
-                closed_at: row.get(13)?,
-                external_ref: row.get(14)?,
-                dependencies: Vec::new(),
-            })
-        })?
+        let issues = stmt.query_map(params_refs.as_slice(), issue_from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
         Ok(issues)
     }
 
-    fn get_blocked_issues(&self) -> Result<Vec<BlockedIssue>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT i.id, i.title, i.description, i.design, i.acceptance_criteria, i.notes, i.status, i.priority, i.issue_type, i.assignee, i.estimated_minutes, i.created_at, i.updated_at, i.closed_at, i.external_ref,
+    fn get_blocked_issues(&self, filter: &WorkFilter) -> Result<Vec<BlockedIssue>> {
+        let mut sql = format!(
+            "SELECT {},
                     COUNT(d.depends_on_id) as blocked_by_count
              FROM issues i
              JOIN dependencies d ON i.id = d.issue_id
              JOIN issues blocker ON d.depends_on_id = blocker.id
-             WHERE d.type = 'blocks' AND blocker.status != 'closed'
-             GROUP BY i.id"
-        )?;
-        
-        let blocked = stmt.query_map([], |row| {
-            let issue = Issue {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                design: row.get(3)?,
-                acceptance_criteria: row.get(4)?,
-                notes: row.get(5)?,
-                status: row.get::<_, String>(6)?.parse().expect("Invalid enum value in database"),
-                priority: row.get(7)?,
-                issue_type: row.get::<_, String>(8)?.parse().expect("Invalid enum value in database"),
-                assignee: row.get::<_, Option<String>>(9)?.unwrap_or_default(),
-                estimated_minutes: row.get(10)?,
-                created_at: row.get(11)?,
-                updated_at: row.get(12)?,
-                closed_at: row.get(13)?,
-                external_ref: row.get(14)?,
-                dependencies: Vec::new(),
-            };
-            let count: i32 = row.get(15)?;
+             WHERE d.type = 'blocks' AND {}",
+            ISSUE_COLUMNS,
+            self.not_done_condition("blocker.status")?
+        );
+
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(priority) = filter.priority {
+            sql.push_str(&format!(" AND i.priority = ?{}", params.len() + 1));
+            params.push(Box::new(priority));
+        }
+        if let Some(assignee) = &filter.assignee {
+            sql.push_str(&format!(" AND i.assignee = ?{}", params.len() + 1));
+            params.push(Box::new(assignee.clone()));
+        }
+        if let Some(milestone) = &filter.milestone {
+            sql.push_str(&format!(" AND i.milestone = ?{}", params.len() + 1));
+            params.push(Box::new(milestone.clone()));
+        }
+
+        sql.push_str(" GROUP BY i.id");
+        sql.push_str(&order_by_clause(&filter.sort));
+        sql.push_str(&limit_clause(filter.limit, filter.offset));
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|b| b.as_ref()).collect();
+        let mut stmt = self.conn.prepare(&sql)?;
+        let blocked = stmt.query_map(params_refs.as_slice(), |row| {
+            let issue = issue_from_row(row)?;
+            let count: i32 = row.get(ISSUE_COLUMN_COUNT)?;
             
             Ok(BlockedIssue {
                 issue,
@@ -689,14 +916,16 @@ impl Storage for SqliteStorage {
         .collect::<Result<Vec<_>, _>>()?;
 
         // Get blockers for each blocked issue
+        let blocker_sql = format!(
+            "SELECT d.depends_on_id
+             FROM dependencies d
+             JOIN issues blocker ON d.depends_on_id = blocker.id
+             WHERE d.issue_id = ?1 AND d.type = 'blocks' AND {}",
+            self.not_done_condition("blocker.status")?
+        );
         let mut result = Vec::new();
         for mut bi in blocked {
-            let blockers: Vec<String> = self.conn.prepare(
-                "SELECT d.depends_on_id
-                 FROM dependencies d
-                 JOIN issues blocker ON d.depends_on_id = blocker.id
-                 WHERE d.issue_id = ?1 AND d.type = 'blocks' AND blocker.status != 'closed'"
-            )?
+            let blockers: Vec<String> = self.conn.prepare(&blocker_sql)?
             .query_map(params![&bi.issue.id], |row| row.get(0))?
             .collect::<Result<Vec<_>, _>>()?;
             
@@ -707,11 +936,244 @@ impl Storage for SqliteStorage {
         Ok(result)
     }
 
+    fn get_stale_issues(&self, inactive_since: DateTime<Utc>, status: Option<Status>) -> Result<Vec<StaleIssue>> {
+        let mut sql = format!(
+            "SELECT {}, last_activity
+             FROM (
+                 SELECT i.*, MAX(i.updated_at, COALESCE(
+                     (SELECT MAX(e.created_at) FROM events e WHERE e.issue_id = i.id),
+                     i.updated_at
+                 )) AS last_activity
+                 FROM issues i
+             ) i
+             WHERE {} AND last_activity < ?1",
+            ISSUE_COLUMNS,
+            self.not_done_condition("i.status")?
+        );
+
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(inactive_since)];
+        if let Some(status) = status {
+            sql.push_str(&format!(" AND i.status = ?{}", params.len() + 1));
+            params.push(Box::new(status.to_string()));
+        }
+        sql.push_str(" ORDER BY i.assignee, last_activity ASC, i.id");
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|b| b.as_ref()).collect();
+        let mut stmt = self.conn.prepare(&sql)?;
+        let stale = stmt.query_map(params_refs.as_slice(), |row| {
+            Ok(StaleIssue {
+                issue: issue_from_row(row)?,
+                last_activity: row.get(ISSUE_COLUMN_COUNT)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(stale)
+    }
+
+    fn save_milestone(&mut self, milestone: &Milestone) -> Result<()> {
+        milestone.validate()?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO milestones (name, description, target_date, status, created_at, closed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                milestone.name,
+                milestone.description,
+                milestone.target_date,
+                milestone.status.to_string(),
+                milestone.created_at,
+                milestone.closed_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn get_milestone(&self, name: &str) -> Result<Option<Milestone>> {
+        let milestone = self.conn
+            .query_row(
+                "SELECT name, description, target_date, status, created_at, closed_at FROM milestones WHERE name = ?1",
+                params![name],
+                milestone_from_row,
+            )
+            .optional()?;
+        Ok(milestone)
+    }
+
+    fn list_milestones(&self) -> Result<Vec<Milestone>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, description, target_date, status, created_at, closed_at
+             FROM milestones
+             ORDER BY target_date IS NULL, target_date, name"
+        )?;
+        let milestones = stmt.query_map([], milestone_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(milestones)
+    }
+
+    fn save_recurrence(&mut self, recurrence: &Recurrence) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO recurrences (name, schedule, title, description, issue_type, priority, labels, assignee, start_at, last_period, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                recurrence.name,
+                recurrence.schedule.to_string(),
+                recurrence.title,
+                recurrence.description,
+                recurrence.issue_type.to_string(),
+                recurrence.priority,
+                serde_json::to_string(&recurrence.labels)?,
+                recurrence.assignee,
+                recurrence.start_at,
+                recurrence.last_period,
+                recurrence.created_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn get_recurrence(&self, name: &str) -> Result<Option<Recurrence>> {
+        let recurrence = self.conn
+            .query_row(
+                &format!("SELECT {} FROM recurrences WHERE name = ?1", RECURRENCE_COLUMNS),
+                params![name],
+                recurrence_from_row,
+            )
+            .optional()?;
+        Ok(recurrence)
+    }
+
+    fn list_recurrences(&self) -> Result<Vec<Recurrence>> {
+        let mut stmt = self.conn.prepare(&format!("SELECT {} FROM recurrences ORDER BY name", RECURRENCE_COLUMNS))?;
+        let recurrences = stmt.query_map([], recurrence_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(recurrences)
+    }
+
+    fn delete_recurrence(&mut self, name: &str) -> Result<()> {
+        let deleted = self.conn.execute("DELETE FROM recurrences WHERE name = ?1", params![name])?;
+        if deleted == 0 {
+            return Err(Error::not_found("Recurrence", name));
+        }
+        Ok(())
+    }
+
+    fn claim_recurrence_period(&mut self, name: &str, period: DateTime<Utc>) -> Result<bool> {
+        let claimed = self.conn.execute(
+            "UPDATE recurrences SET last_period = ?1
+             WHERE name = ?2 AND (last_period IS NULL OR last_period < ?1)",
+            params![period, name],
+        )?;
+        Ok(claimed == 1)
+    }
+
+    fn get_issue_by_external_ref(&self, external_ref: &str) -> Result<Option<Issue>> {
+        let issue = self.conn
+            .query_row(
+                &format!("SELECT {} FROM issues i WHERE i.external_ref = ?1 LIMIT 1", ISSUE_COLUMNS),
+                params![external_ref],
+                issue_from_row,
+            )
+            .optional()?;
+        Ok(issue)
+    }
+
+    fn get_milestone_report(&self, name: &str) -> Result<Option<MilestoneReport>> {
+        let Some(milestone) = self.get_milestone(name)? else {
+            return Ok(None);
+        };
+
+        let not_done = self.not_done_condition("status")?;
+        let (total, open, remaining, unestimated): (i32, i32, i64, i32) = self.conn.query_row(
+            &format!(
+                "SELECT COUNT(*),
+                        COALESCE(SUM(CASE WHEN {0} THEN 1 ELSE 0 END), 0),
+                        COALESCE(SUM(CASE WHEN {0} THEN estimated_minutes ELSE 0 END), 0),
+                        COALESCE(SUM(CASE WHEN {0} AND estimated_minutes IS NULL THEN 1 ELSE 0 END), 0)
+                 FROM issues WHERE milestone = ?1",
+                not_done
+            ),
+            params![name],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )?;
+
+        Ok(Some(MilestoneReport {
+            milestone,
+            total_issues: total,
+            open_issues: open,
+            closed_issues: total - open,
+            remaining_estimated_minutes: remaining,
+            unestimated_open_issues: unestimated,
+        }))
+    }
+
+    fn add_work_log(&mut self, log: &WorkLog) -> Result<()> {
+        self.atomic(|this| {
+            if log.minutes < 0 {
+                return Err(Error::Validation("work log minutes cannot be negative".to_string()));
+            }
+            this.conn.execute(
+                "INSERT INTO work_logs (issue_id, actor, minutes, note, started_at, ended_at, source)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    log.issue_id,
+                    log.actor,
+                    log.minutes,
+                    log.note,
+                    log.started_at,
+                    log.ended_at,
+                    log.source.to_string(),
+                ],
+            )?;
+            this.mark_dirty(&log.issue_id)?;
+            Ok(())
+        })
+    }
+
+    fn get_work_logs(&self, issue_id: &str) -> Result<Vec<WorkLog>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT issue_id, actor, minutes, note, started_at, ended_at, source
+             FROM work_logs
+             WHERE issue_id = ?1
+             ORDER BY started_at ASC, id ASC"
+        )?;
+
+        let logs = stmt.query_map(params![issue_id], |row| {
+            Ok(WorkLog {
+                issue_id: row.get(0)?,
+                actor: row.get(1)?,
+                minutes: row.get(2)?,
+                note: row.get(3)?,
+                started_at: row.get(4)?,
+                ended_at: row.get(5)?,
+                source: parse_column(row, 6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(logs)
+    }
+
     fn add_comment(&mut self, issue_id: &str, actor: &str, comment: &str) -> Result<()> {
+        let exists: bool = self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM issues WHERE id = ?1)",
+            params![issue_id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Err(Error::not_found("Issue", issue_id));
+        }
         self.add_event(issue_id, EventType::Commented, actor, None, None, Some(comment))?;
         Ok(())
     }
 
+    fn move_comments(&mut self, from_issue_id: &str, to_issue_id: &str) -> Result<usize> {
+        let moved = self.conn.execute(
+            "UPDATE events SET issue_id = ?1 WHERE issue_id = ?2 AND event_type = 'commented'",
+            params![to_issue_id, from_issue_id],
+        )?;
+        Ok(moved)
+    }
+
     fn get_events(&self, issue_id: &str, limit: usize) -> Result<Vec<Event>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, issue_id, event_type, actor, old_value, new_value, comment, created_at
@@ -721,45 +1183,101 @@ impl Storage for SqliteStorage {
              LIMIT ?2"
         )?;
         
-        let events = stmt.query_map(params![issue_id, limit], |row| {
-            Ok(Event {
-                id: row.get(0)?,
-                issue_id: row.get(1)?,
-                event_type: row.get::<_, String>(2)?.parse().map_err(|_| rusqlite::Error::InvalidQuery)?,
-                actor: row.get(3)?,
-                old_value: row.get(4)?,
-                new_value: row.get(5)?,
-                comment: row.get(6)?,
-                created_at: row.get(7)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+        let events = stmt.query_map(params![issue_id, limit], event_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(events)
     }
 
+    fn get_all_events(&self, filter: &EventFilter) -> Result<Vec<Event>> {
+        let mut sql = String::from(
+            "SELECT id, issue_id, event_type, actor, old_value, new_value, comment, created_at
+             FROM events"
+        );
+
+        let mut conditions = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(since) = filter.since {
+            conditions.push(format!("created_at >= ?{}", params.len() + 1));
+            params.push(Box::new(since));
+        }
+        if let Some(until) = filter.until {
+            conditions.push(format!("created_at < ?{}", params.len() + 1));
+            params.push(Box::new(until));
+        }
+        if let Some(actor) = &filter.actor {
+            conditions.push(format!("actor = ?{}", params.len() + 1));
+            params.push(Box::new(actor.clone()));
+        }
+        if !filter.event_types.is_empty() {
+            let placeholders: Vec<String> = filter.event_types.iter().enumerate()
+                .map(|(idx, _)| format!("?{}", params.len() + idx + 1))
+                .collect();
+            conditions.push(format!("event_type IN ({})", placeholders.join(", ")));
+            for event_type in &filter.event_types {
+                params.push(Box::new(event_type.to_string()));
+            }
+        }
+
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY created_at ASC, id ASC");
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|b| b.as_ref()).collect();
+        let mut stmt = self.conn.prepare(&sql)?;
+        let events = stmt.query_map(params_refs.as_slice(), event_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(events)
+    }
+
+    fn compact_events(&mut self, before: DateTime<Utc>) -> Result<usize> {
+        // Same rule as compact_routine_events: drop an `updated` event whose next
+        // event on the same issue is also `updated`
+        let removed = self.conn.execute(
+            "DELETE FROM events WHERE id IN (
+                SELECT e.id FROM events e
+                JOIN events n ON n.id = (SELECT MIN(x.id) FROM events x WHERE x.issue_id = e.issue_id AND x.id > e.id)
+                WHERE e.event_type = 'updated' AND n.event_type = 'updated'
+                  AND e.created_at < ?1 AND n.created_at < ?1
+            )",
+            params![before],
+        )?;
+        Ok(removed)
+    }
+
     fn get_statistics(&self) -> Result<Statistics> {
         let total: i32 = self.conn.query_row("SELECT COUNT(*) FROM issues", [], |row| row.get(0))?;
         let open: i32 = self.conn.query_row("SELECT COUNT(*) FROM issues WHERE status = 'open'", [], |row| row.get(0))?;
         let in_progress: i32 = self.conn.query_row("SELECT COUNT(*) FROM issues WHERE status = 'in_progress'", [], |row| row.get(0))?;
         let closed: i32 = self.conn.query_row("SELECT COUNT(*) FROM issues WHERE status = 'closed'", [], |row| row.get(0))?;
+        let not_done = self.not_done_condition("blocker.status")?;
         let blocked: i32 = self.conn.query_row(
-            "SELECT COUNT(DISTINCT i.id) FROM issues i
-             JOIN dependencies d ON i.id = d.issue_id
-             JOIN issues blocker ON d.depends_on_id = blocker.id
-             WHERE d.type = 'blocks' AND blocker.status != 'closed'",
+            &format!(
+                "SELECT COUNT(DISTINCT i.id) FROM issues i
+                 JOIN dependencies d ON i.id = d.issue_id
+                 JOIN issues blocker ON d.depends_on_id = blocker.id
+                 WHERE d.type = 'blocks' AND {}",
+                not_done
+            ),
             [],
             |row| row.get(0)
         )?;
         
         let ready: i32 = self.conn.query_row(
-            "SELECT COUNT(*) FROM issues i
-             WHERE i.status = 'open'
-             AND i.id NOT IN (
-                 SELECT d.issue_id FROM dependencies d
-                 JOIN issues blocker ON d.depends_on_id = blocker.id
-                 WHERE d.type = 'blocks' AND blocker.status != 'closed'
-             )",
+            &format!(
+                "SELECT COUNT(*) FROM issues i
+                 WHERE i.status = 'open'
+                 AND i.id NOT IN (
+                     SELECT d.issue_id FROM dependencies d
+                     JOIN issues blocker ON d.depends_on_id = blocker.id
+                     WHERE d.type = 'blocks' AND {}
+                 )",
+                not_done
+            ),
             [],
             |row| row.get(0)
         )?;
@@ -771,6 +1289,53 @@ impl Storage for SqliteStorage {
             |row| row.get(0)
         ).unwrap_or(0.0);
 
+        let now = Utc::now();
+        let not_done = self.not_done_condition("status")?;
+        let overdue: i32 = self.conn.query_row(
+            &format!("SELECT COUNT(*) FROM issues WHERE due_at < ?1 AND {}", not_done),
+            params![now],
+            |row| row.get(0)
+        )?;
+        let due_soon: i32 = self.conn.query_row(
+            &format!("SELECT COUNT(*) FROM issues WHERE due_at >= ?1 AND due_at < ?2 AND {}", not_done),
+            params![now, now + chrono::Duration::days(7)],
+            |row| row.get(0)
+        )?;
+        let next_due_at: Option<DateTime<Utc>> = self.conn.query_row(
+            &format!("SELECT MIN(due_at) FROM issues WHERE due_at >= ?1 AND {}", not_done),
+            params![now],
+            |row| row.get(0)
+        )?;
+
+        // Estimation accuracy: closed issues with both an estimate and logged time
+        let mut stmt = self.conn.prepare(
+            "SELECT i.issue_type, COALESCE(i.assignee, ''), i.estimated_minutes, SUM(w.minutes)
+             FROM issues i
+             JOIN work_logs w ON w.issue_id = i.id
+             WHERE i.status = 'closed' AND i.estimated_minutes IS NOT NULL AND w.ended_at IS NOT NULL
+             GROUP BY i.id
+             HAVING SUM(w.minutes) > 0"
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?, row.get::<_, i64>(3)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        let mut estimation_by_type: BTreeMap<String, EstimationAccuracy> = BTreeMap::new();
+        let mut estimation_by_actor: BTreeMap<String, EstimationAccuracy> = BTreeMap::new();
+        for (issue_type, assignee, estimated, actual) in rows {
+            for entry in [estimation_by_type.entry(issue_type).or_default(), estimation_by_actor.entry(assignee).or_default()] {
+                entry.issues += 1;
+                entry.estimated_minutes += estimated;
+                entry.actual_minutes += actual;
+            }
+        }
+        for entry in estimation_by_type.values_mut().chain(estimation_by_actor.values_mut()) {
+            if entry.estimated_minutes > 0 {
+                entry.ratio = entry.actual_minutes as f64 / entry.estimated_minutes as f64;
+            }
+        }
+
         Ok(Statistics {
             total_issues: total,
             open_issues: open,
@@ -779,6 +1344,11 @@ impl Storage for SqliteStorage {
             blocked_issues: blocked,
             ready_issues: ready,
             average_lead_time_hours: avg_lead_time,
+            overdue_issues: overdue,
+            due_soon_issues: due_soon,
+            next_due_at,
+            estimation_by_type,
+            estimation_by_actor,
         })
     }
 
@@ -805,6 +1375,51 @@ impl Storage for SqliteStorage {
         Ok(())
     }
 
+    fn archive_issues(&mut self, closed_before: DateTime<Utc>) -> Result<Vec<String>> {
+        self.atomic(|this| {
+            let mut stmt = this.conn.prepare(
+                "SELECT id FROM issues WHERE status = 'closed' AND closed_at < ?1 ORDER BY rowid"
+            )?;
+            let candidates = stmt.query_map(params![closed_before], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+            drop(stmt);
+            let ids = super::archivable(candidates, &this.get_all_dependency_records()?);
+
+            let archived_at = Utc::now();
+            for id in &ids {
+                let archived = this.build_archived_issue(id, archived_at)?;
+                this.insert_archived_issue(&archived)?;
+                // Cascades to the issue's dependencies, labels, events and work logs
+                this.conn.execute("DELETE FROM issues WHERE id = ?1", params![id])?;
+            }
+            Ok(ids)
+        })
+    }
+
+    fn save_archived_issue(&mut self, archived: &ArchivedIssue) -> Result<()> {
+        self.atomic(|this| {
+            this.conn.execute("DELETE FROM issues WHERE id = ?1", params![archived.issue.id])?;
+            this.insert_archived_issue(archived)
+        })
+    }
+
+    fn get_archived_issue(&self, id: &str) -> Result<Option<ArchivedIssue>> {
+        let data: Option<String> = self.conn
+            .query_row("SELECT data FROM archived_issues WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()?;
+        Ok(data.map(|data| serde_json::from_str(&data)).transpose()?)
+    }
+
+    fn list_archived_issues(&self) -> Result<Vec<ArchivedIssue>> {
+        let mut stmt = self.conn.prepare("SELECT data FROM archived_issues ORDER BY id")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut archived = Vec::new();
+        for data in rows {
+            archived.push(serde_json::from_str(&data?)?);
+        }
+        Ok(archived)
+    }
+
     fn set_config(&mut self, key: &str, value: &str) -> Result<()> {
         self.conn.execute(
             "INSERT OR REPLACE INTO config (key, value) VALUES (?1, ?2)",
@@ -820,6 +1435,18 @@ impl Storage for SqliteStorage {
         Ok(value)
     }
 
+    fn get_workflow(&self) -> Result<Workflow> {
+        match self.get_config("workflow")? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(Workflow::default()),
+        }
+    }
+
+    fn set_workflow(&mut self, workflow: &Workflow) -> Result<()> {
+        workflow.validate()?;
+        self.set_config("workflow", &serde_json::to_string(workflow)?)
+    }
+
     fn set_metadata(&mut self, key: &str, value: &str) -> Result<()> {
         self.conn.execute(
             "INSERT OR REPLACE INTO metadata (key, value, updated_at) VALUES (?1, ?2, ?3)",
@@ -838,6 +1465,40 @@ impl Storage for SqliteStorage {
     fn generate_id(&mut self, prefix: &str) -> Result<String> {
         self.get_next_id(prefix)
     }
+
+    // The outermost transaction takes the write lock up front (BEGIN IMMEDIATE):
+    // a deferred transaction that reads first can't wait for the lock when it
+    // later writes, and fails if another process wrote in between. Inner
+    // transactions are savepoints, so they can nest.
+    fn begin_transaction(&mut self) -> Result<()> {
+        if self.depth == 0 {
+            self.retry_while_locked("BEGIN IMMEDIATE")?;
+        } else {
+            self.conn.execute_batch("SAVEPOINT tracer_tx")?;
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        if self.depth <= 1 {
+            self.retry_while_locked("COMMIT")?;
+        } else {
+            self.conn.execute_batch("RELEASE tracer_tx")?;
+        }
+        self.depth = self.depth.saturating_sub(1);
+        Ok(())
+    }
+
+    fn rollback_transaction(&mut self) -> Result<()> {
+        let result = if self.depth <= 1 {
+            self.conn.execute_batch("ROLLBACK")
+        } else {
+            self.conn.execute_batch("ROLLBACK TO tracer_tx; RELEASE tracer_tx")
+        };
+        self.depth = self.depth.saturating_sub(1);
+        Ok(result?)
+    }
 }
 
 impl SqliteStorage {
@@ -900,6 +1561,155 @@ impl SqliteStorage {
     }
 }
 
+/// Column list matching `issue_from_row`, for queries that alias issues as `i`
+const ISSUE_COLUMNS: &str = "i.id, i.title, i.description, i.design, i.acceptance_criteria, i.notes, i.status, i.priority, i.issue_type, i.assignee, i.estimated_minutes, i.created_at, i.updated_at, i.closed_at, i.external_ref, i.due_at, i.milestone";
+
+/// Number of columns in `ISSUE_COLUMNS`, i.e. the index of the first extra column in a query
+const ISSUE_COLUMN_COUNT: usize = 17;
+
+/// Convert a database error, replacing SQLite's message for a primary-key
+/// clash with one that names the record
+fn on_conflict(err: rusqlite::Error, message: impl FnOnce() -> String) -> Error {
+    match Error::from(err) {
+        Error::Conflict(_) => Error::Conflict(message()),
+        other => other,
+    }
+}
+
+/// Read a text column and parse it (status, type, schedule, ...). A value this
+/// version doesn't understand is reported as a conversion error rather than a panic
+fn parse_column<T>(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let value: String = row.get(idx)?;
+    value.parse().map_err(|err: T::Err| {
+        rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, err.to_string().into())
+    })
+}
+
+/// Map a row selected with `ISSUE_COLUMNS`
+fn issue_from_row(row: &rusqlite::Row) -> rusqlite::Result<Issue> {
+    Ok(Issue {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        description: row.get(2)?,
+        design: row.get(3)?,
+        acceptance_criteria: row.get(4)?,
+        notes: row.get(5)?,
+        status: parse_column(row, 6)?,
+        priority: row.get(7)?,
+        issue_type: parse_column(row, 8)?,
+        assignee: row.get::<_, Option<String>>(9)?.unwrap_or_default(),
+        estimated_minutes: row.get(10)?,
+        created_at: row.get(11)?,
+        updated_at: row.get(12)?,
+        closed_at: row.get(13)?,
+        external_ref: row.get(14)?,
+        due_at: row.get(15)?,
+        milestone: row.get(16)?,
+        dependencies: Vec::new(),
+        work_logs: Vec::new(),
+    })
+}
+
+fn event_from_row(row: &rusqlite::Row) -> rusqlite::Result<Event> {
+    Ok(Event {
+        id: row.get(0)?,
+        issue_id: row.get(1)?,
+        event_type: parse_column(row, 2)?,
+        actor: row.get(3)?,
+        old_value: row.get(4)?,
+        new_value: row.get(5)?,
+        comment: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+const RECURRENCE_COLUMNS: &str = "name, schedule, title, description, issue_type, priority, labels, assignee, start_at, last_period, created_at";
+
+fn recurrence_from_row(row: &rusqlite::Row) -> rusqlite::Result<Recurrence> {
+    Ok(Recurrence {
+        name: row.get(0)?,
+        schedule: parse_column(row, 1)?,
+        title: row.get(2)?,
+        description: row.get(3)?,
+        issue_type: parse_column(row, 4)?,
+        priority: row.get(5)?,
+        labels: serde_json::from_str(&row.get::<_, String>(6)?).map_err(|err| {
+            rusqlite::Error::FromSqlConversionFailure(6, rusqlite::types::Type::Text, err.into())
+        })?,
+        assignee: row.get(7)?,
+        start_at: row.get(8)?,
+        last_period: row.get(9)?,
+        created_at: row.get(10)?,
+    })
+}
+
+fn milestone_from_row(row: &rusqlite::Row) -> rusqlite::Result<Milestone> {
+    Ok(Milestone {
+        name: row.get(0)?,
+        description: row.get(1)?,
+        target_date: row.get(2)?,
+        status: parse_column(row, 3)?,
+        created_at: row.get(4)?,
+        closed_at: row.get(5)?,
+    })
+}
+
+/// Build the ORDER BY clause for a sort specification. The issue ID is always
+/// appended as a final tiebreaker so that paginated results are stable.
+fn order_by_clause(sort: &[SortKey]) -> String {
+    let default_order = SortKey::default_order();
+    let keys = if sort.is_empty() { &default_order[..] } else { sort };
+
+    let mut terms = Vec::new();
+    for key in keys {
+        let dir = if key.descending { "DESC" } else { "ASC" };
+        match key.field {
+            SortField::Id => {
+                // Compare prefix, then the numeric suffix, so bd-10 sorts after bd-9
+                terms.push(format!("substr(i.id, 1, instr(i.id, '-') - 1) {}", dir));
+                terms.push(format!("CAST(substr(i.id, instr(i.id, '-') + 1) AS INTEGER) {}", dir));
+            }
+            SortField::Title => terms.push(format!("i.title COLLATE NOCASE {}", dir)),
+            SortField::Status => terms.push(format!("i.status {}", dir)),
+            SortField::Priority => terms.push(format!("i.priority {}", dir)),
+            SortField::Created => terms.push(format!("i.created_at {}", dir)),
+            SortField::Updated => terms.push(format!("i.updated_at {}", dir)),
+            // Nullable columns: issues without a value always sort last
+            SortField::Closed => {
+                terms.push("i.closed_at IS NULL".to_string());
+                terms.push(format!("i.closed_at {}", dir));
+            }
+            SortField::Estimate => {
+                terms.push("i.estimated_minutes IS NULL".to_string());
+                terms.push(format!("i.estimated_minutes {}", dir));
+            }
+            SortField::Due => {
+                terms.push("i.due_at IS NULL".to_string());
+                terms.push(format!("i.due_at {}", dir));
+            }
+        }
+    }
+    terms.push("i.id ASC".to_string());
+
+    format!(" ORDER BY {}", terms.join(", "))
+}
+
+/// Build the LIMIT/OFFSET clause (SQLite requires a LIMIT before OFFSET).
+/// Values past SQLite's 64-bit integers are clamped; no result set is that large anyway
+fn limit_clause(limit: Option<usize>, offset: Option<usize>) -> String {
+    let clamp = |value: usize| value.min(i64::MAX as usize);
+    match (limit.map(clamp), offset.map(clamp)) {
+        (Some(limit), Some(offset)) => format!(" LIMIT {} OFFSET {}", limit, offset),
+        (Some(limit), None) => format!(" LIMIT {}", limit),
+        (None, Some(offset)) => format!(" LIMIT -1 OFFSET {}", offset),
+        (None, None) => String::new(),
+    }
+}
+
 impl std::str::FromStr for EventType {
     type Err = anyhow::Error;
 
@@ -920,6 +1730,129 @@ impl std::str::FromStr for EventType {
     }
 }
 
+/// The highest numeric suffix among live and archived issue IDs, per prefix (`bd-12` counts as 12 for `bd`)
+const MAX_IDS_BY_PREFIX: &str = "SELECT substr(id, 1, instr(id, '-') - 1) AS prefix,
+        MAX(CAST(substr(id, instr(id, '-') + 1) AS INTEGER)) AS max_id
+ FROM (SELECT id FROM issues UNION ALL SELECT id FROM archived_issues)
+ WHERE instr(id, '-') > 0
+ GROUP BY prefix";
+
+/// A numbered schema change. Each runs once, in order, and the database's
+/// `PRAGMA user_version` records the last one applied.
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    apply: fn(&Connection) -> Result<()>,
+}
+
+/// The schema version this tracer creates and understands
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Every migration, oldest first; versions must be 1, 2, 3, ... in order. Append
+/// new migrations to the end and never edit one that has shipped. Migrations 1-7
+/// predate schema versioning, so they tolerate databases that already have the
+/// tables and columns they add.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create issues, milestones, recurrences, work_logs, dependencies, labels, events and config tables",
+        apply: |conn| Ok(conn.execute_batch(SCHEMA)?),
+    },
+    Migration {
+        version: 2,
+        description: "track issues changed since the last export",
+        apply: |conn| {
+            Ok(conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS dirty_issues (
+                    issue_id TEXT PRIMARY KEY,
+                    marked_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (issue_id) REFERENCES issues(id) ON DELETE CASCADE
+                );
+                CREATE INDEX IF NOT EXISTS idx_dirty_issues_marked_at ON dirty_issues(marked_at);"
+            )?)
+        },
+    },
+    Migration {
+        version: 3,
+        description: "add per-prefix issue ID counters",
+        apply: |conn| {
+            Ok(conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS issue_counters (
+                    prefix TEXT PRIMARY KEY,
+                    last_id INTEGER NOT NULL DEFAULT 0
+                );"
+            )?)
+        },
+    },
+    Migration {
+        version: 4,
+        description: "add issues.external_ref",
+        apply: |conn| {
+            add_column_if_missing(conn, "issues", "external_ref", "TEXT")?;
+            conn.execute("CREATE INDEX IF NOT EXISTS idx_issues_external_ref ON issues(external_ref)", [])?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 5,
+        description: "add issues.due_at",
+        apply: |conn| {
+            add_column_if_missing(conn, "issues", "due_at", "DATETIME")?;
+            conn.execute("CREATE INDEX IF NOT EXISTS idx_issues_due_at ON issues(due_at)", [])?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 6,
+        description: "add issues.milestone",
+        apply: |conn| {
+            add_column_if_missing(conn, "issues", "milestone", "TEXT")?;
+            conn.execute("CREATE INDEX IF NOT EXISTS idx_issues_milestone ON issues(milestone)", [])?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 7,
+        description: "add metadata table",
+        apply: |conn| {
+            Ok(conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS metadata (
+                    key TEXT PRIMARY KEY,
+                    value TEXT NOT NULL,
+                    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+                );"
+            )?)
+        },
+    },
+    Migration {
+        version: 8,
+        description: "add archived_issues table",
+        apply: |conn| {
+            Ok(conn.execute_batch(
+                "CREATE TABLE archived_issues (
+                    id TEXT PRIMARY KEY,
+                    data TEXT NOT NULL,
+                    archived_at DATETIME NOT NULL
+                );"
+            )?)
+        },
+    },
+];
+
+/// Add a column unless it is already there (for migrations that predate versioning)
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists = conn
+        .prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|name| name == column);
+
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
 const SCHEMA: &str = r#"
 CREATE TABLE IF NOT EXISTS issues (
     id TEXT PRIMARY KEY,
@@ -935,8 +1868,42 @@ CREATE TABLE IF NOT EXISTS issues (
     estimated_minutes INTEGER,
     created_at DATETIME NOT NULL,
     updated_at DATETIME NOT NULL,
-    closed_at DATETIME,
-    external_ref TEXT
+    closed_at DATETIME
+);
+
+CREATE TABLE IF NOT EXISTS milestones (
+    name TEXT PRIMARY KEY,
+    description TEXT NOT NULL DEFAULT '',
+    target_date DATETIME,
+    status TEXT NOT NULL DEFAULT 'open',
+    created_at DATETIME NOT NULL,
+    closed_at DATETIME
+);
+
+CREATE TABLE IF NOT EXISTS recurrences (
+    name TEXT PRIMARY KEY,
+    schedule TEXT NOT NULL,
+    title TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    issue_type TEXT NOT NULL DEFAULT 'task',
+    priority INTEGER NOT NULL DEFAULT 2,
+    labels TEXT NOT NULL DEFAULT '[]',
+    assignee TEXT NOT NULL DEFAULT '',
+    start_at DATETIME NOT NULL,
+    last_period DATETIME,
+    created_at DATETIME NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS work_logs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    issue_id TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    minutes INTEGER NOT NULL DEFAULT 0,
+    note TEXT NOT NULL DEFAULT '',
+    started_at DATETIME NOT NULL,
+    ended_at DATETIME,
+    source TEXT NOT NULL DEFAULT 'manual',
+    FOREIGN KEY (issue_id) REFERENCES issues(id) ON DELETE CASCADE
 );
 
 CREATE TABLE IF NOT EXISTS dependencies (
@@ -975,8 +1942,12 @@ CREATE TABLE IF NOT EXISTS config (
 );
 
 CREATE INDEX IF NOT EXISTS idx_issues_status ON issues(status);
+CREATE INDEX IF NOT EXISTS idx_work_logs_issue ON work_logs(issue_id);
 CREATE INDEX IF NOT EXISTS idx_issues_priority ON issues(priority);
 CREATE INDEX IF NOT EXISTS idx_issues_assignee ON issues(assignee);
+CREATE INDEX IF NOT EXISTS idx_issues_created_at ON issues(created_at);
+CREATE INDEX IF NOT EXISTS idx_issues_updated_at ON issues(updated_at);
+CREATE INDEX IF NOT EXISTS idx_issues_closed_at ON issues(closed_at);
 CREATE INDEX IF NOT EXISTS idx_dependencies_issue ON dependencies(issue_id);
 CREATE INDEX IF NOT EXISTS idx_dependencies_depends ON dependencies(depends_on_id);
 CREATE INDEX IF NOT EXISTS idx_dependencies_type ON dependencies(type);
@@ -0,0 +1,106 @@
+use crate::types::*;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Load a template by name from the templates directory
+pub fn load_template(dir: &Path, name: &str) -> Result<IssueTemplate> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        anyhow::bail!("invalid template name: {}", name);
+    }
+
+    let path = dir.join(format!("{}.json", name));
+    let data = std::fs::read_to_string(&path)
+        .with_context(|| format!("Template {} not found (expected {})", name, path.display()))?;
+    let template: IssueTemplate = serde_json::from_str(&data)
+        .with_context(|| format!("Failed to parse template {}", path.display()))?;
+
+    let priorities = template.priority.iter().chain(template.children.iter().filter_map(|c| c.priority.as_ref()));
+    for priority in priorities {
+        if !(0..=4).contains(priority) {
+            anyhow::bail!("template {}: priority must be between 0 and 4 (got {})", name, priority);
+        }
+    }
+    Ok(template)
+}
+
+/// List template names in the templates directory, sorted
+pub fn list_templates(dir: &Path) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(stem) = path.file_stem() {
+                    names.push(stem.to_string_lossy().to_string());
+                }
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Replace `{{key}}` placeholders, failing on any variable that isn't provided
+pub fn render(text: &str, vars: &HashMap<String, String>) -> Result<String> {
+    let mut output = String::with_capacity(text.len());
+    let mut missing = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            anyhow::bail!("unterminated placeholder in template: {}", &rest[start..]);
+        };
+        let key = after[..end].trim();
+        match vars.get(key) {
+            Some(value) => output.push_str(value),
+            None => {
+                if !missing.contains(&key.to_string()) {
+                    missing.push(key.to_string());
+                }
+            }
+        }
+        rest = &after[end + 2..];
+    }
+    output.push_str(rest);
+
+    if !missing.is_empty() {
+        anyhow::bail!("missing template variable(s): {} (pass --var key=value)", missing.join(", "));
+    }
+    Ok(output)
+}
+
+/// Fill every text field of a template (including children) from `vars`
+pub fn instantiate(template: &IssueTemplate, vars: &HashMap<String, String>) -> Result<IssueTemplate> {
+    let children = template.children.iter()
+        .map(|child| Ok(TemplateChild {
+            title: render(&child.title, vars)?,
+            description: render(&child.description, vars)?,
+            labels: child.labels.iter().map(|l| render(l, vars)).collect::<Result<_>>()?,
+            ..child.clone()
+        }))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(IssueTemplate {
+        title: template.title.as_deref().map(|t| render(t, vars)).transpose()?,
+        description: render(&template.description, vars)?,
+        design: render(&template.design, vars)?,
+        acceptance_criteria: render(&template.acceptance_criteria, vars)?,
+        labels: template.labels.iter().map(|l| render(l, vars)).collect::<Result<_>>()?,
+        children,
+        ..template.clone()
+    })
+}
+
+/// Parse a `key=value` template variable
+pub fn parse_var(spec: &str) -> Result<(String, String)> {
+    let (key, value) = spec.split_once('=')
+        .with_context(|| format!("invalid variable: {} (expected key=value)", spec))?;
+    let key = key.trim();
+    if key.is_empty() {
+        anyhow::bail!("invalid variable: {} (empty key)", spec);
+    }
+    Ok((key.to_string(), value.to_string()))
+}
@@ -1,3 +1,4 @@
+use crate::error::Error;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -28,25 +29,74 @@ pub struct Issue {
     pub closed_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub external_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub milestone: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub dependencies: Vec<Dependency>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub work_logs: Vec<WorkLog>,
 }
 
 impl Issue {
+    /// A new open task with priority 2, created now; every other field is empty
+    pub fn new(id: impl Into<String>, title: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Issue {
+            id: id.into(),
+            title: title.into(),
+            description: String::new(),
+            design: String::new(),
+            acceptance_criteria: String::new(),
+            notes: String::new(),
+            status: Status::Open,
+            priority: 2,
+            issue_type: IssueType::Task,
+            assignee: String::new(),
+            estimated_minutes: None,
+            created_at: now,
+            updated_at: now,
+            closed_at: None,
+            external_ref: None,
+            due_at: None,
+            milestone: None,
+            dependencies: Vec::new(),
+            work_logs: Vec::new(),
+        }
+    }
+
+    /// Check that `actor` can claim the issue, i.e. start work on it without naming
+    /// an assignee. Fails with `Error::ClaimConflict` when someone else holds it,
+    /// e.g. because another agent claimed it first.
+    pub fn check_claim(&self, actor: &str) -> crate::Result<()> {
+        let holder = if !self.assignee.is_empty() && self.assignee != actor {
+            Some(self.assignee.clone())
+        } else if self.assignee.is_empty() && self.status == Status::InProgress {
+            Some("someone else".to_string())
+        } else {
+            None
+        };
+        match holder {
+            Some(holder) => Err(crate::Error::ClaimConflict { id: self.id.clone(), holder }),
+            None => Ok(()),
+        }
+    }
+
     /// Validate checks if the issue has valid field values
-    pub fn validate(&self) -> anyhow::Result<()> {
+    pub fn validate(&self) -> crate::Result<()> {
         if self.title.is_empty() {
-            anyhow::bail!("title is required");
+            return Err(Error::Validation("title is required".to_string()));
         }
         if self.title.len() > 500 {
-            anyhow::bail!("title must be 500 characters or less (got {})", self.title.len());
+            return Err(Error::Validation(format!("title must be 500 characters or less (got {})", self.title.len())));
         }
         if !(0..=4).contains(&self.priority) {
-            anyhow::bail!("priority must be between 0 and 4 (got {})", self.priority);
+            return Err(Error::Validation(format!("priority must be between 0 and 4 (got {})", self.priority)));
         }
         if let Some(est) = self.estimated_minutes {
             if est < 0 {
-                anyhow::bail!("estimated_minutes cannot be negative");
+                return Err(Error::Validation("estimated_minutes cannot be negative".to_string()));
             }
         }
         Ok(())
@@ -54,14 +104,25 @@ impl Issue {
 }
 
 /// Status represents the current state of an issue
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
-#[serde(rename_all = "snake_case")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(try_from = "String", into = "String")]
 pub enum Status {
     #[default]
     Open,
     InProgress,
     Blocked,
     Closed,
+    /// A status defined by the database's workflow (see `Workflow`)
+    Custom(String),
+}
+
+impl Status {
+    /// The statuses every database has, regardless of workflow configuration
+    pub const BUILTIN: [Status; 4] = [Status::Open, Status::InProgress, Status::Blocked, Status::Closed];
+
+    pub fn is_builtin(&self) -> bool {
+        !matches!(self, Status::Custom(_))
+    }
 }
 
 impl fmt::Display for Status {
@@ -71,6 +132,7 @@ impl fmt::Display for Status {
             Status::InProgress => write!(f, "in_progress"),
             Status::Blocked => write!(f, "blocked"),
             Status::Closed => write!(f, "closed"),
+            Status::Custom(name) => write!(f, "{}", name),
         }
     }
 }
@@ -84,11 +146,151 @@ impl std::str::FromStr for Status {
             "in_progress" => Ok(Status::InProgress),
             "blocked" => Ok(Status::Blocked),
             "closed" => Ok(Status::Closed),
+            // Whether a custom status is actually defined is checked against the workflow
+            _ if is_valid_status_name(s) => Ok(Status::Custom(s.to_string())),
             _ => anyhow::bail!("invalid status: {}", s),
         }
     }
 }
 
+impl TryFrom<String> for Status {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Status> for String {
+    fn from(status: Status) -> Self {
+        status.to_string()
+    }
+}
+
+/// Status names are lowercase identifiers: a letter followed by letters, digits, '_' or '-'
+fn is_valid_status_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+/// Workflow configures extra statuses and which status transitions are allowed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Workflow {
+    /// Statuses in addition to the built-in open/in_progress/blocked/closed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub statuses: Vec<WorkflowStatus>,
+    /// Allowed transitions; when empty, any transition between known statuses is allowed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transitions: Vec<Transition>,
+}
+
+/// WorkflowStatus is a custom status defined by a workflow
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkflowStatus {
+    pub name: String,
+    /// Done statuses satisfy `blocks` dependencies the same way `closed` does
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// Transition is an allowed move from one status to another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transition {
+    pub from: Status,
+    pub to: Status,
+}
+
+impl Workflow {
+    /// All statuses known to this workflow, built-ins first
+    pub fn all_statuses(&self) -> Vec<Status> {
+        Status::BUILTIN.iter().cloned()
+            .chain(self.statuses.iter().map(|s| Status::Custom(s.name.clone())))
+            .collect()
+    }
+
+    pub fn is_known(&self, status: &Status) -> bool {
+        match status {
+            Status::Custom(name) => self.statuses.iter().any(|s| &s.name == name),
+            _ => true,
+        }
+    }
+
+    /// Check that a status used as a filter exists, so a typo is an error rather
+    /// than a filter that silently matches nothing
+    pub fn check_known(&self, status: &Status) -> crate::Result<()> {
+        if self.is_known(status) {
+            return Ok(());
+        }
+        let known: Vec<String> = self.all_statuses().iter().map(Status::to_string).collect();
+        Err(Error::Validation(format!("unknown status: {} (known: {})", status, known.join(", "))))
+    }
+
+    /// Whether issues in this status no longer block their dependents
+    pub fn is_done(&self, status: &Status) -> bool {
+        match status {
+            Status::Closed => true,
+            Status::Custom(name) => self.statuses.iter().any(|s| &s.name == name && s.done),
+            _ => false,
+        }
+    }
+
+    /// `closed` plus every custom status marked as done
+    pub fn done_statuses(&self) -> Vec<Status> {
+        self.all_statuses().into_iter().filter(|s| self.is_done(s)).collect()
+    }
+
+    pub fn can_transition(&self, from: &Status, to: &Status) -> bool {
+        from == to
+            || self.transitions.is_empty()
+            || self.transitions.iter().any(|t| &t.from == from && &t.to == to)
+    }
+
+    /// Check that a status change is to a known status and allowed by the transition table
+    pub fn check_transition(&self, from: &Status, to: &Status) -> crate::Result<()> {
+        if !self.is_known(to) {
+            return Err(Error::Validation(format!("unknown status: {} (add it with `tracer workflow add-status {}`)", to, to)));
+        }
+        if !self.can_transition(from, to) {
+            let allowed: Vec<String> = self.transitions.iter()
+                .filter(|t| &t.from == from)
+                .map(|t| t.to.to_string())
+                .collect();
+            if allowed.is_empty() {
+                return Err(Error::Validation(format!("transition {} -> {} is not allowed by the workflow (no transitions out of {})", from, to, from)));
+            }
+            return Err(Error::Validation(format!("transition {} -> {} is not allowed by the workflow (allowed: {})", from, to, allowed.join(", "))));
+        }
+        Ok(())
+    }
+
+    /// Validate that custom statuses are well-formed and transitions reference known statuses
+    pub fn validate(&self) -> crate::Result<()> {
+        let mut seen = std::collections::HashSet::new();
+        for status in &self.statuses {
+            if !is_valid_status_name(&status.name) {
+                return Err(Error::Validation(format!("invalid status name: {} (use lowercase letters, digits, '_' or '-')", status.name)));
+            }
+            let parsed: Status = status.name.parse()
+                .map_err(|err: anyhow::Error| Error::Validation(err.to_string()))?;
+            if parsed.is_builtin() {
+                return Err(Error::Validation(format!("{} is a built-in status", status.name)));
+            }
+            if !seen.insert(&status.name) {
+                return Err(Error::Validation(format!("duplicate status: {}", status.name)));
+            }
+        }
+        for transition in &self.transitions {
+            for status in [&transition.from, &transition.to] {
+                if !self.is_known(status) {
+                    return Err(Error::Validation(format!("transition references unknown status: {}", status)));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 /// IssueType categorizes the kind of work
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
@@ -139,6 +341,52 @@ pub struct Dependency {
     pub created_by: String,
 }
 
+/// WorkLog records time spent on an issue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkLog {
+    pub issue_id: String,
+    pub actor: String,
+    pub minutes: i64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    pub started_at: DateTime<Utc>,
+    /// None while automatic tracking is still running
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub source: WorkLogSource,
+}
+
+/// WorkLogSource tells manual entries apart from in_progress tracking
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkLogSource {
+    #[default]
+    Manual,
+    Auto,
+}
+
+impl fmt::Display for WorkLogSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkLogSource::Manual => write!(f, "manual"),
+            WorkLogSource::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl std::str::FromStr for WorkLogSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "manual" => Ok(WorkLogSource::Manual),
+            "auto" => Ok(WorkLogSource::Auto),
+            _ => anyhow::bail!("invalid work log source: {}", s),
+        }
+    }
+}
+
 /// DependencyType categorizes the relationship
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -147,6 +395,7 @@ pub enum DependencyType {
     Related,
     ParentChild,
     DiscoveredFrom,
+    DuplicateOf,
 }
 
 impl fmt::Display for DependencyType {
@@ -156,6 +405,7 @@ impl fmt::Display for DependencyType {
             DependencyType::Related => write!(f, "related"),
             DependencyType::ParentChild => write!(f, "parent-child"),
             DependencyType::DiscoveredFrom => write!(f, "discovered-from"),
+            DependencyType::DuplicateOf => write!(f, "duplicate-of"),
         }
     }
 }
@@ -169,11 +419,81 @@ impl std::str::FromStr for DependencyType {
             "related" => Ok(DependencyType::Related),
             "parent-child" => Ok(DependencyType::ParentChild),
             "discovered-from" => Ok(DependencyType::DiscoveredFrom),
+            "duplicate-of" => Ok(DependencyType::DuplicateOf),
             _ => anyhow::bail!("invalid dependency type: {}", s),
         }
     }
 }
 
+/// Milestone groups issues planned for a release or sprint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Milestone {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_date: Option<DateTime<Utc>>,
+    pub status: MilestoneStatus,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl Milestone {
+    /// Validate checks if the milestone has valid field values
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(Error::Validation("milestone name is required".to_string()));
+        }
+        if self.name.len() > 100 {
+            return Err(Error::Validation(format!("milestone name must be 100 characters or less (got {})", self.name.len())));
+        }
+        Ok(())
+    }
+}
+
+/// MilestoneStatus tracks whether a milestone is still being worked towards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MilestoneStatus {
+    #[default]
+    Open,
+    Closed,
+}
+
+impl fmt::Display for MilestoneStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MilestoneStatus::Open => write!(f, "open"),
+            MilestoneStatus::Closed => write!(f, "closed"),
+        }
+    }
+}
+
+impl std::str::FromStr for MilestoneStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(MilestoneStatus::Open),
+            "closed" => Ok(MilestoneStatus::Closed),
+            _ => anyhow::bail!("invalid milestone status: {}", s),
+        }
+    }
+}
+
+/// MilestoneReport summarizes progress towards a milestone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MilestoneReport {
+    #[serde(flatten)]
+    pub milestone: Milestone,
+    pub total_issues: i32,
+    pub open_issues: i32,   // Not in a done status
+    pub closed_issues: i32, // Closed or in a custom done status
+    pub remaining_estimated_minutes: i64,
+    pub unestimated_open_issues: i32,
+}
+
 /// Label represents a tag on an issue
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Label {
@@ -230,6 +550,15 @@ impl fmt::Display for EventType {
     }
 }
 
+/// EventFilter is used to query the audit trail across issues
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub actor: Option<String>,
+    pub event_types: Vec<EventType>, // Empty = all event types
+}
+
 /// BlockedIssue extends Issue with blocking information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockedIssue {
@@ -239,6 +568,114 @@ pub struct BlockedIssue {
     pub blocked_by: Vec<String>,
 }
 
+/// StaleIssue is an unclosed issue with no recent activity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleIssue {
+    #[serde(flatten)]
+    pub issue: Issue,
+    /// Latest of the issue's updated_at and its most recent event
+    pub last_activity: DateTime<Utc>,
+}
+
+/// ActorActivity summarizes what one actor did, as recorded in the audit trail
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActorActivity {
+    pub actor: String,
+    pub created: i32,
+    /// Status transitions into in_progress
+    pub claimed: i32,
+    pub closed: i32,
+    pub comments: i32,
+    pub dependencies_added: i32,
+    /// Median hours from claiming an issue to closing it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub median_claim_to_close_hours: Option<f64>,
+    /// Issues currently assigned to this actor and in progress
+    pub in_progress: Vec<Issue>,
+}
+
+/// SimilarIssue is a likely duplicate found by text similarity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarIssue {
+    pub id: String,
+    pub title: String,
+    pub status: Status,
+    /// Cosine similarity between 0.0 and 1.0
+    pub score: f64,
+}
+
+/// IssueTemplate describes a recurring shape of issue, stored as
+/// `.trace/templates/<name>.json`. Text fields may contain `{{var}}`
+/// placeholders filled from `--var key=value`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IssueTemplate {
+    /// Title used when none is given on the command line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub design: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub acceptance_criteria: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue_type: Option<IssueType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// Child issues created alongside, linked with `parent-child`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TemplateChild>,
+}
+
+/// TemplateChild is a child issue created from a template
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateChild {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue_type: Option<IssueType>,
+    /// Defaults to the parent's priority
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+}
+
+/// Recurrence defines an issue that is re-created on a schedule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recurrence {
+    pub name: String,
+    pub schedule: crate::schedule::Schedule,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    pub issue_type: IssueType,
+    pub priority: i32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub assignee: String,
+    /// Interval schedules count from here; nothing is created for earlier periods
+    pub start_at: DateTime<Utc>,
+    /// The most recent period an issue was created for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_period: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Recurrence {
+    /// External reference linking created issues to this definition and period,
+    /// so the same occurrence is never created twice (even across clones)
+    pub fn external_ref(&self, period: DateTime<Utc>) -> String {
+        format!("recur:{}@{}", self.name, period.format("%Y-%m-%dT%H:%MZ"))
+    }
+}
+
 /// TreeNode represents a node in a dependency tree
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TreeNode {
@@ -258,6 +695,94 @@ pub struct Statistics {
     pub blocked_issues: i32,
     pub ready_issues: i32,
     pub average_lead_time_hours: f64,
+    #[serde(default)]
+    pub overdue_issues: i32,
+    #[serde(default)]
+    pub due_soon_issues: i32, // Due within the next 7 days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_due_at: Option<DateTime<Utc>>,
+    /// Estimated vs. logged time on closed issues, per issue type
+    #[serde(default)]
+    pub estimation_by_type: std::collections::BTreeMap<String, EstimationAccuracy>,
+    /// Estimated vs. logged time on closed issues, per assignee
+    #[serde(default)]
+    pub estimation_by_actor: std::collections::BTreeMap<String, EstimationAccuracy>,
+}
+
+/// EstimationAccuracy compares estimates with logged time for a group of issues
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EstimationAccuracy {
+    pub issues: i32,
+    pub estimated_minutes: i64,
+    pub actual_minutes: i64,
+    /// actual / estimated; above 1.0 means work took longer than estimated
+    pub ratio: f64,
+}
+
+/// Bucket is the time granularity of a history report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Bucket {
+    #[default]
+    Day,
+    Week,
+}
+
+impl fmt::Display for Bucket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bucket::Day => write!(f, "day"),
+            Bucket::Week => write!(f, "week"),
+        }
+    }
+}
+
+impl std::str::FromStr for Bucket {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "day" => Ok(Bucket::Day),
+            "week" => Ok(Bucket::Week),
+            _ => anyhow::bail!("invalid bucket: {} (expected day or week)", s),
+        }
+    }
+}
+
+/// History reports trends over time, reconstructed from the events table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct History {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub bucket: Bucket,
+    pub buckets: Vec<HistoryBucket>,
+    pub cycle_time: CycleTimeStats,
+    /// Issues closed in the window, per actor who closed them
+    pub throughput_by_actor: std::collections::BTreeMap<String, i32>,
+}
+
+/// HistoryBucket holds activity within one bucket and the state at its end
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryBucket {
+    pub start: DateTime<Utc>,
+    pub opened: i32,
+    pub closed: i32,
+    /// Cumulative flow: number of issues in each status at the end of the bucket
+    pub status_counts: std::collections::BTreeMap<String, i32>,
+}
+
+/// CycleTimeStats summarizes time from first `in_progress` to `closed`, in hours
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CycleTimeStats {
+    pub count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p50_hours: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p75_hours: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p90_hours: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p95_hours: Option<f64>,
 }
 
 /// IssueFilter is used to filter issue queries
@@ -268,7 +793,18 @@ pub struct IssueFilter {
     pub issue_type: Option<IssueType>,
     pub assignee: Option<String>,
     pub labels: Vec<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+    pub closed_after: Option<DateTime<Utc>>,
+    pub closed_before: Option<DateTime<Utc>>,
+    pub due_before: Option<DateTime<Utc>>,
+    pub overdue: bool, // Past due and not in a done status
+    pub milestone: Option<String>,
+    pub sort: Vec<SortKey>, // Empty = default order (priority, newest first)
     pub limit: Option<usize>,
+    pub offset: Option<usize>,
 }
 
 /// WorkFilter is used to filter ready work queries
@@ -277,7 +813,176 @@ pub struct WorkFilter {
     pub status: Status,
     pub priority: Option<i32>,
     pub assignee: Option<String>,
+    pub milestone: Option<String>,
+    pub sort: Vec<SortKey>, // Empty = default order (priority, newest first)
     pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// SortField identifies an issue attribute results can be ordered by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Id,
+    Title,
+    Status,
+    Priority,
+    Created,
+    Updated,
+    Closed,
+    Estimate,
+    Due,
+}
+
+impl fmt::Display for SortField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortField::Id => write!(f, "id"),
+            SortField::Title => write!(f, "title"),
+            SortField::Status => write!(f, "status"),
+            SortField::Priority => write!(f, "priority"),
+            SortField::Created => write!(f, "created"),
+            SortField::Updated => write!(f, "updated"),
+            SortField::Closed => write!(f, "closed"),
+            SortField::Estimate => write!(f, "estimate"),
+            SortField::Due => write!(f, "due"),
+        }
+    }
+}
+
+impl std::str::FromStr for SortField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "id" => Ok(SortField::Id),
+            "title" => Ok(SortField::Title),
+            "status" => Ok(SortField::Status),
+            "priority" => Ok(SortField::Priority),
+            "created" | "created_at" => Ok(SortField::Created),
+            "updated" | "updated_at" => Ok(SortField::Updated),
+            "closed" | "closed_at" => Ok(SortField::Closed),
+            "estimate" | "estimated_minutes" => Ok(SortField::Estimate),
+            "due" | "due_at" => Ok(SortField::Due),
+            _ => anyhow::bail!("invalid sort field: {}", s),
+        }
+    }
+}
+
+/// SortKey is a single field/direction pair in a sort specification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl SortKey {
+    pub fn asc(field: SortField) -> Self {
+        Self { field, descending: false }
+    }
+
+    pub fn desc(field: SortField) -> Self {
+        Self { field, descending: true }
+    }
+
+    /// The order used when no sort specification is given
+    pub fn default_order() -> Vec<SortKey> {
+        vec![SortKey::asc(SortField::Priority), SortKey::desc(SortField::Created)]
+    }
 }
 
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.field, if self.descending { "desc" } else { "asc" })
+    }
+}
 
+impl std::str::FromStr for SortKey {
+    type Err = anyhow::Error;
+
+    /// Parse "field", "field:asc", "field:desc" or "-field" (descending)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(field) = s.strip_prefix('-') {
+            return Ok(SortKey::desc(field.parse()?));
+        }
+        match s.split_once(':') {
+            Some((field, "asc")) => Ok(SortKey::asc(field.parse()?)),
+            Some((field, "desc")) => Ok(SortKey::desc(field.parse()?)),
+            Some((_, dir)) => anyhow::bail!("invalid sort direction: {} (expected asc or desc)", dir),
+            None => Ok(SortKey::asc(s.parse()?)),
+        }
+    }
+}
+
+/// Cursor is an opaque pagination token pointing at the next page of results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub offset: usize,
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(format!("offset:{}", self.offset)))
+    }
+}
+
+impl std::str::FromStr for Cursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let decoded = hex::decode(s).ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| anyhow::anyhow!("invalid cursor: {}", s))?;
+        let offset = decoded.strip_prefix("offset:")
+            .and_then(|n| n.parse().ok())
+            .ok_or_else(|| anyhow::anyhow!("invalid cursor: {}", s))?;
+        Ok(Cursor { offset })
+    }
+}
+
+/// Page wraps one page of paginated results with the token for the next one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+
+
+/// Finding is one problem reported by `tracer doctor`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    /// Which check found it, e.g. "orphaned_row" or "jsonl_drift"
+    pub check: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue_id: Option<String>,
+    /// Whether `doctor --fix` can repair it without losing data
+    pub fixable: bool,
+    #[serde(default)]
+    pub fixed: bool,
+}
+
+impl Finding {
+    pub fn new(check: &str, message: String, issue_id: Option<&str>, fixable: bool) -> Self {
+        Finding {
+            check: check.to_string(),
+            message,
+            issue_id: issue_id.map(str::to_string),
+            fixable,
+            fixed: false,
+        }
+    }
+}
+
+/// ArchivedIssue is a closed issue moved out of the live tracker by `tracer archive`,
+/// with everything recorded about it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedIssue {
+    pub issue: Issue, // Dependencies and work logs are carried on the issue
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<Event>, // Oldest first, compacted
+    pub archived_at: DateTime<Utc>,
+}
@@ -4,7 +4,7 @@ use std::path::{Path, PathBuf};
 
 /// Find the database path using the standard search order:
 /// 1. $TRACE_DB environment variable
-/// 2. .trace/*.db in current directory or ancestors
+/// 2. .trace/*.db (or a .trace/*.json JSONL store) in current directory or ancestors
 /// 3. ~/.trace/default.db (fallback)
 pub fn find_database_path() -> Result<PathBuf> {
     // 1. Check environment variable
@@ -29,6 +29,9 @@ pub fn find_database_path() -> Result<PathBuf> {
     Ok(default_db)
 }
 
+/// JSONL files tracer keeps next to the issues file, which must never be mistaken for it
+const AUXILIARY_JSONL_FILES: &[&str] = &["milestones.jsonl", "archive.jsonl"];
+
 /// Find the JSONL path for a given database path
 pub fn find_jsonl_path(db_path: &Path) -> PathBuf {
     let parent = db_path.parent().unwrap_or_else(|| std::path::Path::new("."));
@@ -36,8 +39,9 @@ pub fn find_jsonl_path(db_path: &Path) -> PathBuf {
     // Look for existing .jsonl files
     if let Ok(entries) = std::fs::read_dir(parent) {
         for entry in entries.flatten() {
+            let is_auxiliary = AUXILIARY_JSONL_FILES.iter().any(|name| entry.file_name() == *name);
             if let Some(ext) = entry.path().extension() {
-                if ext == "jsonl" {
+                if ext == "jsonl" && !is_auxiliary {
                     return entry.path();
                 }
             }
@@ -48,22 +52,50 @@ pub fn find_jsonl_path(db_path: &Path) -> PathBuf {
     parent.join("issues.jsonl")
 }
 
-/// Walk up the directory tree looking for .trace/*.db
+/// Find the milestones JSONL path for a given database path
+pub fn find_milestones_path(db_path: &Path) -> PathBuf {
+    let parent = db_path.parent().unwrap_or_else(|| std::path::Path::new("."));
+    parent.join("milestones.jsonl")
+}
+
+/// Find the archived issues JSONL path for a given database path
+pub fn find_archive_path(db_path: &Path) -> PathBuf {
+    let parent = db_path.parent().unwrap_or_else(|| std::path::Path::new("."));
+    parent.join("archive.jsonl")
+}
+
+/// Find the issue templates directory for a given database path
+pub fn find_templates_dir(db_path: &Path) -> PathBuf {
+    let parent = db_path.parent().unwrap_or_else(|| std::path::Path::new("."));
+    parent.join("templates")
+}
+
+/// Find the backups directory for a given database path
+pub fn find_backups_dir(db_path: &Path) -> PathBuf {
+    let parent = db_path.parent().unwrap_or_else(|| std::path::Path::new("."));
+    parent.join("backups")
+}
+
+/// Walk up the directory tree looking for .trace/*.db or .trace/*.json
 fn find_database_in_tree() -> Result<Option<PathBuf>> {
     let mut current = env::current_dir()?;
     
     loop {
         let trace_dir = current.join(".trace");
         if trace_dir.is_dir() {
-            // Look for *.db files in .trace/
+            // Look for a *.db file in .trace/, or a *.json store for the JSONL backend
             if let Ok(entries) = std::fs::read_dir(&trace_dir) {
+                let mut store = None;
                 for entry in entries.flatten() {
-                    if let Some(ext) = entry.path().extension() {
-                        if ext == "db" {
-                            return Ok(Some(entry.path()));
-                        }
+                    match entry.path().extension() {
+                        Some(ext) if ext == "db" => return Ok(Some(entry.path())),
+                        Some(ext) if ext == "json" && store.is_none() => store = Some(entry.path()),
+                        _ => {}
                     }
                 }
+                if store.is_some() {
+                    return Ok(store);
+                }
             }
         }
         
@@ -76,6 +108,15 @@ fn find_database_in_tree() -> Result<Option<PathBuf>> {
     Ok(None)
 }
 
+/// How long to wait for other tracer processes to release the database or the
+/// JSONL files: `$TRACE_BUSY_TIMEOUT` in milliseconds, or 5 seconds if unset or invalid
+pub fn busy_timeout() -> std::time::Duration {
+    env::var("TRACE_BUSY_TIMEOUT").ok()
+        .and_then(|ms| ms.trim().parse().ok())
+        .map(std::time::Duration::from_millis)
+        .unwrap_or(crate::storage::DEFAULT_BUSY_TIMEOUT)
+}
+
 /// Get the actor name from environment or system
 pub fn get_actor() -> String {
     env::var("TRACE_ACTOR")
@@ -84,6 +125,14 @@ pub fn get_actor() -> String {
         .unwrap_or_else(|_| "unknown".to_string())
 }
 
+/// Check that a priority given on the command line is between 0 (highest) and 4 (lowest)
+pub fn check_priority(priority: i32) -> Result<()> {
+    if !(0..=4).contains(&priority) {
+        return Err(crate::Error::Validation(format!("priority must be between 0 and 4 (got {})", priority)).into());
+    }
+    Ok(())
+}
+
 /// Parse dependency specification like "blocks:bd-1" or "bd-1" (defaults to blocks)
 pub fn parse_dependency_spec(spec: &str) -> Result<(crate::types::DependencyType, String)> {
     if let Some((type_str, id)) = spec.split_once(':') {
@@ -95,6 +144,113 @@ pub fn parse_dependency_spec(spec: &str) -> Result<(crate::types::DependencyType
     }
 }
 
+/// Parse a date specification: an ISO date ("2025-10-01", midnight UTC), an
+/// RFC 3339 timestamp, or a relative age like "30m", "24h", "7d" or "2w" ago
+pub fn parse_date_spec(spec: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    use chrono::{DateTime, NaiveDate, Utc};
+
+    let spec = spec.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(spec) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(spec, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+        return Ok(midnight.and_utc());
+    }
+
+    Utc::now().checked_sub_signed(parse_relative_duration(spec)?)
+        .with_context(|| format!("invalid date: {} is too far in the past", spec))
+}
+
+/// Parse a due date: an ISO date (due by the end of that day, UTC), an RFC 3339
+/// timestamp, or a relative offset from now like "3d" or "2w"
+pub fn parse_due_spec(spec: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    use chrono::{DateTime, NaiveDate, Utc};
+
+    let spec = spec.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(spec) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(spec, "%Y-%m-%d") {
+        let end_of_day = date.and_hms_opt(23, 59, 59).expect("23:59:59 is a valid time");
+        return Ok(end_of_day.and_utc());
+    }
+
+    Utc::now().checked_add_signed(parse_relative_duration(spec)?)
+        .with_context(|| format!("invalid date: {} is too far in the future", spec))
+}
+
+/// Parse a relative duration like "30m", "24h", "7d" or "2w"
+fn parse_relative_duration(spec: &str) -> Result<chrono::Duration> {
+    use chrono::Duration;
+
+    let split = spec.char_indices().last().map(|(idx, _)| idx).unwrap_or(0);
+    let (amount, unit) = spec.split_at(split);
+    let amount: i64 = amount.parse()
+        .with_context(|| format!("invalid date: {} (expected YYYY-MM-DD, RFC 3339, or e.g. 7d)", spec))?;
+    let duration = match unit {
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => anyhow::bail!("invalid date: {} (relative units are m, h, d, w)", spec),
+    };
+    duration.with_context(|| format!("invalid date: {} is out of range", spec))
+}
+
+/// Parse a work duration like "45m", "2h", "1h30m" or a bare number of minutes
+pub fn parse_work_duration(spec: &str) -> Result<i64> {
+    let spec = spec.trim();
+    if let Ok(minutes) = spec.parse::<i64>() {
+        if minutes <= 0 {
+            anyhow::bail!("duration must be positive: {}", spec);
+        }
+        return check_work_minutes(minutes, spec);
+    }
+
+    let mut total: i64 = 0;
+    let mut amount = String::new();
+    for c in spec.chars() {
+        match c {
+            '0'..='9' => amount.push(c),
+            'h' | 'm' if !amount.is_empty() => {
+                let value: i64 = amount.parse()
+                    .with_context(|| format!("invalid duration: {}", spec))?;
+                let minutes = if c == 'h' { value.checked_mul(60) } else { Some(value) };
+                total = minutes.and_then(|minutes| total.checked_add(minutes))
+                    .ok_or_else(|| duration_too_long(spec))?;
+                amount.clear();
+            }
+            _ => anyhow::bail!("invalid duration: {} (expected e.g. 45m, 2h or 1h30m)", spec),
+        }
+    }
+    if !amount.is_empty() || total <= 0 {
+        anyhow::bail!("invalid duration: {} (expected e.g. 45m, 2h or 1h30m)", spec);
+    }
+    check_work_minutes(total, spec)
+}
+
+/// Reject durations too long to subtract from a timestamp
+fn check_work_minutes(minutes: i64, spec: &str) -> Result<i64> {
+    match chrono::TimeDelta::try_minutes(minutes) {
+        Some(_) => Ok(minutes),
+        None => Err(duration_too_long(spec)),
+    }
+}
+
+fn duration_too_long(spec: &str) -> anyhow::Error {
+    crate::Error::Validation(format!("duration is too long: {}", spec)).into()
+}
+
+/// Format a number of minutes as "1h 30m" or "45m"
+pub fn format_minutes(minutes: i64) -> String {
+    if minutes >= 60 {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
 /// Format issue for display with colors
 pub fn format_issue(issue: &crate::types::Issue, with_description: bool) -> String {
     use colored::Colorize;
@@ -111,11 +267,12 @@ pub fn format_issue(issue: &crate::types::Issue, with_description: bool) -> Stri
     ));
     
     // Status and assignee
-    let status_colored = match issue.status {
+    let status_colored = match &issue.status {
         crate::types::Status::Open => "open".green(),
         crate::types::Status::InProgress => "in_progress".blue(),
         crate::types::Status::Blocked => "blocked".red(),
         crate::types::Status::Closed => "closed".dimmed(),
+        crate::types::Status::Custom(name) => name.magenta(),
     };
     output.push_str(&format!("  Status: {}\n", status_colored));
     
@@ -127,6 +284,15 @@ pub fn format_issue(issue: &crate::types::Issue, with_description: bool) -> Stri
         output.push_str(&format!("  Estimated: {} minutes\n", est));
     }
     
+    if let Some(due_at) = issue.due_at {
+        let due = due_at.format("%Y-%m-%d %H:%M").to_string();
+        if due_at < chrono::Utc::now() && issue.status != crate::types::Status::Closed {
+            output.push_str(&format!("  Due: {} {}\n", due.red(), "(overdue)".red().bold()));
+        } else {
+            output.push_str(&format!("  Due: {}\n", due));
+        }
+    }
+    
     // Description
     if with_description && !issue.description.is_empty() {
         output.push_str(&format!("\n  {}\n", issue.description));
@@ -146,6 +312,27 @@ pub fn format_issue(issue: &crate::types::Issue, with_description: bool) -> Stri
     output
 }
 
+/// Replace a file's contents atomically: write a temporary file next to it and
+/// rename it into place, so readers see either the old or the new contents
+pub fn write_atomic(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut file = std::fs::File::create(&tmp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    result
+}
+
 /// Compute SHA256 hash of data
 pub fn compute_hash(data: &[u8]) -> String {
     use sha2::{Digest, Sha256};
@@ -154,3 +341,27 @@ pub fn compute_hash(data: &[u8]) -> String {
     hex::encode(hasher.finalize())
 }
 
+
+/// Resolve the starting offset from an explicit `--offset` or a `--cursor` token
+pub fn resolve_offset(offset: Option<usize>, cursor: Option<&str>) -> Result<Option<usize>> {
+    match (offset, cursor) {
+        (Some(_), Some(_)) => anyhow::bail!("--offset and --cursor cannot be used together"),
+        (Some(offset), None) => Ok(Some(offset)),
+        (None, Some(cursor)) => Ok(Some(cursor.parse::<crate::types::Cursor>()?.offset)),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Turn a result set fetched with `limit + 1` rows into a page. The extra row,
+/// if present, only signals that another page exists and is dropped.
+pub fn into_page<T>(mut items: Vec<T>, offset: Option<usize>, limit: Option<usize>) -> crate::types::Page<T> {
+    let next_cursor = match limit {
+        Some(limit) if items.len() > limit => {
+            items.truncate(limit);
+            let next = crate::types::Cursor { offset: offset.unwrap_or(0) + limit };
+            Some(next.to_string())
+        }
+        _ => None,
+    };
+    crate::types::Page { items, next_cursor }
+}
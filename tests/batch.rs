@@ -0,0 +1,114 @@
+//! `tracer batch`: `$ref` placeholders and all-or-nothing application.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+struct Repo {
+    dir: tempfile::TempDir,
+}
+
+impl Repo {
+    fn new() -> Self {
+        let repo = Repo { dir: tempfile::tempdir().unwrap() };
+        assert!(repo.tracer(&["init", "--prefix", "t"]).status.success());
+        repo
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_tracer"));
+        command
+            .args(args)
+            .current_dir(self.dir.path())
+            .env("TRACE_DB", self.dir.path().join(".trace/t.db"))
+            .env("TRACE_ACTOR", "tester");
+        command
+    }
+
+    fn tracer(&self, args: &[&str]) -> Output {
+        self.command(args).output().unwrap()
+    }
+
+    fn json(&self, args: &[&str]) -> serde_json::Value {
+        let output = self.tracer(args);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        serde_json::from_slice(&output.stdout).unwrap()
+    }
+
+    fn batch(&self, lines: &[&str]) -> Output {
+        let mut child = self.command(&["batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(lines.join("\n").as_bytes()).unwrap();
+        child.wait_with_output().unwrap()
+    }
+}
+
+#[test]
+fn refs_resolve_to_issues_created_earlier_in_the_batch() {
+    let repo = Repo::new();
+    let output = repo.batch(&[
+        r#"{"op": "create", "ref": "parent", "title": "Parent"}"#,
+        r#"{"op": "create", "ref": "child", "title": "Child"}"#,
+        r#"{"op": "dep_add", "from": "$child", "to": "$parent"}"#,
+        r#"{"op": "update", "id": "$child", "priority": 0}"#,
+        r#"{"op": "label_add", "id": "$parent", "label": "epic"}"#,
+    ]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["applied"], 5);
+    assert_eq!(result["refs"], serde_json::json!({"parent": "t-1", "child": "t-2"}));
+    assert_eq!(result["results"][2]["id"], "t-2");
+    assert_eq!(result["results"][2]["depends_on_id"], "t-1");
+
+    let blocked = repo.json(&["--json", "blocked"]);
+    assert_eq!(blocked[0]["id"], "t-2");
+    assert_eq!(blocked[0]["priority"], 0);
+    assert_eq!(blocked[0]["blocked_by"], serde_json::json!(["t-1"]));
+    let labeled = repo.json(&["--json", "list", "--labels", "epic"]);
+    assert_eq!(labeled.as_array().unwrap().len(), 1);
+    assert_eq!(labeled[0]["id"], "t-1");
+}
+
+#[test]
+fn unknown_refs_are_rejected() {
+    let repo = Repo::new();
+    let output = repo.batch(&[
+        r#"{"op": "comment", "id": "$later", "text": "too early"}"#,
+        r#"{"op": "create", "ref": "later", "title": "Later"}"#,
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown ref $later"));
+    assert_eq!(repo.json(&["--json", "list"]), serde_json::json!([]));
+}
+
+#[test]
+fn a_failing_op_leaves_no_issues_behind() {
+    let repo = Repo::new();
+    assert!(repo.tracer(&["create", "Existing"]).status.success());
+
+    let output = repo.batch(&[
+        r#"{"op": "create", "ref": "a", "title": "First"}"#,
+        r#"{"op": "create", "ref": "b", "title": "Second"}"#,
+        r#"{"op": "dep_add", "from": "$b", "to": "$a"}"#,
+        r#"{"op": "update", "id": "t-1", "title": "Renamed"}"#,
+        r#"{"op": "close", "id": "t-99"}"#,
+    ]);
+    assert_eq!(output.status.code(), Some(3));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("line 5: close failed; no changes were made"), "{}", stderr);
+
+    let issues = repo.json(&["--json", "list"]);
+    assert_eq!(issues.as_array().unwrap().len(), 1);
+    assert_eq!(issues[0]["id"], "t-1");
+    assert_eq!(issues[0]["title"], "Existing");
+    assert_eq!(repo.json(&["--json", "blocked"]), serde_json::json!([]));
+
+    // The rolled-back IDs are handed out again
+    let output = repo.batch(&[r#"{"op": "create", "title": "Retry"}"#]);
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["results"][0]["id"], "t-2");
+}
@@ -0,0 +1,122 @@
+//! `tracer bulk update`: issue selection and all-or-nothing application.
+
+use std::process::{Command, Output};
+
+struct Repo {
+    dir: tempfile::TempDir,
+}
+
+impl Repo {
+    fn new() -> Self {
+        let repo = Repo { dir: tempfile::tempdir().unwrap() };
+        assert!(repo.tracer(&["init", "--prefix", "t"]).status.success());
+        repo
+    }
+
+    fn tracer(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_tracer"))
+            .args(args)
+            .current_dir(self.dir.path())
+            .env("TRACE_DB", self.dir.path().join(".trace/t.db"))
+            .env("TRACE_ACTOR", "tester")
+            .output()
+            .unwrap()
+    }
+
+    fn run(&self, args: &[&str]) {
+        let output = self.tracer(args);
+        assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    }
+
+    fn json(&self, args: &[&str]) -> serde_json::Value {
+        let output = self.tracer(args);
+        assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        serde_json::from_slice(&output.stdout).unwrap()
+    }
+
+    /// IDs of the issues a dry run would change
+    fn selected(&self, selection: &[&str]) -> Vec<String> {
+        let mut args = vec!["--json", "bulk", "update", "--set", "priority=0", "--dry-run"];
+        args.extend_from_slice(selection);
+        let result = self.json(&args);
+        let mut ids: Vec<String> = result["issues"].as_array().unwrap().iter()
+            .map(|issue| issue["id"].as_str().unwrap().to_string())
+            .collect();
+        ids.sort();
+        ids
+    }
+}
+
+#[test]
+fn every_label_filter_must_match() {
+    let repo = Repo::new();
+    repo.run(&["create", "Both", "--labels", "backend,urgent"]);
+    repo.run(&["create", "Backend only", "--labels", "backend"]);
+    repo.run(&["create", "Urgent only", "--labels", "urgent"]);
+    repo.run(&["create", "All three", "--labels", "backend,urgent,security"]);
+
+    assert_eq!(repo.selected(&["--where", "label=backend"]), ["t-1", "t-2", "t-4"]);
+    assert_eq!(repo.selected(&["--where", "label=backend", "--where", "label=urgent"]), ["t-1", "t-4"]);
+    assert_eq!(
+        repo.selected(&["--where", "label=backend", "--where", "label=urgent", "--where", "label=security"]),
+        ["t-4"]
+    );
+    assert!(repo.selected(&["--where", "label=urgent", "--where", "label=frontend"]).is_empty());
+}
+
+#[test]
+fn ids_and_where_select_the_intersection() {
+    let repo = Repo::new();
+    repo.run(&["create", "One", "-p", "1"]);
+    repo.run(&["create", "Two", "-p", "1", "--labels", "backend"]);
+    repo.run(&["create", "Three", "-p", "3", "--labels", "backend"]);
+    repo.run(&["create", "Four", "-p", "1", "--labels", "backend"]);
+
+    assert_eq!(repo.selected(&["--ids", "t-1,t-2,t-3"]), ["t-1", "t-2", "t-3"]);
+    assert_eq!(repo.selected(&["--ids", "t-1,t-2,t-3", "--where", "priority=1"]), ["t-1", "t-2"]);
+    assert_eq!(
+        repo.selected(&["--ids", "t-1,t-2,t-3", "--where", "priority=1", "--where", "label=backend"]),
+        ["t-2"]
+    );
+    assert!(repo.selected(&["--ids", "t-3", "--where", "priority=1"]).is_empty());
+
+    // A listed ID must exist even if the filter would drop it
+    let output = repo.tracer(&["bulk", "update", "--ids", "t-1,t-9", "--where", "priority=1", "--set", "priority=0"]);
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn a_disallowed_transition_rolls_back_every_issue() {
+    let repo = Repo::new();
+    repo.run(&["create", "Open"]);
+    repo.run(&["create", "Started"]);
+    repo.run(&["create", "Also open"]);
+    repo.run(&["update", "t-2", "--status", "in_progress"]);
+    // Only open issues may be closed
+    repo.run(&["workflow", "allow", "open", "closed"]);
+    repo.run(&["workflow", "allow", "open", "in_progress"]);
+
+    let output = repo.tracer(&[
+        "bulk", "update", "--ids", "t-1,t-2,t-3",
+        "--set", "status=closed", "--set", "priority=0", "--add-label", "swept",
+    ]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("failed to close t-2"), "{}", stderr);
+
+    // t-1 was closed before t-2 failed; none of it stuck
+    let issues = repo.json(&["--json", "list", "--sort", "id"]);
+    let state: Vec<(&str, &str, i64)> = issues.as_array().unwrap().iter()
+        .map(|issue| (
+            issue["id"].as_str().unwrap(),
+            issue["status"].as_str().unwrap(),
+            issue["priority"].as_i64().unwrap(),
+        ))
+        .collect();
+    assert_eq!(state, [("t-1", "open", 2), ("t-2", "in_progress", 2), ("t-3", "open", 2)]);
+    assert_eq!(repo.json(&["--json", "list", "--labels", "swept"]), serde_json::json!([]));
+
+    // Leaving the started issue out, the rest go through
+    repo.run(&["bulk", "update", "--ids", "t-1,t-3", "--set", "status=closed"]);
+    assert_eq!(repo.json(&["--json", "list", "--status", "closed"]).as_array().unwrap().len(), 2);
+}
@@ -0,0 +1,170 @@
+//! Many processes (and connections) using one project at once: writes wait for
+//! each other instead of failing, and the JSONL files stay complete.
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tracer::storage::sqlite::SqliteStorage;
+use tracer::*;
+
+const WORKERS: usize = 8;
+const ISSUES_PER_WORKER: usize = 6;
+
+fn tracer(dir: &Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_tracer"));
+    cmd.current_dir(dir)
+        .env("TRACE_DB", dir.join(".trace/t.db"))
+        .env("TRACE_ACTOR", "stress")
+        .env("TRACE_BUSY_TIMEOUT", "30000")
+        .env("RUST_BACKTRACE", "0");
+    cmd
+}
+
+#[test]
+fn concurrent_processes_keep_every_issue() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = tracer(dir.path()).args(["init", "--prefix", "t"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let workers: Vec<_> = (0..WORKERS)
+        .map(|worker| {
+            let dir = dir.path().to_path_buf();
+            std::thread::spawn(move || {
+                for n in 0..ISSUES_PER_WORKER {
+                    let title = format!("worker {} issue {}", worker, n);
+                    let output = tracer(&dir).args(["create", &title, "--json"]).output().unwrap();
+                    assert!(output.status.success(), "create failed: {}", String::from_utf8_lossy(&output.stderr));
+                    let issue: Issue = serde_json::from_slice(&output.stdout).unwrap();
+
+                    let output = tracer(&dir).args(["update", &issue.id, "--status", "in_progress"]).output().unwrap();
+                    assert!(output.status.success(), "update failed: {}", String::from_utf8_lossy(&output.stderr));
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    let expected = WORKERS * ISSUES_PER_WORKER;
+    let storage = SqliteStorage::new(dir.path().join(".trace/t.db")).unwrap();
+    let issues = storage.search_issues("", &IssueFilter::default()).unwrap();
+    assert_eq!(issues.len(), expected);
+    assert!(issues.iter().all(|issue| issue.status == Status::InProgress));
+    assert!(storage.get_dirty_issues().unwrap().is_empty());
+
+    // Every change made it into the JSONL file, and no temporary files were left behind
+    let jsonl = std::fs::read_to_string(dir.path().join(".trace/issues.jsonl")).unwrap();
+    let exported: Vec<Issue> = jsonl.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(exported.len(), expected);
+    assert!(exported.iter().all(|issue| issue.status == Status::InProgress));
+    let leftovers = std::fs::read_dir(dir.path().join(".trace")).unwrap()
+        .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().ends_with(".tmp"))
+        .count();
+    assert_eq!(leftovers, 0);
+}
+
+#[test]
+fn concurrent_connections_wait_for_the_write_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("t.db");
+    drop(SqliteStorage::new(&path).unwrap());
+
+    let workers: Vec<_> = (0..WORKERS)
+        .map(|worker| {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let storage = SqliteStorage::with_busy_timeout(&path, Duration::from_secs(30)).unwrap();
+                let mut storage: Box<dyn Storage> = Box::new(storage);
+                for n in 0..ISSUES_PER_WORKER {
+                    // Read, then write: the transaction must not fail when another connection wrote in between
+                    storage::with_transaction(&mut storage, |storage| -> Result<()> {
+                        let id = storage.generate_id("t")?;
+                        assert!(storage.get_issue(&id)?.is_none());
+                        storage.create_issue(&Issue::new(id.clone(), format!("{} {}", worker, n)), "stress")?;
+                        storage.add_comment(&id, "stress", "created")
+                    })
+                    .unwrap();
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    let storage = SqliteStorage::new(&path).unwrap();
+    assert_eq!(storage.search_issues("", &IssueFilter::default()).unwrap().len(), WORKERS * ISSUES_PER_WORKER);
+}
+
+#[test]
+fn busy_timeout_ends_in_locked_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("t.db");
+
+    let mut holder: Box<dyn Storage> = Box::new(SqliteStorage::new(&path).unwrap());
+    let mut waiter = SqliteStorage::with_busy_timeout(&path, Duration::from_millis(100)).unwrap();
+    holder.begin_transaction().unwrap();
+
+    let start = Instant::now();
+    assert!(matches!(waiter.begin_transaction(), Err(Error::Locked)));
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    // Reads don't need the write lock
+    assert!(waiter.get_issue("t-1").unwrap().is_none());
+
+    holder.commit_transaction().unwrap();
+    waiter.begin_transaction().unwrap();
+    waiter.commit_transaction().unwrap();
+
+    let lock_path = dir.path().join("t.lock");
+    let _held = storage::lock_file(&lock_path, Duration::from_millis(100)).unwrap();
+    assert!(matches!(storage::lock_file(&lock_path, Duration::from_millis(50)), Err(Error::Locked)));
+}
+
+#[test]
+fn read_only_commands_skip_the_write_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = tracer(dir.path()).args(["init", "--prefix", "t"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let output = tracer(dir.path()).args(["create", "First"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let mut holder: Box<dyn Storage> = Box::new(SqliteStorage::new(dir.path().join(".trace/t.db")).unwrap());
+    holder.begin_transaction().unwrap();
+
+    let output = tracer(dir.path()).env("TRACE_BUSY_TIMEOUT", "100").args(["list", "--json"]).output().unwrap();
+    assert!(output.status.success(), "list failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.stderr.is_empty(), "{}", String::from_utf8_lossy(&output.stderr));
+    let listed: Vec<Issue> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(listed.len(), 1);
+
+    let output = tracer(dir.path()).env("TRACE_BUSY_TIMEOUT", "100").args(["create", "Second"]).output().unwrap();
+    assert!(!output.status.success(), "writes still wait for the lock");
+
+    holder.commit_transaction().unwrap();
+}
+
+#[test]
+fn losing_a_claim_race_is_a_claim_conflict() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = tracer(dir.path()).args(["init", "--prefix", "t"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let output = tracer(dir.path()).args(["create", "Contested"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let claim = |actor: &str| {
+        tracer(dir.path()).args(["--actor", actor, "update", "t-1", "--status", "in_progress", "--json"]).output().unwrap()
+    };
+    assert!(claim("agent-1").status.success());
+    assert!(claim("agent-1").status.success(), "claiming again is harmless");
+
+    let output = claim("agent-2");
+    assert_eq!(output.status.code(), Some(11));
+    let error: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(error["error"]["code"], "claim_conflict");
+    assert_eq!(error["error"]["issue_id"], "t-1");
+
+    let storage = SqliteStorage::new(dir.path().join(".trace/t.db")).unwrap();
+    assert_eq!(storage.get_issue("t-1").unwrap().unwrap().assignee, "agent-1");
+}
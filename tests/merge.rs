@@ -0,0 +1,135 @@
+//! `tracer merge`: re-pointing dependency edges and refusing merges that close a cycle.
+
+use std::process::{Command, Output};
+
+struct Repo {
+    dir: tempfile::TempDir,
+}
+
+impl Repo {
+    fn new(issues: usize) -> Self {
+        let repo = Repo { dir: tempfile::tempdir().unwrap() };
+        repo.run(&["init", "--prefix", "t"]);
+        for n in 1..=issues {
+            repo.run(&["create", &format!("Issue {}", n)]);
+        }
+        repo
+    }
+
+    fn tracer(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_tracer"))
+            .args(args)
+            .current_dir(self.dir.path())
+            .env("TRACE_DB", self.dir.path().join(".trace/t.db"))
+            .env("TRACE_ACTOR", "tester")
+            .output()
+            .unwrap()
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        let output = self.tracer(args);
+        assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        output
+    }
+
+    fn depend(&self, from: &str, to: &str, dep_type: &str) {
+        self.run(&["dep", "add", from, to, "--type", dep_type]);
+    }
+
+    /// Every dependency edge as (from, to, type), sorted
+    fn edges(&self) -> Vec<(String, String, String)> {
+        let output = self.run(&["export"]);
+        let mut edges: Vec<(String, String, String)> = String::from_utf8(output.stdout).unwrap()
+            .lines()
+            .flat_map(|line| {
+                let issue: serde_json::Value = serde_json::from_str(line).unwrap();
+                issue["dependencies"].as_array().cloned().unwrap_or_default()
+            })
+            .map(|dep| (
+                dep["issue_id"].as_str().unwrap().to_string(),
+                dep["depends_on_id"].as_str().unwrap().to_string(),
+                dep["type"].as_str().unwrap().to_string(),
+            ))
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    fn status(&self, id: &str) -> String {
+        let output = self.run(&["--json", "show", id]);
+        let issue: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        issue["status"].as_str().unwrap().to_string()
+    }
+
+    fn merge(&self, sources: &[&str], target: &str) -> serde_json::Value {
+        let mut args = vec!["--json", "merge"];
+        args.extend_from_slice(sources);
+        args.extend_from_slice(&["--into", target]);
+        serde_json::from_slice(&self.run(&args).stdout).unwrap()
+    }
+}
+
+fn edge(from: &str, to: &str, dep_type: &str) -> (String, String, String) {
+    (from.to_string(), to.to_string(), dep_type.to_string())
+}
+
+#[test]
+fn incoming_and_outgoing_edges_move_to_the_target() {
+    let repo = Repo::new(4);
+    repo.depend("t-2", "t-3", "blocks"); // outgoing from the duplicate
+    repo.depend("t-4", "t-2", "related"); // incoming to the duplicate
+
+    let result = repo.merge(&["t-2"], "t-1");
+    assert_eq!(result["merged"], serde_json::json!(["t-2"]));
+    assert_eq!(result["dependencies_moved"], 2);
+
+    assert_eq!(repo.edges(), [
+        edge("t-1", "t-3", "blocks"),
+        edge("t-2", "t-1", "duplicate-of"),
+        edge("t-4", "t-1", "related"),
+    ]);
+    assert_eq!(repo.status("t-2"), "closed");
+    assert_eq!(repo.status("t-1"), "open");
+}
+
+#[test]
+fn edges_the_target_already_has_are_dropped() {
+    let repo = Repo::new(4);
+    repo.depend("t-1", "t-3", "blocks");
+    repo.depend("t-2", "t-3", "related"); // clashes with t-1 -> t-3
+    repo.depend("t-4", "t-1", "blocks");
+    repo.depend("t-4", "t-2", "related"); // clashes with t-4 -> t-1
+    repo.depend("t-2", "t-1", "blocks"); // between the merged issues
+
+    let result = repo.merge(&["t-2"], "t-1");
+    assert_eq!(result["dependencies_moved"], 0);
+
+    // The target's own edges keep their type; the duplicate keeps only its duplicate-of link
+    assert_eq!(repo.edges(), [
+        edge("t-1", "t-3", "blocks"),
+        edge("t-2", "t-1", "duplicate-of"),
+        edge("t-4", "t-1", "blocks"),
+    ]);
+}
+
+#[test]
+fn merges_that_would_create_a_cycle_are_refused() {
+    let repo = Repo::new(3);
+    repo.depend("t-1", "t-3", "blocks");
+    repo.depend("t-3", "t-2", "blocks");
+    let before = repo.edges();
+
+    // t-3 -> t-2 would become t-3 -> t-1, closing t-1 -> t-3 -> t-1
+    let output = repo.tracer(&["--json", "merge", "t-2", "--into", "t-1"]);
+    assert_eq!(output.status.code(), Some(6));
+    let error: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(error["error"]["code"], "cycle");
+
+    assert_eq!(repo.edges(), before);
+    assert_eq!(repo.status("t-2"), "open");
+
+    // Merging the other way round is just as circular; merging into t-3 isn't
+    assert_eq!(repo.tracer(&["merge", "t-1", "--into", "t-2"]).status.code(), Some(6));
+    repo.merge(&["t-2"], "t-3");
+    assert_eq!(repo.edges(), [edge("t-1", "t-3", "blocks"), edge("t-2", "t-3", "duplicate-of")]);
+}
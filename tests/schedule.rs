@@ -0,0 +1,106 @@
+//! Recurrence schedules (`--every` intervals and cron expressions) and the
+//! periods `tracer recur run` creates issues for.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use tracer::schedule::{parse_interval, Schedule};
+
+fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+}
+
+#[test]
+fn intervals_parse_and_round_trip() {
+    assert_eq!(parse_interval("90m").unwrap(), Schedule::Every { minutes: 90 });
+    assert_eq!(parse_interval("12h").unwrap(), Schedule::Every { minutes: 12 * 60 });
+    assert_eq!(parse_interval("1d").unwrap(), Schedule::Every { minutes: 24 * 60 });
+    let fortnight: Schedule = "every 2w".parse().unwrap();
+    assert_eq!(fortnight, Schedule::Every { minutes: 2 * 7 * 24 * 60 });
+    assert_eq!(fortnight.to_string(), "every 2w");
+    assert_eq!(parse_interval("120m").unwrap().to_string(), "every 2h");
+
+    for bad in ["0d", "-1d", "5x", "d", "", "9999999999999999w"] {
+        assert!(parse_interval(bad).is_err(), "{:?} should be rejected", bad);
+    }
+}
+
+#[test]
+fn cron_expressions_parse_and_round_trip() {
+    let schedule: Schedule = "0 9 * * MON".parse().unwrap();
+    assert_eq!(schedule.to_string(), "0 9 * * MON");
+    assert!(matches!(schedule, Schedule::Cron(_)));
+    assert!("*/15 8-17 1,15 JAN-JUN 1-5".parse::<Schedule>().is_ok());
+
+    for bad in ["0 9 * *", "61 * * * *", "0 24 * * *", "*/0 * * * *", "0 9 * * FUNDAY", "0 9 32 * *", "5-1 * * * *"] {
+        assert!(bad.parse::<Schedule>().is_err(), "{:?} should be rejected", bad);
+    }
+}
+
+#[test]
+fn interval_occurrences_count_from_the_start() {
+    let start = at(2025, 1, 1, 0, 0);
+    let daily = parse_interval("1d").unwrap();
+
+    assert_eq!(daily.latest_occurrence(start, start - Duration::minutes(1)), None);
+    assert_eq!(daily.latest_occurrence(start, start), Some(start));
+    assert_eq!(daily.latest_occurrence(start, at(2025, 1, 1, 23, 59)), Some(start));
+    assert_eq!(daily.latest_occurrence(start, at(2025, 1, 2, 0, 0)), Some(at(2025, 1, 2, 0, 0)));
+    assert_eq!(daily.latest_occurrence(start, at(2025, 3, 10, 12, 0)), Some(at(2025, 3, 10, 0, 0)));
+}
+
+#[test]
+fn cron_occurrences_fall_on_matching_times() {
+    // 2025-01-06 is a Monday
+    let weekly: Schedule = "0 9 * * MON".parse().unwrap();
+    let start = at(2024, 1, 1, 0, 0);
+    assert_eq!(weekly.latest_occurrence(start, at(2025, 1, 6, 9, 0)), Some(at(2025, 1, 6, 9, 0)));
+    assert_eq!(weekly.latest_occurrence(start, at(2025, 1, 6, 8, 59)), Some(at(2024, 12, 30, 9, 0)));
+    assert_eq!(weekly.latest_occurrence(start, at(2025, 1, 10, 12, 0)), Some(at(2025, 1, 6, 9, 0)));
+
+    // Nothing before the start counts
+    assert_eq!(weekly.latest_occurrence(at(2025, 1, 7, 0, 0), at(2025, 1, 10, 12, 0)), None);
+
+    // 7 is Sunday too
+    let sunday: Schedule = "30 18 * * 7".parse().unwrap();
+    assert_eq!(sunday.latest_occurrence(start, at(2025, 1, 6, 9, 0)), Some(at(2025, 1, 5, 18, 30)));
+
+    // With both day fields restricted, either one matches (the 1st, or any Friday)
+    let either: Schedule = "0 0 1 * FRI".parse().unwrap();
+    assert_eq!(either.latest_occurrence(start, at(2025, 1, 2, 12, 0)), Some(at(2025, 1, 1, 0, 0)));
+    assert_eq!(either.latest_occurrence(start, at(2025, 1, 4, 12, 0)), Some(at(2025, 1, 3, 0, 0)));
+}
+
+#[test]
+fn recur_run_creates_one_issue_per_period() {
+    let dir = tempfile::tempdir().unwrap();
+    let tracer = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_tracer"))
+            .args(args)
+            .current_dir(dir.path())
+            .env("TRACE_DB", dir.path().join(".trace/t.db"))
+            .env("TRACE_ACTOR", "tester")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap_or_default()
+    };
+
+    tracer(&["init", "--prefix", "t"]);
+    tracer(&["recur", "add", "standup", "--every", "1d", "--title", "Standup", "--start", "2025-01-01", "-l", "team"]);
+
+    let first = tracer(&["recur", "run", "--json"]);
+    assert_eq!(first.as_array().unwrap().len(), 1);
+    assert_eq!(first[0]["issue_id"], "t-1");
+    let again = tracer(&["recur", "run", "--json"]);
+    assert!(again.as_array().unwrap().is_empty(), "the period was already created: {}", again);
+
+    // A definition that never claimed the period (as in another clone) still finds the issue by its external_ref
+    tracer(&["recur", "remove", "standup"]);
+    tracer(&["recur", "add", "standup", "--every", "1d", "--title", "Standup", "--start", "2025-01-01"]);
+    let recreated = tracer(&["recur", "run", "--json"]);
+    assert!(recreated.as_array().unwrap().is_empty(), "{}", recreated);
+
+    let issues = tracer(&["list", "--json"]);
+    let issues = issues.as_array().unwrap();
+    assert_eq!(issues.len(), 1);
+    assert!(issues[0]["external_ref"].as_str().unwrap().starts_with("recur:standup@"));
+}
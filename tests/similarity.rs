@@ -0,0 +1,89 @@
+//! TF-IDF duplicate detection behind `tracer create` and `tracer duplicates`.
+
+use tracer::similarity::{normalize_tokens, SimilarityIndex, DEFAULT_THRESHOLD};
+use tracer::*;
+
+fn issue(id: &str, title: &str, description: &str) -> Issue {
+    Issue { description: description.to_string(), ..Issue::new(id, title) }
+}
+
+#[test]
+fn tokens_are_normalized() {
+    assert_eq!(normalize_tokens("The Tests, for the login-page!"), ["test", "login", "page"]);
+    // Single characters go; short words and double-s endings keep their "s"
+    assert_eq!(normalize_tokens("a bus class x1"), ["bus", "class", "x1"]);
+    assert!(normalize_tokens("it is on to").is_empty());
+}
+
+#[test]
+fn similar_issues_are_ranked_above_the_threshold() {
+    let index = SimilarityIndex::new(&[
+        issue("t-1", "Login page crashes on submit", "Stack trace attached"),
+        issue("t-2", "Login page crashes", ""),
+        issue("t-3", "Update documentation for API", "Describe the endpoints"),
+    ]);
+
+    let matches = index.find_similar("Login pages crashes", "", 0.0);
+    let ids: Vec<&str> = matches.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(ids, ["t-2", "t-1", "t-3"]);
+    assert!(matches.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    assert!((matches[0].1 - 1.0).abs() < 1e-9, "same tokens score 1: {}", matches[0].1);
+    assert_eq!(matches[2].1, 0.0);
+
+    let likely: Vec<String> = index.find_similar("Login pages crashes", "", DEFAULT_THRESHOLD)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    assert_eq!(likely, ["t-2", "t-1"]);
+    assert!(index.find_similar("Login pages crashes", "", 1.01).is_empty());
+}
+
+#[test]
+fn clusters_join_issues_transitively() {
+    let texts = [
+        ("t-1", "alpha beta gamma delta"),
+        ("t-2", "gamma delta epsilon zeta"),
+        ("t-3", "epsilon zeta theta iota"),
+        ("t-4", "kappa lambda"),
+    ];
+    let index = SimilarityIndex::new(&texts.map(|(id, title)| issue(id, title, "")));
+    let score = |a: usize, b: &str| {
+        index.find_similar(texts[a].1, "", 0.0).into_iter().find(|(id, _)| id == b).unwrap().1
+    };
+
+    // t-1 and t-3 share nothing, but each is close to t-2
+    let threshold = score(0, "t-2").min(score(1, "t-3"));
+    assert!(threshold > 0.0);
+    assert_eq!(score(0, "t-3"), 0.0);
+
+    let clusters = index.clusters(threshold);
+    assert_eq!(clusters.len(), 1);
+    let mut ids: Vec<&str> = clusters[0].iter().map(|(id, _)| id.as_str()).collect();
+    ids.sort();
+    assert_eq!(ids, ["t-1", "t-2", "t-3"]);
+
+    // Above every pairwise score nothing clusters
+    assert!(index.clusters(1.01).is_empty());
+}
+
+#[test]
+fn no_duplicates_refuses_with_a_conflict() {
+    let dir = tempfile::tempdir().unwrap();
+    let tracer = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_tracer"))
+            .args(args)
+            .current_dir(dir.path())
+            .env("TRACE_DB", dir.path().join(".trace/t.db"))
+            .env("TRACE_ACTOR", "tester")
+            .output()
+            .unwrap()
+    };
+    assert!(tracer(&["init", "--prefix", "t"]).status.success());
+    assert!(tracer(&["create", "Login page crashes on submit"]).status.success());
+
+    let output = tracer(&["create", "Login page crashes on submit", "--no-duplicates", "--json"]);
+    assert_eq!(output.status.code(), Some(4));
+    let error: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(error["error"]["code"], "conflict");
+    assert!(error["error"]["message"].as_str().unwrap().contains("t-1"));
+}
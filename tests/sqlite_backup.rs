@@ -0,0 +1,95 @@
+//! Online backups of SQLite databases and the rotation of automatic ones.
+
+use tracer::backup::{backup_database, create_automatic, KEEP_AUTOMATIC};
+use tracer::storage::sqlite::SqliteStorage;
+use tracer::*;
+
+#[test]
+fn backup_is_a_working_copy() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("t.db");
+    let mut storage = SqliteStorage::new(&path).unwrap();
+    storage.set_config("prefix", "t").unwrap();
+
+    // Taken while the source is open, as it would be with other agents running
+    let dest = dir.path().join("copy.db");
+    backup_database(&path, &dest).unwrap();
+
+    let copy = SqliteStorage::new(&dest).unwrap();
+    assert_eq!(copy.get_config("prefix").unwrap().as_deref(), Some("t"));
+}
+
+#[test]
+fn automatic_backups_rotate_per_kind() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("t.db");
+    assert!(create_automatic(&path, "pre-import").unwrap().is_none());
+    drop(SqliteStorage::new(&path).unwrap());
+
+    for _ in 0..KEEP_AUTOMATIC + 2 {
+        create_automatic(&path, "pre-import").unwrap();
+    }
+    let newest = create_automatic(&path, "pre-restore").unwrap().unwrap();
+
+    let backups_dir = find_backups_dir(&path);
+    let mut names: Vec<String> = std::fs::read_dir(&backups_dir).unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(names.len(), KEEP_AUTOMATIC + 1);
+    assert!(names[..KEEP_AUTOMATIC].iter().all(|name| name.starts_with("t-pre-import-")));
+    assert_eq!(names[KEEP_AUTOMATIC], newest.file_name().unwrap().to_string_lossy());
+}
+
+#[test]
+fn restore_brings_back_issues_archived_since_the_backup() {
+    let dir = tempfile::tempdir().unwrap();
+    let tracer = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_tracer"))
+            .args(args)
+            .current_dir(dir.path())
+            .env("TRACE_DB", dir.path().join(".trace/t.db"))
+            .env("TRACE_ACTOR", "tester")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    tracer(&["init", "--prefix", "t"]);
+    tracer(&["create", "Old"]);
+    tracer(&["close", "t-1"]);
+    let snapshot = dir.path().join("snapshot.db");
+    tracer(&["backup", snapshot.to_str().unwrap()]);
+    tracer(&["archive", "--closed-before", "2999-01-01"]);
+
+    tracer(&["restore", snapshot.to_str().unwrap()]);
+    let archive = std::fs::read_to_string(dir.path().join(".trace/archive.jsonl")).unwrap();
+    assert!(archive.is_empty(), "archive.jsonl is rewritten from the restored database");
+
+    // The next command's auto-import must not archive the restored issue again
+    let issue: Issue = serde_json::from_str(&tracer(&["show", "t-1", "--json"])).unwrap();
+    assert_eq!(issue.status, Status::Closed);
+    let listed: Vec<Issue> = serde_json::from_str(&tracer(&["list", "--json"])).unwrap();
+    assert_eq!(listed.len(), 1);
+}
+
+#[test]
+fn backup_refuses_a_db_file_next_to_the_database() {
+    let dir = tempfile::tempdir().unwrap();
+    let tracer = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_tracer"))
+            .args(args)
+            .current_dir(dir.path())
+            .env("TRACE_DB", dir.path().join(".trace/t.db"))
+            .env("TRACE_ACTOR", "tester")
+            .output()
+            .unwrap()
+    };
+    assert!(tracer(&["init", "--prefix", "t"]).status.success());
+
+    // Discovery would open it instead of t.db
+    assert!(!tracer(&["backup", ".trace/copy.db"]).status.success());
+    assert!(!dir.path().join(".trace/copy.db").exists());
+    assert!(tracer(&["backup", ".trace/backups/copy.db"]).status.success());
+}
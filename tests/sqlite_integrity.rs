@@ -0,0 +1,121 @@
+//! `SqliteStorage::check_integrity` and `repair_integrity`, behind `tracer doctor`.
+
+use rusqlite::Connection;
+use tracer::storage::sqlite::SqliteStorage;
+use tracer::*;
+
+fn new_issue(storage: &mut SqliteStorage) {
+    let issue = Issue::new(storage.generate_id("t").unwrap(), "Issue");
+    storage.create_issue(&issue, "tester").unwrap();
+}
+
+#[test]
+fn broken_invariants_are_found_and_repaired() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("t.db");
+    let mut storage = SqliteStorage::new(&path).unwrap();
+    for _ in 0..3 {
+        new_issue(&mut storage);
+    }
+    assert!(storage.check_integrity().unwrap().is_empty());
+
+    // What a careless import or an older tracer could leave behind
+    let conn = Connection::open(&path).unwrap();
+    conn.execute_batch(
+        "PRAGMA foreign_keys = OFF;
+         INSERT INTO dependencies VALUES ('t-1', 't-9', 'blocks', '2025-01-01T00:00:00Z', 'x');
+         INSERT INTO labels VALUES ('t-8', 'ghost');
+         UPDATE issues SET status = 'closed', closed_at = NULL WHERE id = 't-1';
+         UPDATE issues SET closed_at = updated_at WHERE id = 't-2';
+         UPDATE issue_counters SET last_id = 1;",
+    )
+    .unwrap();
+    drop(conn);
+
+    let findings = storage.check_integrity().unwrap();
+    let mut checks: Vec<&str> = findings.iter().map(|finding| finding.check.as_str()).collect();
+    checks.sort();
+    assert_eq!(checks, ["closed_at", "closed_at", "issue_counter", "orphaned_row", "orphaned_row"]);
+    assert!(findings.iter().all(|finding| finding.fixable));
+
+    assert!(storage.repair_integrity().unwrap() > 0);
+    assert!(storage.check_integrity().unwrap().is_empty());
+    assert!(storage.get_issue("t-1").unwrap().unwrap().closed_at.is_some());
+    assert!(storage.get_issue("t-2").unwrap().unwrap().closed_at.is_none());
+    assert!(storage.get_dependency_records("t-1").unwrap().is_empty());
+    assert_eq!(storage.generate_id("t").unwrap(), "t-4");
+    assert!(storage.get_dirty_issues().unwrap().contains(&"t-1".to_string()));
+}
+
+#[test]
+fn unreadable_recurrence_labels_are_a_schema_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("t.db");
+    let mut storage = SqliteStorage::new(&path).unwrap();
+    storage.save_recurrence(&Recurrence {
+        name: "standup".to_string(),
+        schedule: tracer::schedule::parse_interval("1d").unwrap(),
+        title: "Standup".to_string(),
+        description: String::new(),
+        issue_type: IssueType::Chore,
+        priority: 2,
+        labels: vec!["team".to_string()],
+        assignee: String::new(),
+        start_at: chrono::Utc::now(),
+        last_period: None,
+        created_at: chrono::Utc::now(),
+    })
+    .unwrap();
+
+    let conn = Connection::open(&path).unwrap();
+    conn.execute("UPDATE recurrences SET labels = 'team'", []).unwrap();
+    drop(conn);
+
+    assert!(matches!(storage.get_recurrence("standup"), Err(Error::Schema(_))));
+}
+
+#[test]
+fn doctor_fails_while_problems_remain() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join(".trace/t.db");
+    let tracer = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_tracer"))
+            .args(args)
+            .current_dir(dir.path())
+            .env("TRACE_DB", &db_path)
+            .env("TRACE_ACTOR", "tester")
+            .output()
+            .unwrap()
+    };
+    assert!(tracer(&["init", "--prefix", "t"]).status.success());
+    assert!(tracer(&["create", "First"]).status.success());
+    assert!(tracer(&["doctor"]).status.success());
+
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute("UPDATE issues SET closed_at = updated_at", []).unwrap();
+    drop(conn);
+    assert_eq!(tracer(&["doctor", "--json"]).status.code(), Some(7));
+    assert!(tracer(&["doctor", "--fix"]).status.success(), "everything found was fixed");
+
+    // An unreadable line can't be fixed without losing data
+    let jsonl_path = dir.path().join(".trace/issues.jsonl");
+    let mut jsonl = std::fs::read_to_string(&jsonl_path).unwrap();
+    jsonl.push_str("{not json\n");
+    std::fs::write(&jsonl_path, jsonl).unwrap();
+    assert_eq!(tracer(&["doctor", "--fix"]).status.code(), Some(7));
+}
+
+#[test]
+fn status_changes_through_update_leave_nothing_to_repair() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut storage = SqliteStorage::new(dir.path().join("t.db")).unwrap();
+    new_issue(&mut storage);
+    new_issue(&mut storage);
+
+    let status = |status: Status| storage::IssueUpdates { status: Some(status), ..Default::default() };
+    storage.update_issue("t-1", &status(Status::Closed), "tester").unwrap();
+    storage.close_issue("t-2", "done", "tester").unwrap();
+    storage.update_issue("t-2", &status(Status::Open), "tester").unwrap();
+
+    assert!(storage.check_integrity().unwrap().is_empty());
+}
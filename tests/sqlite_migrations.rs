@@ -0,0 +1,72 @@
+//! Schema versioning for the SQLite backend: fresh databases, databases from
+//! before versioning, and databases from a newer tracer.
+
+use rusqlite::Connection;
+use tracer::storage::sqlite::{SqliteStorage, SCHEMA_VERSION};
+use tracer::*;
+
+#[test]
+fn new_database_is_at_latest_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = SqliteStorage::new(dir.path().join("t.db")).unwrap();
+
+    assert_eq!(storage.schema_version().unwrap(), SCHEMA_VERSION);
+    assert!(storage.pending_migrations().unwrap().is_empty());
+}
+
+#[test]
+fn unversioned_database_is_migrated_in_place() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("t.db");
+
+    // The original schema: no version, no later columns or tables
+    let conn = Connection::open(&path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE issues (
+            id TEXT PRIMARY KEY, title TEXT NOT NULL, description TEXT NOT NULL DEFAULT '',
+            design TEXT NOT NULL DEFAULT '', acceptance_criteria TEXT NOT NULL DEFAULT '',
+            notes TEXT NOT NULL DEFAULT '', status TEXT NOT NULL DEFAULT 'open',
+            priority INTEGER NOT NULL DEFAULT 2, issue_type TEXT NOT NULL DEFAULT 'task',
+            assignee TEXT, estimated_minutes INTEGER, created_at DATETIME NOT NULL,
+            updated_at DATETIME NOT NULL, closed_at DATETIME
+        );
+        INSERT INTO issues (id, title, created_at, updated_at)
+            VALUES ('t-4', 'Legacy issue', '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z');",
+    )
+    .unwrap();
+    drop(conn);
+
+    let unmigrated = SqliteStorage::open_unmigrated(&path).unwrap();
+    assert_eq!(unmigrated.schema_version().unwrap(), 0);
+    assert_eq!(unmigrated.pending_migrations().unwrap().len(), SCHEMA_VERSION as usize);
+    drop(unmigrated);
+
+    let mut storage = SqliteStorage::new(&path).unwrap();
+    assert_eq!(storage.schema_version().unwrap(), SCHEMA_VERSION);
+    assert_eq!(storage.get_issue("t-4").unwrap().unwrap().title, "Legacy issue");
+    assert_eq!(storage.generate_id("t").unwrap(), "t-5");
+    assert!(storage.migrate().unwrap().is_empty());
+
+    // The database was backed up before it was migrated
+    let backups: Vec<_> = std::fs::read_dir(find_backups_dir(&path)).unwrap().collect();
+    assert_eq!(backups.len(), 1);
+}
+
+#[test]
+fn newer_database_is_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("t.db");
+    drop(SqliteStorage::new(&path).unwrap());
+
+    let conn = Connection::open(&path).unwrap();
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+    drop(conn);
+
+    for result in [SqliteStorage::new(&path), SqliteStorage::open_unmigrated(&path)] {
+        match result {
+            Err(Error::Schema(message)) => assert!(message.contains("upgrade tracer"), "{}", message),
+            Err(err) => panic!("expected a schema error, got {}", err),
+            Ok(_) => panic!("opened a database from a newer tracer"),
+        }
+    }
+}
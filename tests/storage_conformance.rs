@@ -0,0 +1,928 @@
+//! Scenarios every `Storage` backend must pass identically. Each scenario is a
+//! plain function over `Box<dyn Storage>`; the `conformance!` macro runs all of
+//! them once per backend.
+
+use chrono::{Duration, Utc};
+use tempfile::TempDir;
+use tracer::storage::jsonl::JsonlStorage;
+use tracer::storage::memory::InMemoryStorage;
+use tracer::storage::sqlite::SqliteStorage;
+use tracer::storage::{with_transaction, IssueUpdates};
+use tracer::*;
+
+const ACTOR: &str = "tester";
+
+fn sqlite_backend() -> (Option<TempDir>, Box<dyn Storage>) {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = SqliteStorage::new(dir.path().join("tracer.db")).unwrap();
+    (Some(dir), Box::new(storage))
+}
+
+fn memory_backend() -> (Option<TempDir>, Box<dyn Storage>) {
+    (None, Box::new(InMemoryStorage::new()))
+}
+
+fn jsonl_backend() -> (Option<TempDir>, Box<dyn Storage>) {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = JsonlStorage::open(dir.path().join("t.json")).unwrap();
+    (Some(dir), Box::new(storage))
+}
+
+macro_rules! conformance {
+    ($($scenario:ident),* $(,)?) => {
+        mod sqlite {
+            $(
+                #[test]
+                fn $scenario() {
+                    let (_dir, mut storage) = super::sqlite_backend();
+                    super::$scenario(&mut storage);
+                }
+            )*
+        }
+
+        mod memory {
+            $(
+                #[test]
+                fn $scenario() {
+                    let (_dir, mut storage) = super::memory_backend();
+                    super::$scenario(&mut storage);
+                }
+            )*
+        }
+
+        mod jsonl {
+            $(
+                #[test]
+                fn $scenario() {
+                    let (_dir, mut storage) = super::jsonl_backend();
+                    super::$scenario(&mut storage);
+                }
+            )*
+        }
+    };
+}
+
+conformance!(
+    create_and_get,
+    duplicate_issue_conflicts,
+    missing_issue_errors,
+    update_records_status_change,
+    in_progress_tracks_work,
+    close_sets_closed_at,
+    status_updates_track_closed_at,
+    workflow_rules_apply,
+    ready_and_blocked,
+    custom_done_status_unblocks,
+    dependency_rules,
+    cycle_detection,
+    dependency_tree,
+    labels_and_search,
+    sort_and_paginate,
+    events_and_comments,
+    dirty_tracking,
+    transactions_roll_back,
+    id_generation,
+    statistics,
+    milestones,
+    recurrences,
+    stale_issues,
+    config_and_metadata,
+    archive_closed_issues,
+    compact_events_keeps_comments,
+);
+
+#[test]
+fn jsonl_store_persists_across_opens() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("t.json");
+
+    let (a, b) = {
+        let mut storage: Box<dyn Storage> = Box::new(JsonlStorage::open(&path).unwrap());
+        storage.set_config("prefix", "t").unwrap();
+        let a = new_issue(&mut storage, "A", 1);
+        let b = new_issue(&mut storage, "B", 2);
+        depend(&mut storage, &b, &a, DependencyType::Blocks).unwrap();
+        storage.add_label(&a, "ui", ACTOR).unwrap();
+        storage.add_comment(&a, ACTOR, "note").unwrap();
+        set_status(&mut storage, &a, Status::InProgress).unwrap();
+
+        // Rolled-back work is never written
+        let result: Result<()> = with_transaction(&mut storage, |storage| {
+            new_issue(storage, "Discarded", 2);
+            storage.close_issue("t-404", "nope", ACTOR)
+        });
+        assert!(result.is_err());
+        (a, b)
+    };
+
+    let issues = std::fs::read_to_string(dir.path().join("issues.jsonl")).unwrap();
+    assert_eq!(issues.lines().count(), 2);
+
+    let mut storage: Box<dyn Storage> = Box::new(JsonlStorage::open(&path).unwrap());
+    assert_eq!(storage.get_config("prefix").unwrap().as_deref(), Some("t"));
+    assert_eq!(storage.get_issue(&a).unwrap().unwrap().status, Status::InProgress);
+    assert_eq!(ids(&storage.get_dependencies(&b).unwrap()), vec![a.clone()]);
+    assert_eq!(storage.get_labels(&a).unwrap(), vec!["ui".to_string()]);
+    assert_eq!(storage.get_work_logs(&a).unwrap().len(), 1);
+    assert_eq!(
+        event_types(storage.as_ref(), &a),
+        vec![EventType::Created, EventType::LabelAdded, EventType::Commented, EventType::StatusChanged, EventType::Updated]
+    );
+    assert!(storage.get_dirty_issues().unwrap().is_empty());
+    assert_eq!(storage.generate_id("t").unwrap(), "t-3");
+}
+
+#[test]
+fn jsonl_archive_persists_across_opens() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("t.json");
+
+    let (a, b) = {
+        let mut storage: Box<dyn Storage> = Box::new(JsonlStorage::open(&path).unwrap());
+        let a = new_issue(&mut storage, "A", 1);
+        let b = new_issue(&mut storage, "B", 2);
+        storage.add_label(&a, "ui", ACTOR).unwrap();
+        storage.close_issue(&a, "done", ACTOR).unwrap();
+        assert_eq!(storage.archive_issues(Utc::now() + Duration::minutes(1)).unwrap(), vec![a.clone()]);
+        (a, b)
+    };
+
+    let issues = std::fs::read_to_string(dir.path().join("issues.jsonl")).unwrap();
+    assert_eq!(issues.lines().count(), 1, "archived issues leave issues.jsonl");
+    let archive = std::fs::read_to_string(dir.path().join("archive.jsonl")).unwrap();
+    assert_eq!(archive.lines().count(), 1);
+
+    let mut storage: Box<dyn Storage> = Box::new(JsonlStorage::open(&path).unwrap());
+    assert!(storage.get_issue(&a).unwrap().is_none());
+    assert!(storage.get_issue(&b).unwrap().is_some());
+    let archived = storage.get_archived_issue(&a).unwrap().unwrap();
+    assert_eq!(archived.labels, vec!["ui".to_string()]);
+    assert!(archived.events.iter().any(|e| e.event_type == EventType::Closed));
+    assert_eq!(storage.generate_id("t").unwrap(), "t-3", "archived IDs are never reused");
+}
+
+fn new_issue(storage: &mut Box<dyn Storage>, title: &str, priority: i32) -> String {
+    let issue = Issue { priority, ..Issue::new(storage.generate_id("t").unwrap(), title) };
+    storage.create_issue(&issue, ACTOR).unwrap();
+    issue.id
+}
+
+fn depend(storage: &mut Box<dyn Storage>, from: &str, to: &str, dep_type: DependencyType) -> Result<()> {
+    let dep = Dependency {
+        issue_id: from.to_string(),
+        depends_on_id: to.to_string(),
+        dep_type,
+        created_at: Utc::now(),
+        created_by: ACTOR.to_string(),
+    };
+    storage.add_dependency(&dep, ACTOR)
+}
+
+fn set_status(storage: &mut Box<dyn Storage>, id: &str, status: Status) -> Result<()> {
+    let updates = IssueUpdates { status: Some(status), ..Default::default() };
+    storage.update_issue(id, &updates, ACTOR)
+}
+
+fn ids(issues: &[Issue]) -> Vec<String> {
+    issues.iter().map(|i| i.id.clone()).collect()
+}
+
+fn sorted(mut ids: Vec<String>) -> Vec<String> {
+    ids.sort();
+    ids
+}
+
+fn event_types(storage: &dyn Storage, id: &str) -> Vec<EventType> {
+    let mut events = storage.get_events(id, 100).unwrap();
+    events.reverse();
+    events.into_iter().map(|e| e.event_type).collect()
+}
+
+fn create_and_get(storage: &mut Box<dyn Storage>) {
+    let id = new_issue(storage, "First", 1);
+    let issue = storage.get_issue(&id).unwrap().unwrap();
+    assert_eq!(issue.title, "First");
+    assert_eq!(issue.status, Status::Open);
+    assert_eq!(issue.priority, 1);
+    assert!(storage.get_issue("t-999").unwrap().is_none());
+
+    let events = storage.get_events(&id, 10).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, EventType::Created);
+    assert_eq!(events[0].new_value.as_deref(), Some("open"));
+    assert_eq!(events[0].actor, ACTOR);
+}
+
+fn duplicate_issue_conflicts(storage: &mut Box<dyn Storage>) {
+    let id = new_issue(storage, "Original", 2);
+    let mut copy = storage.get_issue(&id).unwrap().unwrap();
+    copy.title = "Copy".to_string();
+    let err = storage.create_issue(&copy, ACTOR).unwrap_err();
+    assert!(matches!(err, Error::Conflict(_)), "{:?}", err);
+    assert_eq!(err.to_string(), format!("Issue {} already exists", id));
+    assert_eq!(storage.get_issue(&id).unwrap().unwrap().title, "Original");
+
+    copy.id = "t-100".to_string();
+    copy.title = String::new();
+    assert!(matches!(storage.create_issue(&copy, ACTOR), Err(Error::Validation(_))));
+    assert!(storage.get_issue("t-100").unwrap().is_none());
+}
+
+fn missing_issue_errors(storage: &mut Box<dyn Storage>) {
+    let id = new_issue(storage, "Exists", 2);
+
+    let err = storage.close_issue("t-404", "done", ACTOR).unwrap_err();
+    assert!(matches!(err, Error::NotFound { .. }), "{:?}", err);
+    assert_eq!(err.issue_id(), Some("t-404"));
+    assert!(matches!(set_status(storage, "t-404", Status::Closed), Err(Error::NotFound { .. })));
+
+    let title = IssueUpdates { title: Some("x".to_string()), ..Default::default() };
+    let err = storage.update_issue("t-404", &title, ACTOR).unwrap_err();
+    assert!(matches!(err, Error::NotFound { .. }), "{:?}", err);
+    let err = storage.add_comment("t-404", ACTOR, "hi").unwrap_err();
+    assert!(matches!(err, Error::NotFound { .. }), "{:?}", err);
+    assert_eq!(err.issue_id(), Some("t-404"));
+
+    // Writes that only reference a missing issue fail like a foreign key would
+    assert!(matches!(storage.add_label("t-404", "x", ACTOR), Err(Error::Validation(_))));
+    assert!(matches!(depend(storage, &id, "t-404", DependencyType::Blocks), Err(Error::Validation(_))));
+
+    assert!(storage.get_dependency_records(&id).unwrap().is_empty());
+    assert_eq!(storage.get_dirty_issues().unwrap(), vec![id]);
+}
+
+fn update_records_status_change(storage: &mut Box<dyn Storage>) {
+    let id = new_issue(storage, "Before", 2);
+    let before = storage.get_issue(&id).unwrap().unwrap();
+
+    let updates = IssueUpdates {
+        title: Some("After".to_string()),
+        status: Some(Status::Blocked),
+        priority: Some(0),
+        assignee: Some("alice".to_string()),
+        estimated_minutes: Some(Some(90)),
+        due_at: Some(Some(Utc::now() + Duration::days(3))),
+        ..Default::default()
+    };
+    storage.update_issue(&id, &updates, ACTOR).unwrap();
+
+    let after = storage.get_issue(&id).unwrap().unwrap();
+    assert_eq!(after.title, "After");
+    assert_eq!(after.status, Status::Blocked);
+    assert_eq!(after.priority, 0);
+    assert_eq!(after.assignee, "alice");
+    assert_eq!(after.estimated_minutes, Some(90));
+    assert!(after.due_at.is_some());
+    assert!(after.updated_at >= before.updated_at);
+
+    let clear = IssueUpdates { estimated_minutes: Some(None), due_at: Some(None), ..Default::default() };
+    storage.update_issue(&id, &clear, ACTOR).unwrap();
+    let cleared = storage.get_issue(&id).unwrap().unwrap();
+    assert_eq!(cleared.estimated_minutes, None);
+    assert_eq!(cleared.due_at, None);
+
+    assert_eq!(
+        event_types(storage.as_ref(), &id),
+        vec![EventType::Created, EventType::StatusChanged, EventType::Updated, EventType::Updated]
+    );
+    let change = storage.get_events(&id, 10).unwrap().into_iter()
+        .find(|e| e.event_type == EventType::StatusChanged)
+        .unwrap();
+    assert_eq!(change.old_value.as_deref(), Some("open"));
+    assert_eq!(change.new_value.as_deref(), Some("blocked"));
+}
+
+fn in_progress_tracks_work(storage: &mut Box<dyn Storage>) {
+    let id = new_issue(storage, "Tracked", 2);
+    set_status(storage, &id, Status::InProgress).unwrap();
+    set_status(storage, &id, Status::InProgress).unwrap();
+
+    let logs = storage.get_work_logs(&id).unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].source, WorkLogSource::Auto);
+    assert_eq!(logs[0].actor, ACTOR);
+    assert!(logs[0].ended_at.is_none());
+
+    set_status(storage, &id, Status::Open).unwrap();
+    let logs = storage.get_work_logs(&id).unwrap();
+    assert!(logs[0].ended_at.is_some());
+    assert_eq!(logs[0].minutes, 0);
+
+    let manual = WorkLog {
+        issue_id: id.clone(),
+        actor: "bob".to_string(),
+        minutes: 30,
+        note: "review".to_string(),
+        started_at: Utc::now() - Duration::days(1),
+        ended_at: Some(Utc::now() - Duration::days(1) + Duration::minutes(30)),
+        source: WorkLogSource::Manual,
+    };
+    storage.add_work_log(&manual).unwrap();
+    let logs = storage.get_work_logs(&id).unwrap();
+    assert_eq!(logs.len(), 2);
+    assert_eq!(logs[0], manual, "oldest first");
+
+    let negative = WorkLog { minutes: -1, ..manual.clone() };
+    assert!(matches!(storage.add_work_log(&negative), Err(Error::Validation(_))));
+    let orphan = WorkLog { issue_id: "t-404".to_string(), ..manual };
+    assert!(matches!(storage.add_work_log(&orphan), Err(Error::Validation(_))));
+}
+
+fn close_sets_closed_at(storage: &mut Box<dyn Storage>) {
+    let id = new_issue(storage, "Closing", 2);
+    set_status(storage, &id, Status::InProgress).unwrap();
+    storage.close_issue(&id, "Done", ACTOR).unwrap();
+
+    let issue = storage.get_issue(&id).unwrap().unwrap();
+    assert_eq!(issue.status, Status::Closed);
+    assert!(issue.closed_at.is_some());
+    assert!(storage.get_work_logs(&id).unwrap()[0].ended_at.is_some());
+
+    let closed = storage.get_events(&id, 1).unwrap();
+    assert_eq!(closed[0].event_type, EventType::Closed);
+    assert_eq!(closed[0].comment.as_deref(), Some("Done"));
+}
+
+fn status_updates_track_closed_at(storage: &mut Box<dyn Storage>) {
+    let id = new_issue(storage, "Round trip", 2);
+    set_status(storage, &id, Status::Closed).unwrap();
+    assert!(storage.get_issue(&id).unwrap().unwrap().closed_at.is_some());
+
+    // Closing again keeps the original time
+    let closed_at = storage.get_issue(&id).unwrap().unwrap().closed_at;
+    set_status(storage, &id, Status::Closed).unwrap();
+    assert_eq!(storage.get_issue(&id).unwrap().unwrap().closed_at, closed_at);
+
+    set_status(storage, &id, Status::Open).unwrap();
+    assert!(storage.get_issue(&id).unwrap().unwrap().closed_at.is_none());
+
+    storage.close_issue(&id, "Done", ACTOR).unwrap();
+    set_status(storage, &id, Status::InProgress).unwrap();
+    assert!(storage.get_issue(&id).unwrap().unwrap().closed_at.is_none());
+}
+
+fn workflow_rules_apply(storage: &mut Box<dyn Storage>) {
+    let workflow = Workflow {
+        statuses: vec![WorkflowStatus { name: "review".to_string(), done: false }],
+        transitions: vec![
+            Transition { from: Status::Open, to: Status::InProgress },
+            Transition { from: Status::InProgress, to: Status::Custom("review".to_string()) },
+            Transition { from: Status::Custom("review".to_string()), to: Status::Closed },
+        ],
+    };
+    storage.set_workflow(&workflow).unwrap();
+    assert_eq!(storage.get_workflow().unwrap(), workflow);
+
+    let id = new_issue(storage, "Flow", 2);
+    assert!(matches!(set_status(storage, &id, Status::Custom("review".to_string())), Err(Error::Validation(_))));
+    assert!(matches!(set_status(storage, &id, Status::Custom("qa".to_string())), Err(Error::Validation(_))));
+    assert!(matches!(storage.close_issue(&id, "skip", ACTOR), Err(Error::Validation(_))));
+    assert_eq!(event_types(storage.as_ref(), &id), vec![EventType::Created], "failed changes leave no events");
+
+    set_status(storage, &id, Status::InProgress).unwrap();
+    set_status(storage, &id, Status::Custom("review".to_string())).unwrap();
+    storage.close_issue(&id, "shipped", ACTOR).unwrap();
+
+    // Imports replay state and skip the transition table
+    let other = new_issue(storage, "Imported", 2);
+    let replay = IssueUpdates { status: Some(Status::Custom("review".to_string())), bypass_workflow: true, ..Default::default() };
+    storage.update_issue(&other, &replay, ACTOR).unwrap();
+
+    let bad = Workflow { statuses: vec![WorkflowStatus { name: "open".to_string(), done: false }], transitions: vec![] };
+    assert!(matches!(storage.set_workflow(&bad), Err(Error::Validation(_))));
+}
+
+fn ready_and_blocked(storage: &mut Box<dyn Storage>) {
+    let a = new_issue(storage, "A", 1);
+    let b = new_issue(storage, "B", 2);
+    let c = new_issue(storage, "C", 3);
+    let d = new_issue(storage, "D", 0);
+    depend(storage, &b, &a, DependencyType::Blocks).unwrap();
+    depend(storage, &c, &a, DependencyType::Blocks).unwrap();
+    depend(storage, &c, &b, DependencyType::Blocks).unwrap();
+    depend(storage, &d, &a, DependencyType::Related).unwrap();
+
+    let ready = storage.get_ready_work(&WorkFilter::default()).unwrap();
+    assert_eq!(ids(&ready), vec![d.clone(), a.clone()], "related links don't block; priority order");
+
+    let blocked = storage.get_blocked_issues(&WorkFilter::default()).unwrap();
+    assert_eq!(blocked.len(), 2);
+    let blocked_c = blocked.iter().find(|bi| bi.issue.id == c).unwrap();
+    assert_eq!(blocked_c.blocked_by_count, 2);
+    assert_eq!(sorted(blocked_c.blocked_by.clone()), sorted(vec![a.clone(), b.clone()]));
+
+    storage.close_issue(&a, "done", ACTOR).unwrap();
+    let ready = storage.get_ready_work(&WorkFilter::default()).unwrap();
+    assert_eq!(ids(&ready), vec![d.clone(), b.clone()]);
+    let blocked = storage.get_blocked_issues(&WorkFilter::default()).unwrap();
+    assert_eq!(blocked.len(), 1);
+    assert_eq!(blocked[0].blocked_by, vec![b.clone()]);
+
+    // Only open issues are ready
+    set_status(storage, &d, Status::InProgress).unwrap();
+    let ready = storage.get_ready_work(&WorkFilter::default()).unwrap();
+    assert_eq!(ids(&ready), vec![b.clone()]);
+
+    let assigned = IssueUpdates { assignee: Some("alice".to_string()), ..Default::default() };
+    storage.update_issue(&c, &assigned, ACTOR).unwrap();
+    let filter = WorkFilter { assignee: Some("alice".to_string()), ..Default::default() };
+    assert!(storage.get_ready_work(&filter).unwrap().is_empty());
+    assert_eq!(storage.get_blocked_issues(&filter).unwrap().len(), 1);
+    let filter = WorkFilter { priority: Some(2), ..Default::default() };
+    assert_eq!(ids(&storage.get_ready_work(&filter).unwrap()), vec![b]);
+}
+
+fn custom_done_status_unblocks(storage: &mut Box<dyn Storage>) {
+    let workflow = Workflow {
+        statuses: vec![WorkflowStatus { name: "shipped".to_string(), done: true }],
+        transitions: vec![],
+    };
+    storage.set_workflow(&workflow).unwrap();
+
+    let blocker = new_issue(storage, "Blocker", 2);
+    let waiting = new_issue(storage, "Waiting", 2);
+    depend(storage, &waiting, &blocker, DependencyType::Blocks).unwrap();
+    assert_eq!(ids(&storage.get_ready_work(&WorkFilter::default()).unwrap()), vec![blocker.clone()]);
+
+    set_status(storage, &blocker, Status::Custom("shipped".to_string())).unwrap();
+    assert_eq!(ids(&storage.get_ready_work(&WorkFilter::default()).unwrap()), vec![waiting.clone()]);
+    assert!(storage.get_blocked_issues(&WorkFilter::default()).unwrap().is_empty());
+
+    // Done issues are never stale
+    let future = Utc::now() + Duration::minutes(1);
+    assert_eq!(ids(&storage.get_stale_issues(future, None).unwrap().into_iter().map(|s| s.issue).collect::<Vec<_>>()), vec![waiting]);
+}
+
+fn dependency_rules(storage: &mut Box<dyn Storage>) {
+    let a = new_issue(storage, "A", 2);
+    let b = new_issue(storage, "B", 2);
+
+    assert!(matches!(depend(storage, &a, &a, DependencyType::Blocks), Err(Error::Cycle(_))));
+
+    depend(storage, &a, &b, DependencyType::ParentChild).unwrap();
+    let err = depend(storage, &a, &b, DependencyType::Blocks).unwrap_err();
+    assert!(matches!(err, Error::Conflict(_)), "{:?}", err);
+    assert_eq!(err.to_string(), format!("dependency {} -> {} already exists", a, b));
+
+    let records = storage.get_dependency_records(&a).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].dep_type, DependencyType::ParentChild);
+    assert_eq!(records[0].created_by, ACTOR);
+    assert_eq!(ids(&storage.get_dependencies(&a).unwrap()), vec![b.clone()]);
+    assert_eq!(ids(&storage.get_dependents(&b).unwrap()), vec![a.clone()]);
+    assert_eq!(storage.get_all_dependency_records().unwrap().len(), 1);
+
+    storage.remove_dependency(&a, &b, ACTOR).unwrap();
+    assert!(storage.get_dependencies(&a).unwrap().is_empty());
+    // Removing a link that doesn't exist still records the attempt
+    storage.remove_dependency(&a, &b, ACTOR).unwrap();
+    assert_eq!(
+        event_types(storage.as_ref(), &a),
+        vec![EventType::Created, EventType::DependencyAdded, EventType::DependencyRemoved, EventType::DependencyRemoved]
+    );
+}
+
+fn cycle_detection(storage: &mut Box<dyn Storage>) {
+    let a = new_issue(storage, "A", 2);
+    let b = new_issue(storage, "B", 2);
+    let c = new_issue(storage, "C", 2);
+    let d = new_issue(storage, "D", 2);
+    depend(storage, &a, &b, DependencyType::Blocks).unwrap();
+    depend(storage, &b, &c, DependencyType::Blocks).unwrap();
+    depend(storage, &c, &d, DependencyType::Blocks).unwrap();
+    assert!(storage.detect_cycles().unwrap().is_empty());
+
+    depend(storage, &c, &a, DependencyType::Related).unwrap();
+    let cycles = storage.detect_cycles().unwrap();
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(sorted(ids(&cycles[0])), sorted(vec![a, b, c]));
+}
+
+fn dependency_tree(storage: &mut Box<dyn Storage>) {
+    let root = new_issue(storage, "Root", 2);
+    let child = new_issue(storage, "Child", 2);
+    let leaf = new_issue(storage, "Leaf", 2);
+    depend(storage, &root, &child, DependencyType::Blocks).unwrap();
+    depend(storage, &child, &leaf, DependencyType::Blocks).unwrap();
+    depend(storage, &leaf, &root, DependencyType::Blocks).unwrap();
+
+    let tree = storage.get_dependency_tree(&root, 10).unwrap();
+    let shape: Vec<(String, i32)> = tree.iter().map(|n| (n.issue.id.clone(), n.depth)).collect();
+    assert_eq!(shape, vec![(root.clone(), 0), (child.clone(), 1), (leaf, 2)], "cycles are visited once");
+
+    let shallow = storage.get_dependency_tree(&root, 2).unwrap();
+    assert_eq!(shallow.len(), 2);
+    assert!(!shallow[0].truncated);
+    assert!(shallow[1].truncated);
+    assert!(storage.get_dependency_tree("t-404", 5).unwrap().is_empty());
+}
+
+fn labels_and_search(storage: &mut Box<dyn Storage>) {
+    let a = new_issue(storage, "A", 1);
+    let b = new_issue(storage, "B", 2);
+    let c = new_issue(storage, "C", 2);
+    storage.add_label(&a, "ui", ACTOR).unwrap();
+    storage.add_label(&a, "bug", ACTOR).unwrap();
+    storage.add_label(&a, "bug", ACTOR).unwrap();
+    storage.add_label(&b, "backend", ACTOR).unwrap();
+    storage.add_label(&c, "ui", ACTOR).unwrap();
+    storage.remove_label(&c, "ui", ACTOR).unwrap();
+    storage.remove_label(&c, "never-added", ACTOR).unwrap();
+
+    assert_eq!(storage.get_labels(&a).unwrap(), vec!["bug".to_string(), "ui".to_string()]);
+    assert!(storage.get_labels(&c).unwrap().is_empty());
+    assert_eq!(ids(&storage.get_issues_by_label("ui").unwrap()), vec![a.clone()]);
+
+    let any_label = IssueFilter { labels: vec!["ui".to_string(), "backend".to_string()], ..Default::default() };
+    assert_eq!(ids(&storage.search_issues("", &any_label).unwrap()), vec![a.clone(), b.clone()]);
+
+    let updates = IssueUpdates {
+        issue_type: Some(IssueType::Bug),
+        assignee: Some("alice".to_string()),
+        milestone: Some(Some("v1".to_string())),
+        due_at: Some(Some(Utc::now() - Duration::hours(1))),
+        ..Default::default()
+    };
+    storage.update_issue(&c, &updates, ACTOR).unwrap();
+
+    let by_type = IssueFilter { issue_type: Some(IssueType::Bug), ..Default::default() };
+    assert_eq!(ids(&storage.search_issues("", &by_type).unwrap()), vec![c.clone()]);
+    let by_assignee = IssueFilter { assignee: Some("alice".to_string()), priority: Some(2), ..Default::default() };
+    assert_eq!(ids(&storage.search_issues("", &by_assignee).unwrap()), vec![c.clone()]);
+    let by_milestone = IssueFilter { milestone: Some("v1".to_string()), ..Default::default() };
+    assert_eq!(ids(&storage.search_issues("", &by_milestone).unwrap()), vec![c.clone()]);
+    let overdue = IssueFilter { overdue: true, ..Default::default() };
+    assert_eq!(ids(&storage.search_issues("", &overdue).unwrap()), vec![c.clone()]);
+
+    storage.close_issue(&c, "done", ACTOR).unwrap();
+    assert!(storage.search_issues("", &overdue).unwrap().is_empty());
+    let closed = IssueFilter { status: Some(Status::Closed), ..Default::default() };
+    assert_eq!(ids(&storage.search_issues("", &closed).unwrap()), vec![c.clone()]);
+    let closed_recently = IssueFilter { closed_after: Some(Utc::now() - Duration::minutes(1)), ..Default::default() };
+    assert_eq!(ids(&storage.search_issues("", &closed_recently).unwrap()), vec![c.clone()]);
+    let created_later = IssueFilter { created_after: Some(Utc::now() + Duration::minutes(1)), ..Default::default() };
+    assert!(storage.search_issues("", &created_later).unwrap().is_empty());
+
+    // Reopened issues drop out of the closed-date filters; closing through update brings them back
+    set_status(storage, &c, Status::Open).unwrap();
+    assert!(storage.search_issues("", &closed_recently).unwrap().is_empty());
+    let closed_earlier = IssueFilter { closed_before: Some(Utc::now() + Duration::minutes(1)), ..Default::default() };
+    assert!(storage.search_issues("", &closed_earlier).unwrap().is_empty());
+    set_status(storage, &c, Status::Closed).unwrap();
+    assert_eq!(ids(&storage.search_issues("", &closed_recently).unwrap()), vec![c.clone()]);
+    assert_eq!(ids(&storage.search_issues("", &closed_earlier).unwrap()), vec![c]);
+}
+
+fn sort_and_paginate(storage: &mut Box<dyn Storage>) {
+    let mut all = Vec::new();
+    for (title, priority) in [("delta", 2), ("Alpha", 1), ("charlie", 1), ("Bravo", 3)] {
+        all.push(new_issue(storage, title, priority));
+    }
+    for _ in 0..8 {
+        all.push(new_issue(storage, "zulu", 4));
+    }
+    let estimate = IssueUpdates { estimated_minutes: Some(Some(60)), ..Default::default() };
+    storage.update_issue(&all[3], &estimate, ACTOR).unwrap();
+
+    let titles = |storage: &dyn Storage, sort: Vec<SortKey>| -> Vec<String> {
+        let filter = IssueFilter { sort, limit: Some(4), ..Default::default() };
+        storage.search_issues("", &filter).unwrap().into_iter().map(|i| i.title).collect()
+    };
+    assert_eq!(titles(storage.as_ref(), vec![SortKey::asc(SortField::Title)]), vec!["Alpha", "Bravo", "charlie", "delta"]);
+    assert_eq!(titles(storage.as_ref(), vec![]), vec!["charlie", "Alpha", "delta", "Bravo"], "priority, then newest first");
+    assert_eq!(titles(storage.as_ref(), vec![SortKey::desc(SortField::Estimate)])[0], "Bravo", "missing values sort last");
+
+    // Numeric ID order: t-10 comes after t-9
+    let filter = IssueFilter { sort: vec![SortKey::desc(SortField::Id)], limit: Some(3), offset: Some(1), ..Default::default() };
+    assert_eq!(ids(&storage.search_issues("", &filter).unwrap()), vec!["t-11", "t-10", "t-9"]);
+
+    let filter = IssueFilter { sort: vec![SortKey::asc(SortField::Id)], offset: Some(10), ..Default::default() };
+    assert_eq!(ids(&storage.search_issues("", &filter).unwrap()), vec!["t-11", "t-12"]);
+    let work = WorkFilter { sort: vec![SortKey::asc(SortField::Id)], limit: Some(2), offset: Some(1), ..Default::default() };
+    assert_eq!(ids(&storage.get_ready_work(&work).unwrap()), vec!["t-2", "t-3"]);
+}
+
+fn events_and_comments(storage: &mut Box<dyn Storage>) {
+    let a = new_issue(storage, "A", 2);
+    let b = new_issue(storage, "B", 2);
+    let start = Utc::now();
+    storage.add_comment(&a, "alice", "first").unwrap();
+    storage.add_label(&a, "x", ACTOR).unwrap();
+    storage.add_comment(&a, "bob", "second").unwrap();
+
+    let latest = storage.get_events(&a, 2).unwrap();
+    assert_eq!(latest.len(), 2);
+    assert_eq!(latest[0].comment.as_deref(), Some("second"), "newest first");
+
+    let filter = EventFilter { since: Some(start), ..Default::default() };
+    assert_eq!(storage.get_all_events(&filter).unwrap().len(), 3);
+    let filter = EventFilter { event_types: vec![EventType::Commented], ..Default::default() };
+    let comments = storage.get_all_events(&filter).unwrap();
+    assert_eq!(comments.iter().map(|e| e.actor.as_str()).collect::<Vec<_>>(), vec!["alice", "bob"]);
+    let filter = EventFilter { actor: Some("alice".to_string()), ..Default::default() };
+    assert_eq!(storage.get_all_events(&filter).unwrap().len(), 1);
+    let filter = EventFilter { until: Some(start), ..Default::default() };
+    assert_eq!(storage.get_all_events(&filter).unwrap().len(), 2, "the two Created events");
+
+    assert_eq!(storage.move_comments(&a, &b).unwrap(), 2);
+    let moved = storage.get_events(&b, 10).unwrap();
+    assert_eq!(moved.iter().filter(|e| e.event_type == EventType::Commented).count(), 2);
+    assert_eq!(moved[0].actor, "bob", "moved comments keep actor and time");
+    assert_eq!(event_types(storage.as_ref(), &a), vec![EventType::Created, EventType::LabelAdded]);
+    assert_eq!(storage.move_comments(&a, &b).unwrap(), 0);
+}
+
+fn dirty_tracking(storage: &mut Box<dyn Storage>) {
+    let a = new_issue(storage, "A", 2);
+    let b = new_issue(storage, "B", 2);
+    storage.add_label(&a, "x", ACTOR).unwrap();
+    assert_eq!(storage.get_dirty_issues().unwrap(), vec![a.clone(), b.clone()]);
+
+    storage.clear_dirty_issues_by_id(std::slice::from_ref(&a)).unwrap();
+    assert_eq!(storage.get_dirty_issues().unwrap(), vec![b.clone()]);
+    storage.clear_dirty_issues().unwrap();
+    assert!(storage.get_dirty_issues().unwrap().is_empty());
+
+    // Comments don't change the exported issue
+    storage.add_comment(&a, ACTOR, "note").unwrap();
+    assert!(storage.get_dirty_issues().unwrap().is_empty());
+
+    depend(storage, &b, &a, DependencyType::Blocks).unwrap();
+    storage.close_issue(&a, "done", ACTOR).unwrap();
+    assert_eq!(storage.get_dirty_issues().unwrap(), vec![b, a]);
+}
+
+fn transactions_roll_back(storage: &mut Box<dyn Storage>) {
+    let kept = new_issue(storage, "Kept", 2);
+
+    let result: Result<()> = with_transaction(storage, |storage| {
+        new_issue(storage, "Discarded", 2);
+        storage.add_label(&kept, "temp", ACTOR)?;
+        storage.close_issue("t-404", "nope", ACTOR)
+    });
+    assert!(matches!(result, Err(Error::NotFound { .. })));
+    assert!(storage.get_issue("t-2").unwrap().is_none());
+    assert!(storage.get_labels(&kept).unwrap().is_empty());
+    assert_eq!(storage.get_dirty_issues().unwrap(), vec![kept.clone()]);
+    assert_eq!(storage.generate_id("t").unwrap(), "t-2", "the ID counter rolls back too");
+
+    // An inner rollback only undoes the inner work
+    storage.begin_transaction().unwrap();
+    storage.add_label(&kept, "outer", ACTOR).unwrap();
+    storage.begin_transaction().unwrap();
+    storage.add_label(&kept, "inner", ACTOR).unwrap();
+    storage.rollback_transaction().unwrap();
+    storage.commit_transaction().unwrap();
+    assert_eq!(storage.get_labels(&kept).unwrap(), vec!["outer".to_string()]);
+
+    // A failed write inside a transaction doesn't undo the earlier work
+    let result: Result<()> = with_transaction(storage, |storage| {
+        storage.add_label(&kept, "second", ACTOR)?;
+        assert!(depend(storage, &kept, &kept, DependencyType::Blocks).is_err());
+        assert!(storage.add_label("t-404", "x", ACTOR).is_err());
+        Ok(())
+    });
+    result.unwrap();
+    assert_eq!(storage.get_labels(&kept).unwrap(), vec!["outer".to_string(), "second".to_string()]);
+}
+
+fn id_generation(storage: &mut Box<dyn Storage>) {
+    assert_eq!(storage.generate_id("a").unwrap(), "a-1");
+    assert_eq!(storage.generate_id("a").unwrap(), "a-2");
+    assert_eq!(storage.generate_id("b").unwrap(), "b-1");
+    assert_eq!(storage.generate_id("a").unwrap(), "a-3");
+}
+
+fn statistics(storage: &mut Box<dyn Storage>) {
+    let a = new_issue(storage, "A", 2);
+    let b = new_issue(storage, "B", 2);
+    let c = new_issue(storage, "C", 2);
+    let d = new_issue(storage, "D", 2);
+    depend(storage, &b, &a, DependencyType::Blocks).unwrap();
+    set_status(storage, &c, Status::InProgress).unwrap();
+
+    let due = IssueUpdates { due_at: Some(Some(Utc::now() + Duration::days(2))), ..Default::default() };
+    storage.update_issue(&a, &due, ACTOR).unwrap();
+    let overdue = IssueUpdates { due_at: Some(Some(Utc::now() - Duration::days(2))), ..Default::default() };
+    storage.update_issue(&b, &overdue, ACTOR).unwrap();
+
+    let estimated = IssueUpdates { estimated_minutes: Some(Some(60)), assignee: Some("alice".to_string()), ..Default::default() };
+    storage.update_issue(&d, &estimated, ACTOR).unwrap();
+    let log = WorkLog {
+        issue_id: d.clone(),
+        actor: "alice".to_string(),
+        minutes: 90,
+        note: String::new(),
+        started_at: Utc::now() - Duration::hours(2),
+        ended_at: Some(Utc::now()),
+        source: WorkLogSource::Manual,
+    };
+    storage.add_work_log(&log).unwrap();
+    storage.close_issue(&d, "done", ACTOR).unwrap();
+
+    let stats = storage.get_statistics().unwrap();
+    assert_eq!(stats.total_issues, 4);
+    assert_eq!(stats.open_issues, 2);
+    assert_eq!(stats.in_progress_issues, 1);
+    assert_eq!(stats.closed_issues, 1);
+    assert_eq!(stats.blocked_issues, 1);
+    assert_eq!(stats.ready_issues, 1);
+    assert!(stats.average_lead_time_hours >= 0.0 && stats.average_lead_time_hours < 0.1);
+    assert_eq!(stats.overdue_issues, 1);
+    assert_eq!(stats.due_soon_issues, 1);
+    assert_eq!(stats.next_due_at, storage.get_issue(&a).unwrap().unwrap().due_at);
+
+    let task = &stats.estimation_by_type["task"];
+    assert_eq!((task.issues, task.estimated_minutes, task.actual_minutes), (1, 60, 90));
+    assert!((task.ratio - 1.5).abs() < 1e-9);
+    assert_eq!(stats.estimation_by_actor["alice"].issues, 1);
+}
+
+fn milestones(storage: &mut Box<dyn Storage>) {
+    let now = Utc::now();
+    let milestone = |name: &str, target_date| Milestone {
+        name: name.to_string(),
+        description: String::new(),
+        target_date,
+        status: MilestoneStatus::Open,
+        created_at: now,
+        closed_at: None,
+    };
+    storage.save_milestone(&milestone("someday", None)).unwrap();
+    storage.save_milestone(&milestone("v2", Some(now + Duration::days(60)))).unwrap();
+    storage.save_milestone(&milestone("v1", Some(now + Duration::days(30)))).unwrap();
+    assert!(matches!(storage.save_milestone(&milestone(" ", None)), Err(Error::Validation(_))));
+
+    let names: Vec<String> = storage.list_milestones().unwrap().into_iter().map(|m| m.name).collect();
+    assert_eq!(names, vec!["v1", "v2", "someday"]);
+
+    let mut updated = milestone("v1", None);
+    updated.description = "first release".to_string();
+    storage.save_milestone(&updated).unwrap();
+    assert_eq!(storage.get_milestone("v1").unwrap().unwrap().description, "first release");
+    assert!(storage.get_milestone("v9").unwrap().is_none());
+
+    let a = new_issue(storage, "A", 2);
+    let b = new_issue(storage, "B", 2);
+    let c = new_issue(storage, "C", 2);
+    for (id, estimate) in [(&a, Some(30)), (&b, None), (&c, Some(45))] {
+        let updates = IssueUpdates { milestone: Some(Some("v1".to_string())), estimated_minutes: Some(estimate), ..Default::default() };
+        storage.update_issue(id, &updates, ACTOR).unwrap();
+    }
+    storage.close_issue(&c, "done", ACTOR).unwrap();
+
+    let report = storage.get_milestone_report("v1").unwrap().unwrap();
+    assert_eq!(report.total_issues, 3);
+    assert_eq!(report.open_issues, 2);
+    assert_eq!(report.closed_issues, 1);
+    assert_eq!(report.remaining_estimated_minutes, 30);
+    assert_eq!(report.unestimated_open_issues, 1);
+    assert!(storage.get_milestone_report("v9").unwrap().is_none());
+}
+
+fn recurrences(storage: &mut Box<dyn Storage>) {
+    let now = Utc::now();
+    let recurrence = |name: &str| Recurrence {
+        name: name.to_string(),
+        schedule: "every 1w".parse().unwrap(),
+        title: "Weekly review".to_string(),
+        description: String::new(),
+        issue_type: IssueType::Chore,
+        priority: 2,
+        labels: vec!["routine".to_string()],
+        assignee: String::new(),
+        start_at: now,
+        last_period: None,
+        created_at: now,
+    };
+    storage.save_recurrence(&recurrence("weekly")).unwrap();
+    storage.save_recurrence(&recurrence("audit")).unwrap();
+    let names: Vec<String> = storage.list_recurrences().unwrap().into_iter().map(|r| r.name).collect();
+    assert_eq!(names, vec!["audit", "weekly"]);
+    assert_eq!(storage.get_recurrence("weekly").unwrap().unwrap(), recurrence("weekly"));
+
+    assert!(storage.claim_recurrence_period("weekly", now).unwrap());
+    assert!(!storage.claim_recurrence_period("weekly", now).unwrap(), "a period is claimed once");
+    assert!(!storage.claim_recurrence_period("weekly", now - Duration::days(7)).unwrap());
+    assert!(storage.claim_recurrence_period("weekly", now + Duration::days(7)).unwrap());
+    assert!(!storage.claim_recurrence_period("missing", now).unwrap());
+
+    storage.delete_recurrence("audit").unwrap();
+    assert!(matches!(storage.delete_recurrence("audit"), Err(Error::NotFound { .. })));
+    assert_eq!(storage.list_recurrences().unwrap().len(), 1);
+
+    let id = new_issue(storage, "Weekly review", 2);
+    let reference = IssueUpdates { external_ref: Some(Some("recur:weekly@1".to_string())), ..Default::default() };
+    storage.update_issue(&id, &reference, ACTOR).unwrap();
+    assert_eq!(storage.get_issue_by_external_ref("recur:weekly@1").unwrap().map(|i| i.id), Some(id));
+    assert!(storage.get_issue_by_external_ref("recur:weekly@2").unwrap().is_none());
+}
+
+fn stale_issues(storage: &mut Box<dyn Storage>) {
+    let a = new_issue(storage, "A", 2);
+    let b = new_issue(storage, "B", 2);
+    let c = new_issue(storage, "C", 2);
+    storage.close_issue(&c, "done", ACTOR).unwrap();
+    set_status(storage, &b, Status::InProgress).unwrap();
+
+    let future = Utc::now() + Duration::minutes(1);
+    let stale = storage.get_stale_issues(future, None).unwrap();
+    assert_eq!(sorted(stale.iter().map(|s| s.issue.id.clone()).collect()), vec![a.clone(), b.clone()]);
+    assert!(stale.iter().all(|s| s.last_activity >= s.issue.updated_at));
+
+    let in_progress = storage.get_stale_issues(future, Some(Status::InProgress)).unwrap();
+    assert_eq!(in_progress.len(), 1);
+    assert_eq!(in_progress[0].issue.id, b);
+
+    let past = Utc::now() - Duration::minutes(1);
+    assert!(storage.get_stale_issues(past, None).unwrap().is_empty());
+}
+
+fn config_and_metadata(storage: &mut Box<dyn Storage>) {
+    assert!(storage.get_config("prefix").unwrap().is_none());
+    storage.set_config("prefix", "abc").unwrap();
+    storage.set_config("prefix", "xyz").unwrap();
+    assert_eq!(storage.get_config("prefix").unwrap().as_deref(), Some("xyz"));
+
+    storage.set_metadata("last_import_hash", "deadbeef").unwrap();
+    assert_eq!(storage.get_metadata("last_import_hash").unwrap().as_deref(), Some("deadbeef"));
+    assert!(storage.get_metadata("other").unwrap().is_none());
+    assert_eq!(storage.get_workflow().unwrap(), Workflow::default());
+
+    storage.set_config("workflow", "not json").unwrap();
+    assert!(matches!(storage.get_workflow(), Err(Error::Schema(_))));
+}
+
+fn archive_closed_issues(storage: &mut Box<dyn Storage>) {
+    let a = new_issue(storage, "A", 1);
+    let b = new_issue(storage, "B", 2);
+    let c = new_issue(storage, "C", 3);
+    depend(storage, &b, &a, DependencyType::Blocks).unwrap();
+    storage.add_label(&c, "old", ACTOR).unwrap();
+    storage.add_comment(&c, ACTOR, "wrapped up").unwrap();
+    storage.close_issue(&a, "done", ACTOR).unwrap();
+    storage.close_issue(&c, "done", ACTOR).unwrap();
+
+    let past = Utc::now() - Duration::minutes(1);
+    assert!(storage.archive_issues(past).unwrap().is_empty());
+
+    // The open B still depends on A, so only C moves
+    let future = Utc::now() + Duration::minutes(1);
+    assert_eq!(storage.archive_issues(future).unwrap(), vec![c.clone()]);
+    assert!(storage.get_issue(&c).unwrap().is_none());
+    assert!(storage.search_issues("", &IssueFilter::default()).unwrap().iter().all(|i| i.id != c));
+
+    let archived = storage.get_archived_issue(&c).unwrap().unwrap();
+    assert_eq!(archived.issue.status, Status::Closed);
+    assert_eq!(archived.labels, vec!["old".to_string()]);
+    assert!(archived.events.iter().any(|e| e.comment.as_deref() == Some("wrapped up")));
+
+    // Once B closes (here through update rather than close), A and B go together
+    set_status(storage, &b, Status::Closed).unwrap();
+    assert_eq!(sorted(storage.archive_issues(future).unwrap()), vec![a.clone(), b.clone()]);
+    assert!(storage.get_all_dependency_records().unwrap().is_empty());
+    assert_eq!(
+        ids(&storage.list_archived_issues().unwrap().into_iter().map(|a| a.issue).collect::<Vec<_>>()),
+        vec![a.clone(), b.clone(), c.clone()]
+    );
+
+    let filter = IssueFilter { priority: Some(3), ..Default::default() };
+    assert_eq!(ids(&storage.search_archived_issues("", &filter).unwrap()), vec![c.clone()]);
+    assert_eq!(storage.generate_id("t").unwrap(), "t-4");
+
+    // Saving an archived copy replaces any live issue with that ID
+    let d = new_issue(storage, "D", 2);
+    let mut copy = archived.clone();
+    copy.issue.id = d.clone();
+    storage.save_archived_issue(&copy).unwrap();
+    assert!(storage.get_issue(&d).unwrap().is_none());
+    assert_eq!(storage.get_archived_issue(&d).unwrap().unwrap().issue.title, "C");
+}
+
+fn compact_events_keeps_comments(storage: &mut Box<dyn Storage>) {
+    let a = new_issue(storage, "A", 2);
+    let b = new_issue(storage, "B", 2);
+    for title in ["A1", "A2", "A3"] {
+        let updates = IssueUpdates { title: Some(title.to_string()), ..Default::default() };
+        storage.update_issue(&a, &updates, ACTOR).unwrap();
+    }
+    storage.add_comment(&a, ACTOR, "keep me").unwrap();
+    for priority in [1, 0] {
+        let updates = IssueUpdates { priority: Some(priority), ..Default::default() };
+        storage.update_issue(&a, &updates, ACTOR).unwrap();
+    }
+    let updates = IssueUpdates { title: Some("B1".to_string()), ..Default::default() };
+    storage.update_issue(&b, &updates, ACTOR).unwrap();
+
+    let past = Utc::now() - Duration::minutes(1);
+    assert_eq!(storage.compact_events(past).unwrap(), 0, "recent events are kept");
+
+    let future = Utc::now() + Duration::minutes(1);
+    assert_eq!(storage.compact_events(future).unwrap(), 3);
+    assert_eq!(
+        event_types(storage.as_ref(), &a),
+        vec![EventType::Created, EventType::Updated, EventType::Commented, EventType::Updated]
+    );
+    assert_eq!(event_types(storage.as_ref(), &b), vec![EventType::Created, EventType::Updated]);
+    assert_eq!(storage.compact_events(future).unwrap(), 0);
+}